@@ -0,0 +1,246 @@
+//! Non-interactive fixes.
+//!
+//! Only suggestions with an unambiguous replacement are applied, all others
+//! are kept and reported together with the reason they were skipped.
+
+use super::*;
+
+use crate::vocabulary::edit_distance;
+use std::fmt;
+
+/// Frequent typos and their correction, in lowercase.
+const TYPOS: &[(&str, &str)] = &[
+    ("accomodate", "accommodate"),
+    ("acheive", "achieve"),
+    ("adress", "address"),
+    ("agressive", "aggressive"),
+    ("alot", "a lot"),
+    ("arguement", "argument"),
+    ("begining", "beginning"),
+    ("beleive", "believe"),
+    ("calender", "calendar"),
+    ("comming", "coming"),
+    ("commited", "committed"),
+    ("completly", "completely"),
+    ("definately", "definitely"),
+    ("dependancy", "dependency"),
+    ("enviroment", "environment"),
+    ("existance", "existence"),
+    ("explicitely", "explicitly"),
+    ("gaurantee", "guarantee"),
+    ("identifer", "identifier"),
+    ("independant", "independent"),
+    ("occured", "occurred"),
+    ("occurence", "occurrence"),
+    ("paramter", "parameter"),
+    ("preceeding", "preceding"),
+    ("recieve", "receive"),
+    ("recieved", "received"),
+    ("reponse", "response"),
+    ("retreive", "retrieve"),
+    ("seperate", "separate"),
+    ("seperator", "separator"),
+    ("succesful", "successful"),
+    ("sucess", "success"),
+    ("teh", "the"),
+    ("threshhold", "threshold"),
+    ("untill", "until"),
+    ("wich", "which"),
+];
+
+/// Why a suggestion was not applied automatically.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Skip {
+    /// There is nothing to replace the finding with.
+    NoReplacement,
+    /// Multiple replacements, none of which is a safe pick.
+    Ambiguous(usize),
+    /// The finding overlaps another one which was applied already.
+    Overlap,
+    /// The replacement can not be written to the doc comment.
+    Invalid(String),
+}
+
+impl fmt::Display for Skip {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoReplacement => formatter.write_str("there is no replacement"),
+            Self::Ambiguous(n) => write!(
+                formatter,
+                "{} replacements and the top one is not an obvious correction",
+                n
+            ),
+            Self::Overlap => formatter.write_str("it overlaps a fix applied before"),
+            Self::Invalid(reason) => formatter.write_str(reason),
+        }
+    }
+}
+
+/// Correction of a known typo, keeping the casing of the first character
+/// or of the whole word.
+fn known_typo(word: &str) -> Option<String> {
+    let lowercase = word.to_lowercase();
+    let (_, correction) = TYPOS.iter().find(|(typo, _)| *typo == lowercase)?;
+    let correction = if word.len() > 1 && word.chars().all(|c| c.is_uppercase()) {
+        correction.to_uppercase()
+    } else if word.chars().next().map_or(false, char::is_uppercase) {
+        let mut chars = correction.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        (*correction).to_owned()
+    };
+    Some(correction)
+}
+
+/// The replacement to apply for a suggestion, if the policy allows it.
+///
+/// A suggestion qualifies with exactly one replacement, if the spelling
+/// mistake is a known typo, or if the top spelling suggestion is a single
+/// edit away from the mistake.
+fn policy(suggestion: &Suggestion) -> std::result::Result<String, Skip> {
+    let spelling = match suggestion.detector {
+        Detector::Hunspell | Detector::Affix => true,
+        _ => false,
+    };
+    let word = util::sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+    if spelling {
+        if let Some(correction) = known_typo(&word) {
+            return Ok(correction);
+        }
+    }
+    match suggestion.replacements.as_slice() {
+        [] => Err(Skip::NoReplacement),
+        [single] => Ok(single.clone()),
+        [top, ..] if spelling && edit_distance(&word, top) == 1 => Ok(top.clone()),
+        replacements => Err(Skip::Ambiguous(replacements.len())),
+    }
+}
+
+/// Pick all suggestions which qualify for an automatic fix.
+///
+/// Returns the picked fixes, the number of suggestions they cover, and the
+/// skipped suggestions with the reason.
+pub(super) fn select<'s>(
+    suggestions_per_path: SuggestionSet<'s>,
+) -> (UserPicked, usize, Vec<(Suggestion<'s>, Skip)>) {
+    let mut picked = UserPicked::default();
+    let mut applied = 0usize;
+    let mut skipped = Vec::new();
+    for (origin, mut suggestions) in suggestions_per_path {
+        suggestions
+            .sort_by_key(|suggestion| (suggestion.span.start.line, suggestion.span.start.column));
+        let mut covered: Option<LineColumn> = None;
+        for suggestion in suggestions {
+            let start = suggestion.span.start;
+            if let Some(end) = covered {
+                if (start.line, start.column) <= (end.line, end.column) {
+                    skipped.push((suggestion, Skip::Overlap));
+                    continue;
+                }
+            }
+            let bandaids = policy(&suggestion).and_then(|replacement| {
+                BandAid::distribute(&suggestion, &replacement)
+                    .map_err(|e| Skip::Invalid(e.to_string()))
+            });
+            match bandaids {
+                Ok(bandaids) => {
+                    trace!("Applying {:?}", &bandaids);
+                    covered = Some(suggestion.span.end);
+                    applied += 1;
+                    picked
+                        .bandaids
+                        .entry(origin.clone())
+                        .or_insert_with(Vec::new)
+                        .push(bandaids);
+                }
+                Err(reason) => skipped.push((suggestion, reason)),
+            }
+        }
+    }
+    (picked, applied, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fluff_up;
+
+    #[test]
+    fn typos() {
+        assert_eq!(known_typo("teh"), Some("the".to_owned()));
+        assert_eq!(known_typo("Recieve"), Some("Receive".to_owned()));
+        assert_eq!(known_typo("SEPERATE"), Some("SEPARATE".to_owned()));
+        assert_eq!(known_typo("Alot"), Some("A lot".to_owned()));
+        assert_eq!(known_typo("unicorn"), None);
+    }
+
+    #[test]
+    fn pick_unambiguous() {
+        const TEXT: &str = fluff_up!["Teh unicrons recieve a colour in hte Morning."];
+        let docs = Documentation::from((ContentOrigin::TestEntityRust, TEXT));
+        let (origin, chunks) = docs.iter().next().expect("Must contain one origin");
+        let chunk = &chunks[0];
+        let suggestion = |detector: Detector, word: &str, replacements: &[&str]| {
+            let start = chunk.as_str().find(word).expect("Must contain the word");
+            let range = start..(start + word.len());
+            let (_, span) = chunk
+                .find_spans(range.clone())
+                .into_iter()
+                .next()
+                .expect("Must map to a span");
+            Suggestion {
+                detector,
+                origin: origin.clone(),
+                chunk,
+                span,
+                range,
+                replacements: replacements.iter().map(|s| (*s).to_owned()).collect(),
+                description: None,
+            }
+        };
+        let mut set = SuggestionSet::new();
+        set.extend(
+            origin.clone(),
+            vec![
+                // known typo, regardless of the suggestions
+                suggestion(Detector::Hunspell, "Teh", &[]),
+                // the top suggestion is too far away
+                suggestion(Detector::Hunspell, "unicrons", &["unicorn", "unicorns"]),
+                suggestion(Detector::Hunspell, "recieve", &["receive", "relieve"]),
+                // a single replacement
+                suggestion(Detector::Variants, "colour", &["color"]),
+                // the top suggestion is one transposition away
+                suggestion(Detector::Hunspell, "hte", &["the", "hate", "he"]),
+                // not a spelling mistake
+                suggestion(Detector::Typography, "Morning", &["morning", "mourning"]),
+            ],
+        );
+
+        let (picked, applied, skipped) = select(set);
+        assert_eq!(applied, 4);
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|(suggestion, reason)| (
+                    util::sub_chars(chunk.as_str(), suggestion.range.clone()),
+                    reason.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("unicrons".to_owned(), Skip::Ambiguous(2)),
+                ("Morning".to_owned(), Skip::Ambiguous(2)),
+            ]
+        );
+        let replacements = picked
+            .bandaids
+            .values()
+            .flatten()
+            .flatten()
+            .map(|bandaid| bandaid.replacement.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(replacements, vec!["The", "receive", "color", "the"]);
+    }
+}
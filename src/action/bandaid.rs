@@ -5,9 +5,10 @@
 
 use crate::span::Span;
 use crate::suggestion::Suggestion;
-use anyhow::{bail, Error, Result};
+use crate::util::sub_chars;
+use crate::Range;
+use anyhow::{bail, Result};
 use log::trace;
-use std::convert::TryFrom;
 
 /// A choosen sugestion for a certain span
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -35,11 +36,10 @@ impl BandAid {
             replacement: replacement.to_owned(),
         }
     }
-}
 
-impl<'s> TryFrom<(&Suggestion<'s>, usize)> for BandAid {
-    type Error = Error;
-    fn try_from((suggestion, pick_idx): (&Suggestion<'s>, usize)) -> Result<Self> {
+    /// Bandaids replacing the content of the suggestion with its
+    /// `pick_idx`th replacement.
+    pub fn picked(suggestion: &Suggestion<'_>, pick_idx: usize) -> Result<Vec<Self>> {
         let literal_file_span = suggestion.span;
         trace!(
             "proc_macro literal span of doc comment: ({},{})..({},{})",
@@ -50,17 +50,67 @@ impl<'s> TryFrom<(&Suggestion<'s>, usize)> for BandAid {
         );
 
         if let Some(replacement) = suggestion.replacements.iter().nth(pick_idx) {
-            Ok(Self::new(replacement.as_str(), &suggestion.span))
+            Self::distribute(suggestion, replacement.as_str())
         } else {
             bail!("Does not contain any replacements")
         }
     }
-}
 
-impl<'s> TryFrom<(Suggestion<'s>, usize)> for BandAid {
-    type Error = Error;
-    fn try_from((suggestion, pick_idx): (Suggestion<'s>, usize)) -> Result<Self> {
-        Self::try_from((&suggestion, pick_idx))
+    /// Bandaids replacing the content of the suggestion with `replacement`.
+    ///
+    /// A suggestion grouping the fragments of several lines gets one bandaid
+    /// per fragment and the words of the replacement are distributed across
+    /// them, so the `///` prefixes and the indentation in between are kept.
+    /// A replacement containing a newline carries its own line structure and
+    /// covers the whole span, which is only possible in literals which may
+    /// span multiple lines, but not in `///` or `//!` comments.
+    ///
+    /// The replacement is escaped according to the literal it is written to,
+    /// errors if it can not be represented there.
+    pub fn distribute(suggestion: &Suggestion<'_>, replacement: &str) -> Result<Vec<Self>> {
+        let chunk = suggestion.chunk;
+        let escape = |range: &Range, content: &str| match chunk.kind(range) {
+            Some(kind) => kind.escape(content),
+            None => Ok(content.to_owned()),
+        };
+        let fragments = chunk.find_spans(suggestion.range.clone());
+        if fragments.len() < 2 || replacement.contains('\n') {
+            let replacement = escape(&suggestion.range, replacement)?;
+            return Ok(vec![Self::new(&replacement, &suggestion.span)]);
+        }
+        let last = fragments.len() - 1;
+        let mut words = replacement.split_whitespace().peekable();
+        fragments
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (range, span))| {
+                let original = sub_chars(chunk.as_str(), range.clone());
+                let available = original.trim().chars().count();
+                // every fragment but the last one is filled up to its original length
+                let mut fill = String::new();
+                while let Some(word) = words.peek() {
+                    let len = fill.chars().count() + 1 + word.chars().count();
+                    if idx != last && !fill.is_empty() && len > available {
+                        break;
+                    }
+                    if !fill.is_empty() {
+                        fill.push(' ');
+                    }
+                    fill.push_str(word);
+                    words.next();
+                }
+                if fill.is_empty() {
+                    return Ok(Self::new("", &span));
+                }
+                let fill = escape(&range, &fill)?;
+                let leading = &original[..(original.len() - original.trim_start().len())];
+                let trailing = &original[original.trim_end().len()..];
+                Ok(Self::new(
+                    &format!("{}{}{}", leading, fill, trailing),
+                    &span,
+                ))
+            })
+            .collect()
     }
 }
 
@@ -0,0 +1,366 @@
+//! Render fixes as a unified diff.
+//!
+//! The output is accepted by `git apply` and `patch -p1`.
+
+use std::path::Path;
+
+/// Number of unchanged lines surrounding every change.
+const CONTEXT: usize = 3;
+
+/// A single line operation turning the original into the modified content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Keep,
+    Remove,
+    Insert,
+}
+
+/// Split the content into lines, each including its terminating `\n`
+/// if there is one.
+fn lines(content: &str) -> Vec<&str> {
+    let mut lines = Vec::with_capacity(content.len() / 32 + 1);
+    let mut rest = content;
+    while let Some(idx) = rest.find('\n') {
+        let (line, tail) = rest.split_at(idx + 1);
+        lines.push(line);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        lines.push(rest);
+    }
+    lines
+}
+
+/// Shortest edit script between `a` and `b`, according to Myers' algorithm.
+fn edits(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    // the common prefix and suffix do not need to go through the search
+    let prefix = a.iter().zip(b.iter()).take_while(|(a, b)| a == b).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let n = a_mid.len() as isize;
+    let m = b_mid.len() as isize;
+    let max = n + m;
+    let offset = max + 1;
+    let idx = |k: isize| (k + offset) as usize;
+
+    // the furthest reaching `x` per diagonal `k`, recorded for every `d`
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    let mut trace = Vec::new();
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        let mut k = -d;
+        while k <= d {
+            let mut x = if k == -d || (k != d && v[idx(k - 1)] < v[idx(k + 1)]) {
+                v[idx(k + 1)]
+            } else {
+                v[idx(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a_mid[x as usize] == b_mid[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+
+    let mut script = Vec::with_capacity(a.len().max(b.len()));
+    script.extend(std::iter::repeat(Edit::Keep).take(suffix));
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[idx(k - 1)] < v[idx(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[idx(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            script.push(Edit::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            script.push(if x == prev_x {
+                Edit::Insert
+            } else {
+                Edit::Remove
+            });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    script.extend(std::iter::repeat(Edit::Keep).take(prefix));
+    script.reverse();
+    script
+}
+
+/// Format a hunk range, an empty range refers to the line before.
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        len => format!("{},{}", start + 1, len),
+    }
+}
+
+/// Write a line of the hunk, noting a missing final newline.
+fn line(hunk: &mut String, marker: char, line: &str) {
+    hunk.push(marker);
+    hunk.push_str(line);
+    if !line.ends_with('\n') {
+        hunk.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// Render the changes from `original` to `modified` of the file at `path`
+/// as unified diff, `path` is used as is for both sides and must be relative
+/// to the directory the patch is applied in.
+///
+/// Returns an empty string if there are no changes.
+pub fn unified(path: &Path, original: &str, modified: &str) -> String {
+    debug_assert!(path.is_relative(), "Patches only refer to relative paths");
+    let a = lines(original);
+    let b = lines(modified);
+    let script = edits(&a, &b);
+
+    // indices into the script of all changes
+    let changes = script
+        .iter()
+        .enumerate()
+        .filter(|(_, edit)| **edit != Edit::Keep)
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // changes with overlapping context end up in the same hunk
+    let mut hunks = Vec::<(usize, usize)>::new();
+    for idx in changes {
+        let start = idx.saturating_sub(CONTEXT);
+        let end = (idx + CONTEXT + 1).min(script.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let path = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let mut diff = format!(
+        "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n",
+        path = path
+    );
+
+    // position in the original and the modified lines
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut cursor = 0usize;
+    for (start, end) in hunks {
+        for edit in &script[cursor..start] {
+            match edit {
+                Edit::Keep => {
+                    x += 1;
+                    y += 1;
+                }
+                Edit::Remove => x += 1,
+                Edit::Insert => y += 1,
+            }
+        }
+        let removed = script[start..end]
+            .iter()
+            .filter(|edit| **edit != Edit::Insert)
+            .count();
+        let inserted = script[start..end]
+            .iter()
+            .filter(|edit| **edit != Edit::Remove)
+            .count();
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(x, removed),
+            range(y, inserted)
+        ));
+        for edit in &script[start..end] {
+            match edit {
+                Edit::Keep => {
+                    line(&mut diff, ' ', a[x]);
+                    x += 1;
+                    y += 1;
+                }
+                Edit::Remove => {
+                    line(&mut diff, '-', a[x]);
+                    x += 1;
+                }
+                Edit::Insert => {
+                    line(&mut diff, '+', b[y]);
+                    y += 1;
+                }
+            }
+        }
+        cursor = end;
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+
+    /// Apply the diff with `git apply` and return the patched content.
+    fn git_apply(name: &str, original: &str, modified: &str) -> String {
+        let dir =
+            std::env::temp_dir().join(format!("spellcheck-diff-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("x.rs"), original).unwrap();
+        fs::write(
+            dir.join("fix.patch"),
+            unified(Path::new("x.rs"), original, modified),
+        )
+        .unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(&dir)
+                .output()
+                .expect("Must be able to run git");
+            assert!(
+                output.status.success(),
+                "git {:?} failed: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        };
+        git(&["apply", "--check", "fix.patch"]);
+        git(&["apply", "fix.patch"]);
+        let patched = fs::read_to_string(dir.join("x.rs")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        patched
+    }
+
+    #[test]
+    fn single_hunk() {
+        let original = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let modified = "a\nb\nc\nd\nE\nf\ng\nh\n";
+        assert_eq!(
+            unified(Path::new("src/lib.rs"), original, modified),
+            r#"diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -2,7 +2,7 @@
+ b
+ c
+ d
+-e
++E
+ f
+ g
+ h
+"#
+        );
+    }
+
+    #[test]
+    fn separate_hunks() {
+        let original = (1..=20).map(|i| format!("{}\n", i)).collect::<String>();
+        let modified = (1..=20)
+            .map(|i| match i {
+                2 => "two\n".to_owned(),
+                18 => "18\n18½\n".to_owned(),
+                i => format!("{}\n", i),
+            })
+            .collect::<String>();
+        assert_eq!(
+            unified(Path::new("x.md"), &original, &modified),
+            r#"diff --git a/x.md b/x.md
+--- a/x.md
++++ b/x.md
+@@ -1,5 +1,5 @@
+ 1
+-2
++two
+ 3
+ 4
+ 5
+@@ -16,5 +16,6 @@
+ 16
+ 17
+ 18
++18½
+ 19
+ 20
+"#
+        );
+    }
+
+    #[test]
+    fn missing_final_newline() {
+        assert_eq!(
+            unified(Path::new("x.rs"), "a\r\nb", "A\r\nb"),
+            "diff --git a/x.rs b/x.rs\n--- a/x.rs\n+++ b/x.rs\n@@ -1,2 +1,2 @@\n-a\r\n+A\r\n b\n\\ No newline at end of file\n"
+        );
+        assert_eq!(unified(Path::new("x.rs"), "a\nb", "a\nb"), "");
+    }
+
+    #[test]
+    fn git_applies_line_endings() {
+        let original = "a\r\nb\r\nc\r\nd\r\ne\r\nf\r\n";
+        let modified = "a\r\nb\r\nC\r\nd\r\ne\r\nf\r\n";
+        assert_eq!(git_apply("crlf", original, modified), modified);
+    }
+
+    #[test]
+    fn git_applies_missing_final_newline() {
+        for (name, original, modified) in &[
+            ("keep", "a\nb\nc", "A\nb\nc"),
+            ("change", "a\nb\nc", "a\nb\nC"),
+            ("crlf", "a\r\nb\r\nc", "a\r\nB\r\nc"),
+        ] {
+            assert_eq!(&git_apply(name, original, modified), modified);
+        }
+    }
+
+    #[test]
+    fn edit_script() {
+        let a = lines("a\nb\nc\na\nb\nb\na\n");
+        let b = lines("c\nb\na\nb\na\nc\n");
+        let script = edits(&a, &b);
+        assert_eq!(script.iter().filter(|e| **e == Edit::Keep).count(), 4);
+        // replaying the script yields the modified lines
+        let mut x = 0;
+        let mut replayed = Vec::new();
+        let mut y = 0;
+        for edit in script {
+            match edit {
+                Edit::Keep => {
+                    assert_eq!(a[x], b[y]);
+                    replayed.push(a[x]);
+                    x += 1;
+                    y += 1;
+                }
+                Edit::Remove => x += 1,
+                Edit::Insert => {
+                    replayed.push(b[y]);
+                    y += 1;
+                }
+            }
+        }
+        assert_eq!(replayed, b);
+    }
+}
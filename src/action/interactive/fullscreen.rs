@@ -0,0 +1,492 @@
+//! Full-screen selection of replacements.
+//!
+//! The screen is split into a sidebar with the files and their number of
+//! reviewed and total findings, a preview of the source around the current
+//! finding, the replacements to pick from, and a status bar. Keys have the
+//! same meaning as in line mode, `PageUp` and `PageDown` scroll the preview.
+
+use super::*;
+
+use crossterm::style::Print;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Number of lines the preview is scrolled by per key.
+const SCROLL: isize = 5;
+
+/// Set while the alternate screen of the terminal is in use.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Leave the alternate screen, if the full screen is in use.
+///
+/// Required when terminating on a signal.
+pub fn restore_screen() -> Result<()> {
+    if ACTIVE.swap(false, Ordering::SeqCst) {
+        stdout().queue(terminal::LeaveAlternateScreen)?;
+        stdout().flush()?;
+    }
+    Ok(())
+}
+
+/// The findings of one origin and the decisions made so far.
+struct Review<'s> {
+    origin: ContentOrigin,
+    suggestions: Vec<Suggestion<'s>>,
+    /// `None` until decided upon.
+    decisions: Vec<Option<Decision>>,
+}
+
+impl<'s> Review<'s> {
+    /// Number of findings decided upon.
+    fn reviewed(&self) -> usize {
+        self.decisions
+            .iter()
+            .filter(|decision| decision.is_some())
+            .count()
+    }
+
+    /// The first finding not decided upon, starting at `idx`.
+    fn pending(&self, idx: usize) -> usize {
+        (idx..self.decisions.len())
+            .find(|idx| self.decisions[*idx].is_none())
+            .unwrap_or(self.decisions.len())
+    }
+}
+
+/// At most `width` characters of `text`.
+fn fit(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+/// At most `width` characters of `text`, the start is cut off if necessary.
+fn fit_tail(text: &str, width: usize) -> String {
+    let count = text.chars().count();
+    if count <= width {
+        text.to_owned()
+    } else if width == 0 {
+        String::new()
+    } else {
+        std::iter::once('…')
+            .chain(text.chars().skip(count - width + 1))
+            .collect()
+    }
+}
+
+/// The characters `from..to` of `chars`, limited to the visible `window`.
+fn segment(chars: &[char], from: usize, to: usize, window: Range) -> String {
+    let from = from.max(window.start).min(chars.len());
+    let to = to.min(window.end).min(chars.len());
+    if from < to {
+        chars[from..to].iter().collect()
+    } else {
+        String::new()
+    }
+}
+
+/// Everything drawn besides the findings themselves.
+#[derive(Debug, Default)]
+struct Screen {
+    /// Lines of the files shown in the preview.
+    sources: HashMap<PathBuf, Vec<String>>,
+    /// Lines the preview is scrolled by, relative to the finding.
+    scroll: isize,
+    /// Show the help instead of the preview.
+    help: bool,
+}
+
+impl Screen {
+    /// Lines of the file at `path`, empty if it can not be read.
+    fn source(&mut self, path: &Path) -> &[String] {
+        self.sources.entry(path.to_owned()).or_insert_with(|| {
+            fs::read_to_string(path)
+                .map(|content| {
+                    content
+                        .lines()
+                        .map(|line| line.replace('\t', " "))
+                        .collect()
+                })
+                .unwrap_or_else(|e| {
+                    debug!("Failed to read {} for the preview: {}", path.display(), e);
+                    Vec::new()
+                })
+        })
+    }
+
+    /// Go through all findings until all are decided upon or the user aborts.
+    fn review(
+        &mut self,
+        reviews: &mut [Review],
+        terminal: &mut dyn Terminal,
+        session: &mut Session,
+    ) -> Result<UserSelection> {
+        for file in 0..reviews.len() {
+            let mut idx = reviews[file].pending(0);
+            while idx < reviews[file].suggestions.len() {
+                let selection = {
+                    let mut state = State::from(&reviews[file].suggestions[idx]);
+                    self.scroll = 0;
+                    loop {
+                        self.draw(reviews, (file, idx), &state, terminal)?;
+                        let event = match terminal.read()? {
+                            Event::Key(event) => event,
+                            sth => {
+                                // a resize only requires drawing again
+                                trace!("read() something other than a key: {:?}", sth);
+                                continue;
+                            }
+                        };
+                        self.help = false;
+                        let pick = match event.code {
+                            KeyCode::PageUp => {
+                                self.scroll -= SCROLL;
+                                UserSelection::Nop
+                            }
+                            KeyCode::PageDown => {
+                                self.scroll += SCROLL;
+                                UserSelection::Nop
+                            }
+                            _ if state.is_custom_entry() => {
+                                UserPicked::enter_custom_replacement(&mut state, event)?
+                            }
+                            _ => UserPicked::pick(&mut state, event),
+                        };
+                        match pick {
+                            UserSelection::Nop => continue,
+                            UserSelection::Help => self.help = true,
+                            other => break other,
+                        }
+                    }
+                };
+                let review = &mut reviews[file];
+                let decision = match selection {
+                    UserSelection::Replacement(bandaids) => Decision::Accepted(bandaids),
+                    UserSelection::Skip => Decision::Skipped,
+                    UserSelection::Previous => {
+                        idx = idx.saturating_sub(1);
+                        continue;
+                    }
+                    UserSelection::SkipFile => {
+                        // the remaining findings of the file are ignored just the same
+                        for (suggestion, decision) in review
+                            .suggestions
+                            .iter()
+                            .zip(review.decisions.iter_mut())
+                            .skip(idx)
+                            .filter(|(_, decision)| decision.is_none())
+                        {
+                            session.decide(suggestion, Decision::Ignored);
+                            *decision = Some(Decision::Ignored);
+                        }
+                        session.save()?;
+                        break;
+                    }
+                    UserSelection::Abort => return Ok(UserSelection::Abort),
+                    UserSelection::Help | UserSelection::Nop => {
+                        unreachable!("Help and Nop are handled before")
+                    }
+                };
+                session.record(&review.suggestions[idx], decision.clone())?;
+                review.decisions[idx] = Some(decision);
+                idx = review.pending(idx + 1);
+            }
+        }
+        Ok(UserSelection::Nop)
+    }
+
+    /// Draw the whole screen for the finding `idx` of the file `file`.
+    fn draw(
+        &mut self,
+        reviews: &[Review],
+        (file, idx): (usize, usize),
+        state: &State,
+        terminal: &mut dyn Terminal,
+    ) -> Result<()> {
+        let (columns, rows) = terminal.size().unwrap_or((80, 24));
+        let mut out = terminal.sink();
+        out.queue(cursor::Hide)?
+            .queue(terminal::Clear(terminal::ClearType::All))?;
+        if columns < 20 || rows < 6 {
+            out.queue(cursor::MoveTo(0, 0))?
+                .queue(Print(fit("Terminal too small", columns as usize)))?;
+            out.flush()?;
+            return Ok(());
+        }
+
+        let bold = ContentStyle::new().attribute(Attribute::Bold);
+        let current = ContentStyle::new()
+            .foreground(Color::Green)
+            .attribute(Attribute::Bold);
+        let mistake = ContentStyle::new()
+            .foreground(Color::Red)
+            .attribute(Attribute::Bold)
+            .attribute(Attribute::Underlined);
+        let heading = ContentStyle::new()
+            .foreground(Color::Blue)
+            .attribute(Attribute::Bold);
+        let highlight = ContentStyle::new()
+            .background(Color::Black)
+            .foreground(Color::Green)
+            .attribute(Attribute::Bold);
+        let others = ContentStyle::new()
+            .background(Color::Black)
+            .foreground(Color::Blue);
+        let custom = ContentStyle::new()
+            .background(Color::Black)
+            .foreground(Color::Yellow);
+        let status_style = ContentStyle::new().attribute(Attribute::Reverse);
+
+        // layout, the sidebar is only shown if there is enough room
+        let sidebar = if columns >= 60 {
+            (columns / 4).min(32)
+        } else {
+            0
+        };
+        let left = if sidebar > 0 { sidebar + 2 } else { 0 };
+        let width = (columns - left) as usize;
+        let status = rows - 1;
+        let list = (state.n_items as u16 + 1).min(rows / 3).max(2);
+        let preview = status - list;
+
+        // files with the number of reviewed and total findings
+        if sidebar > 0 {
+            let first = (file + 1).saturating_sub(status as usize);
+            for row in 0..status {
+                out.queue(cursor::MoveTo(sidebar, row))?.queue(Print('│'))?;
+                let review = match reviews.get(first + row as usize) {
+                    Some(review) => review,
+                    None => continue,
+                };
+                let counts = format!(" {}/{}", review.reviewed(), review.suggestions.len());
+                let name = fit_tail(
+                    &review.origin.to_string(),
+                    (sidebar as usize).saturating_sub(counts.len() + 2),
+                );
+                let entry = format!(
+                    "{:<name_width$}{}",
+                    name,
+                    counts,
+                    name_width = (sidebar as usize).saturating_sub(counts.len() + 2)
+                );
+                out.queue(cursor::MoveTo(0, row))?;
+                if first + row as usize == file {
+                    out.queue(PrintStyledContent(StyledContent::new(current, '»')))?
+                        .queue(cursor::MoveTo(2, row))?
+                        .queue(PrintStyledContent(StyledContent::new(bold, entry)))?;
+                } else {
+                    out.queue(cursor::MoveTo(2, row))?.queue(Print(entry))?;
+                }
+            }
+        }
+
+        let review = &reviews[file];
+        let suggestion = &review.suggestions[idx];
+        let span = suggestion.origin.absolute_span(suggestion.span);
+
+        // the source around the finding, or the help
+        if self.help {
+            for (row, line) in HELP.lines().take(preview as usize).enumerate() {
+                out.queue(cursor::MoveTo(left, row as u16))?
+                    .queue(Print(fit(line, width)))?;
+            }
+        } else {
+            let scroll = self.scroll;
+            let lines = self.source(suggestion.origin.as_path());
+            let first = (span.start.line as isize - preview as isize / 2 + scroll).max(1) as usize;
+            let last = (first + preview as usize - 1).min(lines.len()).max(first);
+            let digits = last.to_string().len();
+            let text_width = width.saturating_sub(digits + 3);
+            // long lines are shifted, so the end of the finding is visible
+            let shift = (span.end.column + 1).saturating_sub(text_width);
+            let window = shift..(shift + text_width);
+            for (row, lineno) in (first..=last).enumerate() {
+                let chars = match lines.get(lineno - 1) {
+                    Some(line) => line.chars().collect::<Vec<_>>(),
+                    None => break,
+                };
+                let gutter = format!("{:>digits$} │ ", lineno, digits = digits);
+                out.queue(cursor::MoveTo(left, row as u16))?;
+                if lineno < span.start.line || lineno > span.end.line {
+                    out.queue(Print(gutter))?.queue(Print(segment(
+                        &chars,
+                        0,
+                        chars.len(),
+                        window.clone(),
+                    )))?;
+                    continue;
+                }
+                let from = if lineno == span.start.line {
+                    span.start.column
+                } else {
+                    0
+                };
+                let to = if lineno == span.end.line {
+                    span.end.column + 1
+                } else {
+                    chars.len()
+                };
+                out.queue(PrintStyledContent(StyledContent::new(bold, gutter)))?
+                    .queue(Print(segment(&chars, 0, from, window.clone())))?
+                    .queue(PrintStyledContent(StyledContent::new(
+                        mistake,
+                        segment(&chars, from, to, window.clone()),
+                    )))?
+                    .queue(Print(segment(&chars, to, chars.len(), window.clone())))?;
+            }
+        }
+
+        // the replacements, listed bottom up as in line mode
+        let title = match suggestion.description.as_ref() {
+            Some(description) => format!("── {} ── {}", suggestion.detector, description),
+            None => format!("── {} ──", suggestion.detector),
+        };
+        out.queue(cursor::MoveTo(left, preview))?
+            .queue(PrintStyledContent(StyledContent::new(
+                heading,
+                fit(&title, width),
+            )))?;
+        let slots = (list - 1) as usize;
+        let position = |item: usize| (item + 1) % state.n_items;
+        let lowest = position(state.pick_idx).saturating_sub(slots - 1);
+        for item in 0..state.n_items {
+            let pos = position(item);
+            if pos < lowest || pos >= lowest + slots {
+                continue;
+            }
+            let row = status - 1 - (pos - lowest) as u16;
+            if item == state.pick_idx {
+                out.queue(cursor::MoveTo(left + 2, row))?
+                    .queue(PrintStyledContent(StyledContent::new(current, '»')))?;
+            }
+            let text_width = width.saturating_sub(4);
+            out.queue(cursor::MoveTo(left + 4, row))?;
+            if item + 1 == state.n_items {
+                let content = if state.custom_replacement.is_empty() {
+                    "..."
+                } else {
+                    state.custom_replacement.as_str()
+                };
+                out.queue(PrintStyledContent(StyledContent::new(
+                    custom,
+                    fit(content, text_width),
+                )))?;
+            } else {
+                let style = if item == state.pick_idx {
+                    highlight
+                } else {
+                    others
+                };
+                out.queue(PrintStyledContent(StyledContent::new(
+                    style,
+                    fit(&suggestion.replacements[item], text_width),
+                )))?;
+            }
+        }
+
+        // where we are and what can be done
+        let total = reviews
+            .iter()
+            .map(|review| review.suggestions.len())
+            .sum::<usize>();
+        let reviewed = reviews.iter().map(Review::reviewed).sum::<usize>();
+        let status_line = format!(
+            " ({}/{}) {}:{}  {} of {} reviewed  [y,n,q,d,j,e,?] PgUp/PgDn scroll",
+            idx + 1,
+            review.suggestions.len(),
+            review.origin,
+            span.start.line,
+            reviewed,
+            total,
+        );
+        out.queue(cursor::MoveTo(0, status))?
+            .queue(PrintStyledContent(StyledContent::new(
+                status_style,
+                format!(
+                    "{:<columns$}",
+                    fit(&status_line, columns as usize),
+                    columns = columns as usize
+                ),
+            )))?;
+
+        if state.is_custom_entry() {
+            out.queue(cursor::MoveTo(left + 4 + state.cursor_offset, status - 1))?
+                .queue(cursor::Show)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Let the user pick the replacements on the full screen.
+pub(super) fn select<'s>(
+    suggestions_per_path: SuggestionSet<'s>,
+    terminal: &mut dyn Terminal,
+    session: &mut Session,
+) -> Result<(UserPicked, UserSelection)> {
+    let mut reviews = suggestions_per_path
+        .into_iter()
+        .filter_map(|(origin, suggestions)| {
+            let suggestions = suggestions
+                .into_iter()
+                .filter(|suggestion| !suggestion.replacements.is_empty())
+                .collect::<Vec<_>>();
+            if suggestions.is_empty() {
+                None
+            } else {
+                // decided upon in the session which is resumed
+                let decisions = suggestions
+                    .iter()
+                    .map(|suggestion| session.decision(suggestion).cloned())
+                    .collect();
+                Some(Review {
+                    origin,
+                    decisions,
+                    suggestions,
+                })
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let selection = {
+        let _guard = raw(terminal);
+        if terminal.raw_mode() {
+            ACTIVE.store(true, Ordering::SeqCst);
+        }
+        terminal.sink().queue(terminal::EnterAlternateScreen)?;
+        let selection = Screen::default().review(&mut reviews, terminal, session);
+        let left = terminal
+            .sink()
+            .queue(cursor::Show)
+            .and_then(|out| out.queue(terminal::LeaveAlternateScreen))
+            .map_err(anyhow::Error::from)
+            .and_then(|out| out.flush().map_err(anyhow::Error::from));
+        ACTIVE.store(false, Ordering::SeqCst);
+        left?;
+        selection?
+    };
+
+    let mut picked = UserPicked::default();
+    for review in reviews {
+        for decision in review.decisions {
+            if let Some(Decision::Accepted(bandaids)) = decision {
+                picked.add_bandaids(&review.origin, bandaids);
+            }
+        }
+    }
+    Ok((picked, selection))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_text() {
+        assert_eq!(fit("spellcheck", 5), "spell");
+        assert_eq!(fit_tail("src/lib.rs", 20), "src/lib.rs");
+        assert_eq!(fit_tail("src/nested/lib.rs", 8), "…/lib.rs");
+        let chars = "abcdef".chars().collect::<Vec<_>>();
+        assert_eq!(segment(&chars, 1, 4, 2..10), "cd");
+        assert_eq!(segment(&chars, 4, 2, 0..10), "");
+    }
+}
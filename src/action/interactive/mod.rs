@@ -13,7 +13,10 @@ use crossterm::{
     terminal, QueueableCommand,
 };
 
-use std::convert::TryFrom;
+use super::terminal::Terminal;
+
+pub mod fullscreen;
+
 use std::io::stdout;
 
 const HELP: &'static str = r##"y - apply this suggestion
@@ -60,6 +63,15 @@ impl Drop for ScopedRaw {
     }
 }
 
+/// Enter raw mode, if the terminal requires it.
+fn raw(terminal: &dyn Terminal) -> Option<ScopedRaw> {
+    if terminal.raw_mode() {
+        ScopedRaw::new().ok()
+    } else {
+        None
+    }
+}
+
 /// In which direction we should progress.
 #[derive(Debug, Clone, Copy)]
 enum Direction {
@@ -73,7 +85,8 @@ enum Direction {
 /// The user picked something. This is the pick representation.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) enum UserSelection {
-    Replacement(BandAid),
+    /// Replace the suggestion, one bandaid per fragment.
+    Replacement(Vec<BandAid>),
     /// Skip this suggestion and move on to the next suggestion.
     Skip,
     /// Jump to the previous suggestion.
@@ -142,16 +155,14 @@ where
         self.pick_idx + 1 == self.n_items
     }
 
-    /// Convert the replacment selection to a bandaid.
-    pub fn to_bandaid(&self) -> BandAid {
+    /// Convert the replacment selection to bandaids.
+    ///
+    /// Errors if the replacement can not be represented in the literal.
+    pub fn to_bandaids(&self) -> Result<Vec<BandAid>> {
         if self.is_custom_entry() {
-            BandAid::from((
-                self.custom_replacement.clone(),
-                self.suggestion.span.clone(),
-            ))
+            BandAid::distribute(self.suggestion, &self.custom_replacement)
         } else {
-            BandAid::try_from((self.suggestion, self.pick_idx))
-                .expect("Was constructed around this suggestion.")
+            BandAid::picked(self.suggestion, self.pick_idx)
         }
     }
 }
@@ -159,8 +170,9 @@ where
 /// The selection of used suggestion replacements.
 #[derive(Debug, Clone, Default)]
 pub struct UserPicked {
-    /// Associates the bandaids to a content origin, or path respectively.
-    pub bandaids: indexmap::IndexMap<ContentOrigin, Vec<BandAid>>,
+    /// Associates the bandaids to a content origin, or path respectively,
+    /// grouped by the finding they fix.
+    pub bandaids: indexmap::IndexMap<ContentOrigin, Vec<Vec<BandAid>>>,
 }
 
 impl UserPicked {
@@ -169,33 +181,19 @@ impl UserPicked {
         self.bandaids.iter().map(|(_origin, vec)| vec.len()).sum()
     }
 
-    /// Apply a single bandaid.
-    fn add_bandaid<'u>(&mut self, origin: &ContentOrigin, fix: BandAid) {
-        self.bandaids
-            .entry(origin.clone())
-            .or_insert_with(|| Vec::with_capacity(10))
-            .push(fix);
-    }
-
-    /// Apply multiple bandaids.
-    #[allow(unused)]
+    /// Apply the bandaids fixing a single finding.
     fn add_bandaids<I>(&mut self, origin: &ContentOrigin, fixes: I)
     where
         I: IntoIterator<Item = BandAid>,
     {
-        let iter = fixes.into_iter();
         self.bandaids
             .entry(origin.clone())
-            .or_insert_with(|| Vec::with_capacity(iter.size_hint().0))
-            .extend(iter);
+            .or_insert_with(Vec::new)
+            .push(fixes.into_iter().collect());
     }
 
     /// Provide a replacement that was not provided by the backend
-    fn enter_custom_replacement(
-        &self,
-        state: &mut State,
-        event: KeyEvent,
-    ) -> Result<UserSelection> {
+    fn enter_custom_replacement(state: &mut State, event: KeyEvent) -> Result<UserSelection> {
         let KeyEvent { code, modifiers } = event;
 
         let length = state.custom_replacement.len() as u16;
@@ -219,8 +217,10 @@ impl UserPicked {
                 }
             }
             KeyCode::Enter => {
-                let bandaid = BandAid::new(&state.custom_replacement, &state.suggestion.span);
-                return Ok(UserSelection::Replacement(bandaid));
+                match BandAid::distribute(state.suggestion, &state.custom_replacement) {
+                    Ok(bandaids) => return Ok(UserSelection::Replacement(bandaids)),
+                    Err(e) => warn!("Refusing the replacement: {}", e),
+                }
             }
             KeyCode::Esc => return Ok(UserSelection::Abort),
             KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => {
@@ -245,8 +245,12 @@ impl UserPicked {
     // arrow left
     // .. suggestion1 [suggestion2] suggestion3 suggestion4 ..
     // but now it's only a very simple list for now
-    fn print_replacements_list(&self, state: &mut State) -> Result<()> {
-        let mut stdout = stdout();
+    fn print_replacements_list(
+        &self,
+        state: &mut State,
+        terminal: &mut dyn Terminal,
+    ) -> Result<()> {
+        let mut stdout = terminal.sink();
 
         let tick = ContentStyle::new()
             .foreground(Color::Green)
@@ -358,9 +362,14 @@ impl UserPicked {
     }
 
     /// Wait for user input and process it into a `UserSelection` enum.
-    fn user_input(&self, state: &mut State, running_idx: (usize, usize)) -> Result<UserSelection> {
+    fn user_input(
+        &self,
+        state: &mut State,
+        running_idx: (usize, usize),
+        terminal: &mut dyn Terminal,
+    ) -> Result<UserSelection> {
         {
-            let _guard = ScopedRaw::new();
+            let _guard = raw(terminal);
 
             let boring = ContentStyle::new()
                 .foreground(Color::Blue)
@@ -382,7 +391,8 @@ impl UserPicked {
             // lines used by the question
             const QUESTION: u16 = 4;
             let extra_rows_to_flush = (state.n_items - (ERASE - QUESTION) as usize) as u16;
-            stdout()
+            terminal
+                .sink()
                 .queue(cursor::Hide)
                 .unwrap()
                 .queue(cursor::MoveUp(ERASE)) // erase the 5 last lines of suggestion print
@@ -402,27 +412,26 @@ impl UserPicked {
         }
 
         loop {
-            let mut _guard = ScopedRaw::new();
+            let mut _guard = raw(terminal);
 
-            self.print_replacements_list(state)?;
+            self.print_replacements_list(state, terminal)?;
 
             if state.is_custom_entry() {
                 info!("Custom entry mode");
 
-                stdout().queue(cursor::SavePosition).unwrap();
-                stdout()
+                let mut stdout = terminal.sink();
+                stdout.queue(cursor::SavePosition).unwrap();
+                stdout
                     .queue(cursor::Show)
                     .unwrap()
                     .queue(cursor::MoveToPreviousLine(1))
                     .unwrap()
                     .queue(cursor::MoveToColumn(4 + state.cursor_offset))
                     .unwrap();
-                let _ = stdout().flush();
+                let _ = stdout.flush();
             }
 
-            let event = match crossterm::event::read()
-                .map_err(|e| anyhow::anyhow!("Something unexpected happened on the CLI: {}", e))?
-            {
+            let event = match terminal.read()? {
                 Event::Key(event) => event,
                 Event::Resize(..) => {
                     drop(_guard);
@@ -437,11 +446,12 @@ impl UserPicked {
             if state.is_custom_entry() {
                 drop(_guard);
                 info!("Custom entry mode");
-                _guard = ScopedRaw::new();
+                _guard = raw(terminal);
 
-                let pick = self.enter_custom_replacement(state, event)?;
+                let pick = Self::enter_custom_replacement(state, event)?;
 
-                stdout()
+                terminal
+                    .sink()
                     .queue(cursor::Hide)
                     .unwrap()
                     .queue(cursor::RestorePosition)
@@ -457,40 +467,61 @@ impl UserPicked {
             // print normally again
             trace!("registered event: {:?}", &event);
 
-            let KeyEvent { code, modifiers } = event;
+            match Self::pick(state, event) {
+                UserSelection::Nop => continue,
+                other => return Ok(other),
+            }
+        }
+        unreachable!("Unexpected return when dealing with user input")
+    }
 
-            match code {
-                KeyCode::Up => state.select_next(),
-                KeyCode::Down => state.select_previous(),
-                KeyCode::Enter | KeyCode::Char('y') => {
-                    let bandaid: BandAid = state.to_bandaid();
-                    // TODO handle interactive intput for those where there are no suggestions
-                    return Ok(UserSelection::Replacement(bandaid));
-                }
-                KeyCode::Char('n') => return Ok(UserSelection::Skip),
-                KeyCode::Char('j') => return Ok(UserSelection::Previous),
-                KeyCode::Char('q') | KeyCode::Esc => return Ok(UserSelection::Abort),
-                KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => {
-                    return Ok(UserSelection::Abort)
-                }
-                KeyCode::Char('d') => return Ok(UserSelection::SkipFile),
-                KeyCode::Char('e') => {
-                    // jump to the user input entry
-                    state.select_custom();
-                }
-                KeyCode::Char('?') => return Ok(UserSelection::Help),
-                x => {
-                    trace!("Unexpected input {:?}", x);
+    /// Process a key outside of the custom entry into a `UserSelection`.
+    ///
+    /// Keys which only move the highlight, or are not bound at all, yield `Nop`.
+    fn pick(state: &mut State, event: KeyEvent) -> UserSelection {
+        let KeyEvent { code, modifiers } = event;
+
+        match code {
+            KeyCode::Up => state.select_next(),
+            KeyCode::Down => state.select_previous(),
+            KeyCode::Enter | KeyCode::Char('y') => {
+                // TODO handle interactive intput for those where there are no suggestions
+                match state.to_bandaids() {
+                    Ok(bandaids) => return UserSelection::Replacement(bandaids),
+                    Err(e) => warn!("Refusing the replacement: {}", e),
                 }
             }
+            KeyCode::Char('n') => return UserSelection::Skip,
+            KeyCode::Char('j') => return UserSelection::Previous,
+            KeyCode::Char('q') | KeyCode::Esc => return UserSelection::Abort,
+            KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => {
+                return UserSelection::Abort
+            }
+            KeyCode::Char('d') => return UserSelection::SkipFile,
+            KeyCode::Char('e') => {
+                // jump to the user input entry
+                state.select_custom();
+            }
+            KeyCode::Char('?') => return UserSelection::Help,
+            x => {
+                trace!("Unexpected input {:?}", x);
+            }
         }
-        unreachable!("Unexpected return when dealing with user input")
+        UserSelection::Nop
     }
 
+    /// Let the user pick the replacements, on the full screen if the terminal allows it.
     pub(super) fn select_interactive<'s>(
         suggestions_per_path: SuggestionSet<'s>,
         _config: &Config,
+        terminal: &mut dyn Terminal,
+        session: &mut Session,
     ) -> Result<(Self, UserSelection)> {
+        // without a terminal of known size, fall back to the line mode
+        if terminal.size().is_some() {
+            return fullscreen::select(suggestions_per_path, terminal, session);
+        }
+
         let mut picked = UserPicked::default();
 
         trace!("Select the ones to actully use");
@@ -529,27 +560,50 @@ impl UserPicked {
                     trace!("Suggestion did not contain a replacement, skip");
                     continue;
                 }
-                println!("{}", suggestion);
+                // decided upon in the session which is resumed
+                match session.decision(&suggestion) {
+                    Some(Decision::Accepted(bandaids)) => {
+                        picked.add_bandaids(&origin, bandaids.clone());
+                        continue;
+                    }
+                    Some(_) => continue,
+                    None => {}
+                }
+                writeln!(terminal.sink(), "{}", suggestion)?;
 
                 let mut state = State::from(&suggestion);
 
-                let mut pick = picked.user_input(&mut state, (idx, count))?;
+                let mut pick = picked.user_input(&mut state, (idx, count), terminal)?;
                 while pick == UserSelection::Help {
-                    println!("{}", HELP);
-                    pick = picked.user_input(&mut state, (idx, count))?;
+                    writeln!(terminal.sink(), "{}", HELP)?;
+                    pick = picked.user_input(&mut state, (idx, count), terminal)?;
                 }
                 match pick {
                     UserSelection::Abort => return Ok((picked, UserSelection::Abort)),
-                    UserSelection::SkipFile => break, // break the inner loop
+                    UserSelection::SkipFile => {
+                        // the remaining findings of the file are ignored just the same
+                        session.decide(&suggestion, Decision::Ignored);
+                        for (_, suggestion) in suggestions_it.by_ref() {
+                            if !suggestion.replacements.is_empty()
+                                && session.decision(&suggestion).is_none()
+                            {
+                                session.decide(&suggestion, Decision::Ignored);
+                            }
+                        }
+                        session.save()?;
+                        break; // break the inner loop
+                    }
                     UserSelection::Previous => {
                         unimplemented!("Requires a iterator which works bidrectionally")
                     }
                     UserSelection::Help => {
                         unreachable!("Help must not be reachable here, it is handled before")
                     }
-                    UserSelection::Replacement(bandaid) => {
-                        picked.add_bandaid(&origin, bandaid);
+                    UserSelection::Replacement(bandaids) => {
+                        session.record(&suggestion, Decision::Accepted(bandaids.clone()))?;
+                        picked.add_bandaids(&origin, bandaids);
                     }
+                    UserSelection::Skip => session.record(&suggestion, Decision::Skipped)?,
                     _ => continue,
                 };
 
@@ -558,4 +612,246 @@ impl UserPicked {
         }
         Ok((picked, UserSelection::Nop))
     }
+
+    /// Pick the first replacement of every suggestion, without asking.
+    ///
+    /// Suggestions overlapping a previously picked one are skipped.
+    pub(super) fn select_first<'s>(suggestions_per_path: SuggestionSet<'s>) -> Self {
+        let mut picked = UserPicked::default();
+        for (origin, mut suggestions) in suggestions_per_path {
+            suggestions.sort_by_key(|suggestion| {
+                (suggestion.span.start.line, suggestion.span.start.column)
+            });
+            let mut covered: Option<LineColumn> = None;
+            for suggestion in suggestions {
+                let start = suggestion.span.start;
+                if let Some(end) = covered {
+                    if (start.line, start.column) <= (end.line, end.column) {
+                        trace!("Suggestion overlaps a picked one, skip");
+                        continue;
+                    }
+                }
+                match BandAid::picked(&suggestion, 0) {
+                    Ok(bandaids) => {
+                        covered = Some(suggestion.span.end);
+                        picked.add_bandaids(&origin, bandaids);
+                    }
+                    Err(e) => debug!("Not applying suggestion: {}", e),
+                }
+            }
+        }
+        picked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::terminal::KeyScript;
+    use crate::checker;
+    use std::path::Path;
+
+    /// A library with one typography mistake in every kind of doc literal.
+    const LIBRARY: &str = r####"//! Module docs are are really helpful if they contain usage examples.
+
+/// Pick option a,  also known as door #1.
+pub fn a() {}
+
+#[doc = "Pick option b, also known as door two.."]
+pub fn b() {}
+
+#[doc = r#"Risk is not your thing, just take the the money and run."#]
+pub fn take_the_money_and_leave() {}
+"####;
+
+    /// Write the library into a directory of its own, named after the test.
+    fn library(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("spellcheck-keys-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lib.rs");
+        fs::write(&path, LIBRARY).unwrap();
+        path
+    }
+
+    /// Check the library at `path` for typography mistakes and pick fixes, as
+    /// the keys of `script` tell.
+    ///
+    /// Returns whatever was drawn and the selection.
+    fn review(
+        path: &Path,
+        script: &str,
+        size: Option<(u16, u16)>,
+        session: &mut Session,
+    ) -> (String, Result<(UserPicked, UserSelection)>) {
+        let content = fs::read_to_string(path).unwrap();
+        let mut docs = Documentation::new();
+        docs.add_rust(ContentOrigin::RustSourceFile(path.to_owned()), &content)
+            .expect("Library must parse");
+        let config = Config::parse("[typography]\n").expect("Must be a valid config");
+        let suggestions = checker::check(&docs, &config, None).expect("Must check the library");
+        assert_eq!(suggestions.total_count(), 4);
+
+        let mut terminal = KeyScript::parse(script, Vec::new()).expect("Key script must parse");
+        if let Some((columns, rows)) = size {
+            terminal = terminal.with_size(columns, rows);
+        }
+        let picked = UserPicked::select_interactive(suggestions, &config, &mut terminal, session);
+        assert_eq!(terminal.remaining(), 0);
+        let output = String::from_utf8_lossy(terminal.output()).into_owned();
+        (output, picked)
+    }
+
+    /// The replacements of all picked bandaids.
+    fn replacements(picked: &UserPicked) -> Vec<&str> {
+        picked
+            .bandaids
+            .values()
+            .flatten()
+            .flatten()
+            .map(|bandaid| bandaid.replacement.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn replay_keys() {
+        // the replacement, skip, the help and the replacement, a custom one
+        let path = library("replay");
+        let (output, picked) = review(
+            &path,
+            r#"
+y
+n
+?
+y
+e "teh" <backspace> <backspace> "he" <enter>
+"#,
+            None,
+            &mut Session::default(),
+        );
+        assert!(output.contains("Module docs are are"));
+        let (picked, selection) = picked.expect("Must complete the selection");
+        assert_eq!(selection, UserSelection::Nop);
+
+        let origin = ContentOrigin::RustSourceFile(path.clone());
+        let bandaids = picked.bandaids.get(&origin).expect("Must pick fixes");
+        assert_eq!(
+            bandaids
+                .iter()
+                .flatten()
+                .map(|bandaid| (
+                    bandaid.span.start.line,
+                    bandaid.span.start.column,
+                    bandaid.replacement.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            vec![(1, 16, "are"), (6, 46, "."), (9, 45, "the")]
+        );
+
+        Action::Fix
+            .write_changes_to_disk(picked, &Config::default(), &Writeback::default())
+            .expect("Must apply the fixes");
+        let expected = LIBRARY
+            .replacen("are are", "are", 1)
+            .replacen("two..", "two.", 1)
+            .replacen("the the", "the", 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn replay_keys_full_screen() {
+        // go back to the first finding and skip it after all
+        let path = library("fullscreen");
+        let (output, picked) = review(
+            &path,
+            r#"
+y j n
+<pagedown> ? y
+<pageup> y
+e "the" <enter>
+"#,
+            Some((100, 30)),
+            &mut Session::default(),
+        );
+        // the sidebar, the preview and the status bar
+        assert!(output.contains("lib.rs 3/4"));
+        assert!(output.contains("Module docs"));
+        assert!(output.contains(" (4/4) "));
+        let (picked, selection) = picked.expect("Must complete the selection");
+        assert_eq!(selection, UserSelection::Nop);
+        assert_eq!(replacements(&picked), vec![" ", ".", "the"]);
+
+        Action::Fix
+            .write_changes_to_disk(picked, &Config::default(), &Writeback::default())
+            .expect("Must apply the fixes");
+        let expected = LIBRARY
+            .replacen("a,  also", "a, also", 1)
+            .replacen("two..", "two.", 1)
+            .replacen("the the", "the", 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+
+        // too small for anything but the status
+        fs::write(&path, LIBRARY).unwrap();
+        let (output, picked) = review(&path, "y n q", Some((10, 4)), &mut Session::default());
+        assert_eq!(picked.unwrap().1, UserSelection::Abort);
+        assert!(output.contains("Terminal"));
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn abort_keeps_files() {
+        let path = library("abort");
+        let (_, picked) = review(&path, "y q", None, &mut Session::default());
+        let (picked, selection) = picked.expect("Must complete the selection");
+        assert_eq!(selection, UserSelection::Abort);
+        assert_eq!(picked.total_count(), 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), LIBRARY);
+
+        // the script ends before the selection does
+        let (_, picked) = review(&path, "y n", None, &mut Session::default());
+        assert!(picked.is_err());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn resume_session() {
+        let dir = std::env::temp_dir().join(format!("spellcheck-session-{}", std::process::id()));
+        let digests = IndexMap::new();
+        let path = library("resume");
+
+        // quit after two decisions
+        let mut session = Session::start(&dir, &digests).unwrap();
+        let (_, picked) = review(&path, "y n q", None, &mut session);
+        assert_eq!(picked.unwrap().1, UserSelection::Abort);
+        assert_eq!(fs::read_to_string(&path).unwrap(), LIBRARY);
+
+        // only the remaining findings are asked for
+        let mut session = Session::resume(&dir, &digests).expect("Must resume the session");
+        assert_eq!(session.len(), 2);
+        let (_, picked) = review(&path, r#"y e "the" <enter>"#, None, &mut session);
+        let (picked, _) = picked.expect("Must complete the selection");
+        assert_eq!(replacements(&picked), vec!["are", ".", "the"]);
+
+        // nothing is left to ask for, on the full screen just the same
+        let mut session = Session::resume(&dir, &digests).unwrap();
+        assert_eq!(session.len(), 4);
+        let (_, picked) = review(&path, "", Some((100, 30)), &mut session);
+        let (picked, _) = picked.expect("Must complete the selection");
+        assert_eq!(replacements(&picked), vec!["are", ".", "the"]);
+
+        // a changed file is reviewed again
+        let mut changed = IndexMap::new();
+        changed.insert(path.clone(), util::digest(b"changed"));
+        assert!(Session::resume(&dir, &changed).unwrap().is_empty());
+
+        session.finish().unwrap();
+        assert!(Session::resume(&dir, &digests).is_err());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
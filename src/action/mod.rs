@@ -1,19 +1,27 @@
 //! Covers all user triggered actions (except for signals).
 
 use super::*;
-use anyhow::{anyhow, Result};
-use log::{debug, trace};
-use std::convert::TryInto;
+use anyhow::{anyhow, bail, Result};
+use indexmap::IndexMap;
+use log::{debug, trace, warn};
 use std::fs::{self, OpenOptions};
-use std::io::{BufRead, Write};
+use std::io::Write;
 
 use std::path::PathBuf;
 
+mod auto;
 pub mod bandaid;
+mod diff;
 pub mod interactive;
+pub mod report;
+pub mod session;
+pub mod terminal;
 
 pub(crate) use bandaid::*;
 use interactive::*;
+pub use report::Report;
+pub use session::{Decision, Session};
+pub use terminal::{KeyScript, Terminal, Tty};
 
 /// State of conclusion.
 #[derive(Debug, Clone, Copy)]
@@ -23,6 +31,14 @@ pub enum Finish {
     /// Completion of the check run, with the resulting number of
     /// mistakes accumulated.
     MistakeCount(usize),
+    /// Completion of a non-interactive fix, with the number of applied
+    /// fixes and of the findings which remain.
+    Fixed {
+        /// Findings which were fixed.
+        applied: usize,
+        /// Findings which were skipped.
+        remaining: usize,
+    },
 }
 
 impl Finish {
@@ -30,6 +46,7 @@ impl Finish {
     pub fn found_any(&self) -> bool {
         match *self {
             Self::MistakeCount(n) if n > 0 => true,
+            Self::Fixed { remaining, .. } if remaining > 0 => true,
             _ => false,
         }
     }
@@ -38,90 +55,131 @@ impl Finish {
 /// correct all lines
 /// `bandaids` are the fixes to be applied to the lines
 ///
-/// Note that `Lines` as created by `(x as BufLines).lines()` does
-/// not preserve trailing newlines, so either the iterator
-/// needs to be modified to yield an extra (i.e. with `.chain("".to_owned())`)
-/// or a manual newlines has to be written to the `sink`.
+/// A bandaid spanning multiple lines replaces everything from its start
+/// in the first line up to its end in the last line, the lines in between
+/// are dropped.
+///
+/// Every line written to the `sink` is terminated by a `\n`, `rewrite`
+/// takes care of the original line endings.
 fn correct_lines<'s>(
     mut bandaids: impl Iterator<Item = BandAid>,
     source: impl Iterator<Item = (usize, String)>,
     mut sink: impl Write,
 ) -> Result<()> {
     let mut nxt: Option<BandAid> = bandaids.next();
+    // end of a multi-line bandaid, the line and the column
+    // from which on the content is retained
+    let mut resume: Option<(usize, usize)> = None;
     for (line_number, content) in source {
         trace!("Processing line {}", line_number);
         let mut remainder_column = 0usize;
-        // let content: String = content.map_err(|e| {
-        //     anyhow!("Line {} contains invalid utf8 characters", line_number).context(e)
-        // })?;
 
-        if nxt.is_none() {
-            // no candidates remaining, just keep going
+        if let Some((line, column)) = resume {
+            if line > line_number {
+                trace!("Line {} is covered by a multi-line bandaid", line_number);
+                continue;
+            }
+            resume = None;
+            remainder_column = column;
+        } else if nxt
+            .as_ref()
+            .map_or(true, |bandaid| !bandaid.span.covers_line(line_number))
+        {
+            // no candidates for this line, just keep going
             sink.write(content.as_bytes())?;
             sink.write("\n".as_bytes())?;
             continue;
         }
 
-        if let Some(ref bandaid) = nxt {
-            if !bandaid.span.covers_line(line_number) {
-                sink.write(content.as_bytes())?;
-                sink.write("\n".as_bytes())?;
-                continue;
-            }
-        }
-
         let content_len = content.chars().count();
         while let Some(bandaid) = nxt.take() {
+            if bandaid.span.start.line != line_number {
+                nxt = Some(bandaid);
+                break;
+            }
             trace!("Applying next bandaid {:?}", bandaid);
             trace!("where line {} is: >{}<", line_number, content);
-            let range: Range = bandaid
-                .span
-                .try_into()
-                .expect("There should be no multiline strings as of today");
+            let start = bandaid.span.start.column;
             // write prelude for this line between start or previous replacement
-            if range.start > remainder_column {
-                sink.write(util::sub_chars(&content, remainder_column..range.start).as_bytes())?;
+            if start > remainder_column {
+                sink.write_all(util::sub_chars(&content, remainder_column..start).as_bytes())?;
             }
             // write the replacement chunk
             sink.write(bandaid.replacement.as_bytes())?;
-
-            remainder_column = range.end;
             nxt = bandaids.next();
-            let complete_current_line = if let Some(ref bandaid) = nxt {
-                // if `nxt` is also targeting the current line, don't complete the line
-                !bandaid.span.covers_line(line_number)
-            } else {
-                true
-            };
-            if complete_current_line {
-                // the last replacement may be the end of content
-                if remainder_column < content_len {
-                    debug!(
-                        "line {} len is {}, and remainder column is {}",
-                        line_number, content_len, remainder_column
-                    );
-                    // otherwise write all
-                    // not that this also covers writing a line without any suggestions
-                    sink.write(
-                        util::sub_chars(&content, remainder_column..content_len).as_bytes(),
-                    )?;
-                } else {
-                    debug!(
-                        "line {} len is {}, and remainder column is {}",
-                        line_number, content_len, remainder_column
-                    );
-                }
-                sink.write("\n".as_bytes())?;
-                // break the inner loop
+
+            if bandaid.span.end.line > line_number {
+                resume = Some((bandaid.span.end.line, bandaid.span.end.column + 1));
                 break;
-                // } else {
-                // next suggestion covers same line
             }
+            remainder_column = bandaid.span.end.column + 1;
+        }
+
+        if resume.is_some() {
+            // the line is completed by the end of the multi-line bandaid
+            continue;
+        }
+        debug!(
+            "line {} len is {}, and remainder column is {}",
+            line_number, content_len, remainder_column
+        );
+        // the last replacement may be the end of content
+        if remainder_column < content_len {
+            sink.write_all(util::sub_chars(&content, remainder_column..content_len).as_bytes())?;
         }
+        sink.write_all("\n".as_bytes())?;
     }
     Ok(())
 }
 
+/// Apply the bandaids to the content of a file.
+///
+/// The line endings, `\n` or `\r\n`, as well as the presence of a final
+/// newline are kept as they are.
+fn rewrite(content: &str, bandaids: impl IntoIterator<Item = BandAid>) -> Result<String> {
+    let crlf = content.contains("\r\n");
+    // a trailing `\r` is retained as part of the line content
+    let mut lines = content.split('\n').collect::<Vec<_>>();
+    let final_newline = content.ends_with('\n');
+    if final_newline {
+        lines.pop();
+    }
+    let bandaids = bandaids.into_iter().map(|mut bandaid| {
+        if crlf {
+            bandaid.replacement = bandaid.replacement.replace('\n', "\r\n");
+        }
+        bandaid
+    });
+
+    let mut sink = Vec::with_capacity(content.len() + 64);
+    correct_lines(
+        bandaids,
+        lines
+            .into_iter()
+            .enumerate()
+            .map(|(lineno, line)| (lineno + 1, line.to_owned())),
+        &mut sink,
+    )?;
+    if !final_newline && sink.last() == Some(&b'\n') {
+        sink.pop();
+    }
+    Ok(String::from_utf8(sink)?)
+}
+
+/// How fixes are written back to the files.
+#[derive(Debug, Clone, Default)]
+pub struct Writeback {
+    /// Keep a copy of every modified file, with an additional `.orig` extension.
+    pub backup: bool,
+    /// Digests of the file contents at the time they were parsed, a file
+    /// which changed since is not touched.
+    pub digests: IndexMap<PathBuf, u64>,
+    /// Render the fixes as unified diff instead of writing them.
+    pub dry_run: bool,
+    /// Write the diff of a dry run to this file rather than to stdout.
+    pub diff_output: Option<PathBuf>,
+}
+
 /// Mode in which `cargo-spellcheck` operates
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Action {
@@ -129,20 +187,29 @@ pub enum Action {
     Check,
     /// Interactively choose from checker provided suggestions.
     Fix,
+    /// Apply the first suggested replacement of every suggestion.
+    Apply,
+    /// Apply only unambiguous replacements, without asking.
+    Auto,
+    /// Only show errors, as JSON report.
+    Report,
 }
 
 impl Action {
-    /// Apply bandaids to the file represented by content origin.
-    fn correction<'s>(
-        &self,
-        origin: ContentOrigin,
-        bandaids: impl IntoIterator<Item = BandAid>,
-    ) -> Result<()> {
+    /// The file represented by content origin, with the bandaids re-based
+    /// from the content of the origin onto the full file.
+    fn correction(origin: ContentOrigin, bandaids: Vec<BandAid>) -> (PathBuf, Vec<BandAid>) {
+        let bandaids = bandaids
+            .into_iter()
+            .map(|mut bandaid| {
+                bandaid.span = origin.absolute_span(bandaid.span);
+                bandaid
+            })
+            .collect();
         match origin {
-            ContentOrigin::CommonMarkFile(path) => self.correct_file(path, bandaids),
-            ContentOrigin::RustSourceFile(path) => self.correct_file(path, bandaids),
-            //TODO bandaids are relative to the doc-test, so fix the span with the one provided
-            ContentOrigin::RustDocTest(path, _span) => self.correct_file(path, bandaids),
+            ContentOrigin::CommonMarkFile(path) => (path, bandaids),
+            ContentOrigin::RustSourceFile(path) => (path, bandaids),
+            ContentOrigin::RustDocTest(path, _span) => (path, bandaids),
             #[cfg(test)]
             ContentOrigin::TestEntityRust => unreachable!("Use a proper file"),
             #[cfg(test)]
@@ -150,101 +217,247 @@ impl Action {
         }
     }
 
-    /// assumes suggestions are sorted by line number and column number and must be non overlapping
+    /// Apply bandaids to the file at `path`.
+    ///
+    /// With a `patch` the changes are appended to it as unified diff,
+    /// and the file is left as is.
+    ///
+    /// Assumes suggestions are sorted by line number and column number and must be non overlapping.
     fn correct_file<'s>(
         &self,
         path: PathBuf,
         bandaids: impl IntoIterator<Item = BandAid>,
+        writeback: &Writeback,
+        patch: Option<&mut String>,
     ) -> Result<()> {
+        let expected = writeback.digests.get(&path).copied();
         let path = path
             .as_path()
             .canonicalize()
             .map_err(|e| anyhow!("Failed to canonicalize {}", path.display()).context(e))?;
         let path = path.as_path();
-        trace!("Attempting to open {} as read", path.display());
-        let ro = std::fs::OpenOptions::new()
-            .read(true)
-            .open(path)
-            .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
-
-        let mut reader = std::io::BufReader::new(ro);
-
-        const TEMPORARY: &'static str = ".spellcheck.tmp";
-
-        let tmp = std::env::current_dir()
-            .expect("Must have cwd")
-            .join(TEMPORARY);
-        // let tmp = tmp.canonicalize().map_err(|e| { anyhow!("Failed to canonicalize {}", tmp.display() ).context(e) })?;
-        //trace!("Attempting to open {} as read", tmp.display());
-        let wr = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(&tmp)
-            .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
-
-        let mut writer = std::io::BufWriter::with_capacity(1024, wr);
-
-        correct_lines(
-            bandaids.into_iter(),
-            (&mut reader)
-                .lines()
-                .filter_map(|line| line.ok())
-                .enumerate()
-                .map(|(lineno, content)| (lineno + 1, content)),
-            &mut writer,
-        )?;
-
-        writer.flush()?;
-
-        fs::rename(tmp, path)?;
+        trace!("Attempting to read {}", path.display());
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}", path.display()).context(e))?;
+
+        if let Some(expected) = expected {
+            if util::digest(content.as_bytes()) != expected {
+                bail!(
+                    "{} changed since it was checked, not applying any fixes",
+                    path.display()
+                );
+            }
+        }
 
-        Ok(())
+        let corrected = rewrite(&content, bandaids)?;
+
+        if let Some(patch) = patch {
+            // paths relative to the working directory, as expected by `git apply`
+            let cwd = traverse::cwd()?.canonicalize()?;
+            let relative = path.strip_prefix(&cwd).map_err(|_| {
+                anyhow!(
+                    "{} is outside of the working directory {}, can not render it as patch",
+                    path.display(),
+                    cwd.display()
+                )
+            })?;
+            patch.push_str(&diff::unified(relative, &content, &corrected));
+            return Ok(());
+        }
+
+        // a temporary file next to the target, so the rename stays on the same file system
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| anyhow!("Not a file {}", path.display()))?
+            .to_string_lossy();
+        let tmp = path.with_file_name(format!(
+            ".{}.spellcheck.{}.tmp",
+            file_name,
+            std::process::id()
+        ));
+        let written = (|| -> Result<()> {
+            let mut wr = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&tmp)
+                .map_err(|e| anyhow!("Failed to create {}", tmp.display()).context(e))?;
+            wr.write_all(corrected.as_bytes())?;
+            wr.sync_all()?;
+            fs::set_permissions(&tmp, fs::metadata(path)?.permissions())?;
+            if writeback.backup {
+                let mut backup = path.as_os_str().to_owned();
+                backup.push(".orig");
+                debug!("Keeping a backup of {} as {:?}", path.display(), &backup);
+                fs::copy(path, backup)?;
+            }
+            fs::rename(&tmp, path)?;
+            Ok(())
+        })();
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        written
     }
 
-    /// Consumingly apply the user picked changes to a file.
+    /// Consumingly apply the user picked changes to a file, or render them
+    /// as unified diff for a dry run.
     ///
     /// **Attention**: Must be consuming, repeated usage causes shifts in spans and
     /// would destroy the file structure!
-    pub fn write_changes_to_disk(&self, userpicked: UserPicked, _config: &Config) -> Result<()> {
+    pub fn write_changes_to_disk(
+        &self,
+        userpicked: UserPicked,
+        _config: &Config,
+        writeback: &Writeback,
+    ) -> Result<()> {
+        let mut patch = if writeback.dry_run {
+            Some(String::new())
+        } else {
+            None
+        };
         if userpicked.total_count() > 0 {
             debug!("Writing changes back to disk");
-            for (path, bandaids) in userpicked.bandaids.into_iter() {
-                self.correction(path, bandaids.into_iter())?;
+            // doc-tests and the doc comments around them share one file
+            let mut per_file = IndexMap::<PathBuf, Vec<Vec<BandAid>>>::new();
+            for (origin, fixes) in userpicked.bandaids.into_iter() {
+                for bandaids in fixes {
+                    let (path, bandaids) = Self::correction(origin.clone(), bandaids);
+                    per_file.entry(path).or_insert_with(Vec::new).push(bandaids);
+                }
+            }
+            for (path, mut fixes) in per_file {
+                fixes.retain(|bandaids| !bandaids.is_empty());
+                fixes.sort_by_key(|bandaids| {
+                    let start = bandaids[0].span.start;
+                    (start.line, start.column)
+                });
+                // the fragments of a finding are applied all together or not at all
+                let mut covered: Option<LineColumn> = None;
+                fixes.retain(|bandaids| {
+                    let start = bandaids[0].span.start;
+                    match covered {
+                        Some(end) if (start.line, start.column) <= (end.line, end.column) => {
+                            warn!(
+                                "Overlapping fixes in {}:{}, keeping the first one",
+                                path.display(),
+                                start.line
+                            );
+                            false
+                        }
+                        _ => {
+                            covered = bandaids
+                                .iter()
+                                .map(|bandaid| bandaid.span.end)
+                                .max_by_key(|end| (end.line, end.column));
+                            true
+                        }
+                    }
+                });
+                let mut bandaids = fixes.into_iter().flatten().collect::<Vec<_>>();
+                bandaids
+                    .sort_by_key(|bandaid| (bandaid.span.start.line, bandaid.span.start.column));
+                self.correct_file(path, bandaids, writeback, patch.as_mut())?;
             }
         } else {
             debug!("No band aids to apply");
         }
+        if let Some(patch) = patch {
+            match writeback.diff_output.as_ref() {
+                Some(path) => fs::write(path, patch).map_err(|e| {
+                    anyhow!("Failed to write diff to {}", path.display()).context(e)
+                })?,
+                None => print!("{}", patch),
+            }
+        }
         Ok(())
     }
 
     /// Purpose was to check, checking complete, so print the results.
-    fn check(&self, suggestions_per_path: SuggestionSet, _config: &Config) -> Result<Finish> {
+    ///
+    /// Only suggestions with severity error are counted as mistakes.
+    fn check(&self, suggestions_per_path: SuggestionSet, config: &Config) -> Result<Finish> {
         let mut count = 0usize;
         for (_path, suggestions) in suggestions_per_path {
-            count += suggestions.len();
             for suggestion in suggestions {
-                println!("{}", suggestion);
+                let severity = config.severity(suggestion.detector);
+                if severity == Severity::Error {
+                    count += 1;
+                }
+                println!("{}", suggestion.with_severity(severity));
             }
         }
         Ok(Finish::MistakeCount(count))
     }
 
     /// Run the requested action.
-    pub fn run(self, suggestions: SuggestionSet, config: &Config) -> Result<Finish> {
+    ///
+    /// Fixes are picked interactively on the `terminal`, the decisions are
+    /// kept in the `session` until the fixes are written.
+    pub fn run(
+        self,
+        suggestions: SuggestionSet,
+        config: &Config,
+        writeback: &Writeback,
+        terminal: &mut dyn Terminal,
+        session: &mut Session,
+    ) -> Result<Finish> {
         match self {
             Self::Check => self.check(suggestions, config),
+            Self::Report => {
+                let report = Report::new(&suggestions, config)?;
+                println!("{}", report);
+                let count = report
+                    .entries()
+                    .iter()
+                    .filter(|entry| entry.severity == Severity::Error)
+                    .count();
+                Ok(Finish::MistakeCount(count))
+            }
             Self::Fix => {
-                let (picked, user_sel) =
-                    interactive::UserPicked::select_interactive(suggestions, config)?;
+                let (picked, user_sel) = interactive::UserPicked::select_interactive(
+                    suggestions,
+                    config,
+                    terminal,
+                    session,
+                )?;
                 if user_sel == UserSelection::Abort {
                     Ok(Finish::Abort)
                 } else {
                     let n = picked.total_count();
-                    self.write_changes_to_disk(picked, config)?;
+                    self.write_changes_to_disk(picked, config, writeback)?;
+                    std::mem::take(session).finish()?;
                     Ok(Finish::MistakeCount(n))
                 }
             }
+            Self::Apply => {
+                let picked = interactive::UserPicked::select_first(suggestions);
+                let n = picked.total_count();
+                self.write_changes_to_disk(picked, config, writeback)?;
+                Ok(Finish::MistakeCount(n))
+            }
+            Self::Auto => {
+                let (picked, applied, skipped) = auto::select(suggestions);
+                // a diff on stdout must not be interleaved with anything else
+                let diff_to_stdout = writeback.dry_run && writeback.diff_output.is_none();
+                for (suggestion, reason) in skipped.iter() {
+                    let severity = config.severity(suggestion.detector);
+                    let skipped = format!(
+                        "{}\nnot applied, {}\n",
+                        suggestion.with_severity(severity),
+                        reason
+                    );
+                    if diff_to_stdout {
+                        eprintln!("{}", skipped);
+                    } else {
+                        println!("{}", skipped);
+                    }
+                }
+                self.write_changes_to_disk(picked, config, writeback)?;
+                Ok(Finish::Fixed {
+                    applied,
+                    remaining: skipped.len(),
+                })
+            }
         }
     }
 }
@@ -252,6 +465,8 @@ impl Action {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{chyrp_up, fluff_up};
+    use std::convert::TryInto;
 
     const TEXT: &'static str = r#"
 I like unicorns every second Mondays.
@@ -296,4 +511,425 @@ I like banana icecream every third day.
 
         assert_eq!(String::from_utf8_lossy(sink.as_slice()), CORRECTED);
     }
+
+    #[test]
+    fn replace_multiline() {
+        let _ = env_logger::Builder::new()
+            .filter(None, log::LevelFilter::Trace)
+            .is_test(true)
+            .try_init();
+
+        const TEXT: &str = "/// A short\n/// paragraph that\n/// is reflowed.\nstruct X;\n";
+        const CORRECTED: &str = "/// A short paragraph\n/// that is reflowed!\nstruct X;\n";
+
+        let mut sink: Vec<u8> = Vec::with_capacity(1024);
+        let bandaids = vec![
+            BandAid {
+                span: Span {
+                    start: LineColumn { line: 1, column: 4 },
+                    end: LineColumn { line: 3, column: 5 },
+                },
+                replacement: "A short paragraph\n/// that is".to_owned(),
+            },
+            BandAid {
+                span: (3usize, 15..16).try_into().unwrap(),
+                replacement: "!".to_owned(),
+            },
+        ];
+
+        let lines = TEXT
+            .lines()
+            .map(|line| line.to_owned())
+            .enumerate()
+            .map(|(lineno, content)| (lineno + 1, content));
+
+        correct_lines(bandaids.into_iter(), lines, &mut sink).expect("should be able to");
+
+        assert_eq!(String::from_utf8_lossy(sink.as_slice()), CORRECTED);
+    }
+    #[test]
+    fn replace_grouped_fragments() {
+        let _ = env_logger::Builder::new()
+            .filter(None, log::LevelFilter::Trace)
+            .is_test(true)
+            .try_init();
+
+        const TEXT: &str = fluff_up!["I like unicorns every", "second Mondays."];
+        const CORRECTED: &str = "/// I like unicorns every\n/// other Monday.\nstruct Fluff;\n";
+
+        let docs = Documentation::from((ContentOrigin::TestEntityRust, TEXT));
+        let (origin, chunks) = docs.iter().next().expect("Must contain one origin");
+        let chunk = &chunks[0];
+        let plain = chunk.erase_markdown();
+        let start = plain.as_str().find("every").expect("Must contain the word");
+        let end = plain.as_str().find(".").expect("Must contain the period");
+        let fragments = plain.find_spans(start..end);
+        assert_eq!(fragments.len(), 2);
+        let suggestion = Suggestion {
+            detector: Detector::Dummy,
+            origin: origin.clone(),
+            chunk,
+            span: Span {
+                start: fragments.values().next().unwrap().start,
+                end: fragments.values().last().unwrap().end,
+            },
+            range: fragments.keys().next().unwrap().start..fragments.keys().last().unwrap().end,
+            replacements: vec!["every other Monday".to_owned()],
+            description: None,
+        };
+
+        let bandaids = BandAid::picked(&suggestion, 0).expect("Must have a replacement");
+        assert_eq!(bandaids.len(), 2);
+
+        let mut sink: Vec<u8> = Vec::with_capacity(1024);
+        let lines = TEXT
+            .lines()
+            .map(|line| line.to_owned())
+            .enumerate()
+            .map(|(lineno, content)| (lineno + 1, content));
+
+        correct_lines(bandaids.into_iter(), lines, &mut sink).expect("should be able to");
+
+        assert_eq!(String::from_utf8_lossy(sink.as_slice()), CORRECTED);
+    }
+    /// Replace the first occurrence of `word` in the doc comment of `source`.
+    fn fix_word(source: &str, word: &str, replacement: &str) -> Result<String> {
+        let docs = Documentation::from((ContentOrigin::TestEntityRust, source));
+        let (origin, chunks) = docs.iter().next().expect("Must contain one origin");
+        let chunk = &chunks[0];
+        let start = chunk.as_str().find(word).expect("Must contain the word");
+        let range = start..(start + word.len());
+        let (_, span) = chunk
+            .find_spans(range.clone())
+            .into_iter()
+            .next()
+            .expect("Must map to a span");
+        let suggestion = Suggestion {
+            detector: Detector::Dummy,
+            origin: origin.clone(),
+            chunk,
+            span,
+            range,
+            replacements: vec![replacement.to_owned()],
+            description: None,
+        };
+        let bandaids = BandAid::picked(&suggestion, 0)?;
+
+        let mut sink: Vec<u8> = Vec::with_capacity(1024);
+        let lines = source
+            .lines()
+            .map(|line| line.to_owned())
+            .enumerate()
+            .map(|(lineno, content)| (lineno + 1, content));
+        correct_lines(bandaids.into_iter(), lines, &mut sink)?;
+        Ok(String::from_utf8(sink)?)
+    }
+
+    /// The doc attribute value of the item in `source`, as seen by rustc.
+    fn doc_value(source: &str) -> String {
+        let item = syn::parse_str::<syn::ItemStruct>(source).expect("Must be valid rust");
+        match item.attrs[0]
+            .parse_meta()
+            .expect("Must be a meta attribute")
+        {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) => lit.value(),
+            _ => panic!("Must be a doc attribute with a string value"),
+        }
+    }
+
+    #[test]
+    fn escape_fluff() {
+        let fixed = fix_word(fluff_up!["Some word here"], "word", r#""quoted" \ path"#)
+            .expect("Comments take anything");
+        assert_eq!(fixed, "/// Some \"quoted\" \\ path here\nstruct Fluff;\n");
+        assert_eq!(doc_value(&fixed), r#" Some "quoted" \ path here"#);
+
+        // a newline would end the comment and leave a bare source line behind
+        assert!(fix_word(fluff_up!["Some word here"], "word", "two\nlines").is_err());
+        assert!(fix_word(fluff_up!["Some word here"], "word", "two\r\nlines").is_err());
+    }
+
+    #[test]
+    fn escape_str_newline() {
+        const SOURCE: &str = "#[doc = \"Some word here\"]\nstruct Plain;";
+        let fixed = fix_word(SOURCE, "word", "two\nlines").expect("Strings may span lines");
+        assert_eq!(doc_value(&fixed), "Some two\nlines here");
+    }
+
+    #[test]
+    fn escape_str() {
+        const SOURCE: &str = "#[doc = \"Some word here\"]\nstruct Plain;";
+        let fixed = fix_word(SOURCE, "word", r#""quoted" \ path"#).expect("Strings can be escaped");
+        assert_eq!(
+            fixed,
+            "#[doc = \"Some \\\"quoted\\\" \\\\ path here\"]\nstruct Plain;\n"
+        );
+        assert_eq!(doc_value(&fixed), r#"Some "quoted" \ path here"#);
+    }
+
+    #[test]
+    fn escape_chyrp() {
+        let fixed = fix_word(chyrp_up!["Some word here"], "word", r#"a "quote""#)
+            .expect("Raw strings may contain quotes");
+        assert_eq!(doc_value(&fixed), r#"Some a "quote" here"#);
+
+        assert!(fix_word(chyrp_up!["Some word here"], "word", r##"a "# b"##).is_err());
+    }
+
+    #[test]
+    fn escape_block_comment() {
+        const SOURCE: &str = "/** Some word here */\nstruct Block;";
+        let fixed = fix_word(SOURCE, "word", "\"quoted\"").expect("Comments take quotes");
+        assert_eq!(doc_value(&fixed), r#" Some "quoted" here "#);
+
+        assert!(fix_word(SOURCE, "word", "*/ oops").is_err());
+    }
+    #[test]
+    fn rewrite_keeps_line_endings() {
+        let bandaids = vec![
+            BandAid {
+                span: (1usize, 11..19).try_into().unwrap(),
+                replacement: "unicorns".to_owned(),
+            },
+            BandAid {
+                span: Span {
+                    start: LineColumn { line: 2, column: 4 },
+                    end: LineColumn { line: 3, column: 4 },
+                },
+                replacement: "two\n/// lines".to_owned(),
+            },
+        ];
+        const TEXT: &str = "/// I like unicrons\r\n/// A\r\n/// B\r\nstruct X;";
+        const CORRECTED: &str = "/// I like unicorns\r\n/// two\r\n/// lines\r\nstruct X;";
+        assert_eq!(rewrite(TEXT, bandaids.clone()).unwrap(), CORRECTED);
+
+        const UNIX: &str = "/// I like unicrons\n/// A\n/// B\nstruct X;\n";
+        assert_eq!(
+            rewrite(UNIX, bandaids).unwrap(),
+            "/// I like unicorns\n/// two\n/// lines\nstruct X;\n"
+        );
+    }
+
+    #[test]
+    fn correct_file_in_place() {
+        // within the working directory, so the diff can refer to it
+        let dir = traverse::cwd()
+            .unwrap()
+            .join("target")
+            .join(format!("spellcheck-fix-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lib.rs");
+        const TEXT: &str = "/// I like unicrons\nstruct X;\n";
+        fs::write(&path, TEXT).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        let bandaids = || {
+            vec![BandAid {
+                span: (1usize, 11..19).try_into().unwrap(),
+                replacement: "unicorns".to_owned(),
+            }]
+        };
+
+        // the file changed since it was parsed
+        let mut writeback = Writeback {
+            backup: true,
+            ..Default::default()
+        };
+        writeback
+            .digests
+            .insert(path.clone(), util::digest(b"something else"));
+        assert!(Action::Apply
+            .correct_file(path.clone(), bandaids(), &writeback, None)
+            .is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), TEXT);
+
+        writeback
+            .digests
+            .insert(path.clone(), util::digest(TEXT.as_bytes()));
+
+        // a dry run only renders the diff
+        let mut patch = String::new();
+        Action::Apply
+            .correct_file(path.clone(), bandaids(), &writeback, Some(&mut patch))
+            .expect("Must be able to render the diff");
+        assert!(patch.starts_with(&format!(
+            "diff --git a/target/spellcheck-fix-{}/lib.rs",
+            std::process::id()
+        )));
+        assert!(patch.ends_with(
+            "@@ -1,2 +1,2 @@\n-/// I like unicrons\n+/// I like unicorns\n struct X;\n"
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), TEXT);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        Action::Apply
+            .correct_file(path.clone(), bandaids(), &writeback, None)
+            .expect("Must be able to fix the file");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "/// I like unicorns\nstruct X;\n"
+        );
+        assert_eq!(fs::read_to_string(dir.join("lib.rs.orig")).unwrap(), TEXT);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+        // only the file and its backup remain
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn patch_outside_working_directory() {
+        let dir = std::env::temp_dir().join(format!("spellcheck-patch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lib.rs");
+        fs::write(&path, "/// I like unicrons\nstruct X;\n").unwrap();
+        let bandaids = vec![BandAid {
+            span: (1usize, 11..19).try_into().unwrap(),
+            replacement: "unicorns".to_owned(),
+        }];
+
+        // `a/tmp/..` could not be applied from the working directory
+        let mut patch = String::new();
+        let err = Action::Apply
+            .correct_file(
+                path.clone(),
+                bandaids,
+                &Writeback::default(),
+                Some(&mut patch),
+            )
+            .expect_err("Must refuse to render the diff");
+        assert!(err.to_string().contains("outside of the working directory"));
+        assert!(patch.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn doc_test_spans_are_absolute() {
+        let dir = std::env::temp_dir().join(format!("spellcheck-doctest-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lib.rs");
+        const CONTENT: &str = "mod outer {\n    /// A unicrons doc.\n    struct X;\n}\n";
+        const CORRECTED: &str = "mod outer {\n    /// A unicorns doc.\n    struct X;\n}\n";
+        fs::write(&path, CONTENT).unwrap();
+
+        // the excerpt starts in the middle of the second line
+        let origin = ContentOrigin::RustDocTest(
+            path.clone(),
+            Span {
+                start: LineColumn { line: 2, column: 4 },
+                end: LineColumn {
+                    line: 3,
+                    column: 12,
+                },
+            },
+        );
+        let docs = Documentation::from((origin.clone(), CONTENT));
+        let chunk = &docs.iter().next().expect("Must contain the excerpt").1[0];
+        let start = chunk
+            .as_str()
+            .find("unicrons")
+            .expect("Must contain the word");
+        let range = start..(start + 8);
+        let (_, span) = chunk
+            .find_spans(range.clone())
+            .into_iter()
+            .next()
+            .expect("Must map to a span");
+        assert_eq!(span, (1usize, 6..14).try_into().unwrap());
+        let absolute: Span = (2usize, 10..18).try_into().unwrap();
+        assert_eq!(origin.absolute_span(span), absolute);
+
+        let suggestion = Suggestion {
+            detector: Detector::Dummy,
+            origin: origin.clone(),
+            chunk,
+            span,
+            range,
+            replacements: vec!["unicorns".to_owned()],
+            description: None,
+        };
+        let displayed = suggestion.to_string();
+        let displayed = console::strip_ansi_codes(&displayed);
+        assert!(displayed.contains(&format!("{}:2\n", path.display())));
+        assert!(displayed.contains(" 2 |  A unicrons doc."));
+
+        let mut set = SuggestionSet::new();
+        set.add(origin.clone(), suggestion);
+        let mut report = Report::new(&set, &Config::default()).unwrap();
+        assert_eq!(report.entries()[0].span, absolute);
+
+        // applied as picked interactively
+        let picked = UserPicked::select_first(set);
+        Action::Apply
+            .write_changes_to_disk(picked, &Config::default(), &Writeback::default())
+            .expect("Must apply the fix");
+        assert_eq!(fs::read_to_string(&path).unwrap(), CORRECTED);
+
+        // applied from a report
+        fs::write(&path, CONTENT).unwrap();
+        let exported = dir.join("report.json");
+        fs::write(
+            &exported,
+            report
+                .to_string()
+                .replace(r#""replacement": null"#, r#""replacement": 0"#),
+        )
+        .unwrap();
+        report = Report::load(&exported).unwrap();
+        let (picked, applied, skipped) = report.pick(&docs);
+        assert_eq!((applied, skipped.len()), (1, 0));
+        Action::Apply
+            .write_changes_to_disk(picked, &Config::default(), &Writeback::default())
+            .expect("Must apply the fix");
+        assert_eq!(fs::read_to_string(&path).unwrap(), CORRECTED);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn overlapping_findings() {
+        let dir = traverse::cwd()
+            .unwrap()
+            .join("target")
+            .join(format!("spellcheck-overlap-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lib.rs");
+        fs::write(
+            &path,
+            "/// I like unicrons\n/// every second\n/// Monday.\nstruct X;\n",
+        )
+        .unwrap();
+        let bandaid = |line: usize, columns: Range, replacement: &str| BandAid {
+            span: (line, columns).try_into().unwrap(),
+            replacement: replacement.to_owned(),
+        };
+        let picked = UserPicked {
+            bandaids: indexmap::indexmap! {
+                ContentOrigin::RustSourceFile(path.clone()) => vec![
+                    vec![bandaid(1, 11..19, "unicorns")],
+                    // overlaps the first finding with its first fragment only
+                    vec![bandaid(1, 15..19, "corns"), bandaid(2, 4..9, "each")],
+                    vec![bandaid(2, 10..16, "other"), bandaid(3, 4..10, "day")],
+                ],
+            },
+        };
+        Action::Fix
+            .write_changes_to_disk(picked, &Config::default(), &Writeback::default())
+            .expect("Must be able to fix the file");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "/// I like unicorns\n/// every other\n/// day.\nstruct X;\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
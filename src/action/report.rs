@@ -0,0 +1,796 @@
+//! Findings as JSON report.
+//!
+//! `check --format=json` exports all findings, a reviewer picks one
+//! replacement per finding by filling in its `replacement`, either with the
+//! replacement itself or its index in `replacements`, and `fix --from`
+//! applies the picked ones if the text under their span is still the same.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "findings": [
+//!     {
+//!       "detector": "Hunspell",
+//!       "severity": "error",
+//!       "path": "src/lib.rs",
+//!       "span": {
+//!         "start": { "line": 1, "column": 11 },
+//!         "end": { "line": 1, "column": 18 }
+//!       },
+//!       "text": "unicrons",
+//!       "replacements": ["unicorns", "unicron"],
+//!       "description": "Possible spelling mistake found.",
+//!       "replacement": null
+//!     }
+//!   ]
+//! }
+//! ```
+
+use super::*;
+
+use std::fmt;
+use std::path::Path;
+
+/// Version of the report format.
+const VERSION: usize = 1;
+
+/// A finding of a report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Which checker reported the finding.
+    pub detector: Detector,
+    /// Severity of the finding, as configured when it was reported.
+    pub severity: Severity,
+    /// The file, relative to the working directory if it is located below.
+    pub path: PathBuf,
+    /// The span within the file.
+    pub span: Span,
+    /// Content of the doc comment covered by the span.
+    pub text: String,
+    /// All suggested replacements.
+    pub replacements: Vec<String>,
+    /// Descriptive reason for the finding.
+    pub description: Option<String>,
+    /// The replacement picked by a reviewer.
+    pub replacement: Option<String>,
+}
+
+/// A set of findings, as exported by `check --format=json`.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    entries: Vec<Entry>,
+}
+
+impl Report {
+    /// Collect all suggestions.
+    pub fn new(suggestions_per_path: &SuggestionSet, config: &Config) -> Result<Self> {
+        let cwd = traverse::cwd()?;
+        let mut entries = Vec::with_capacity(suggestions_per_path.total_count());
+        for (origin, suggestions) in suggestions_per_path.iter() {
+            let path = origin.as_path();
+            let path = path.strip_prefix(&cwd).unwrap_or(path);
+            entries.extend(suggestions.iter().map(|suggestion| Entry {
+                detector: suggestion.detector,
+                severity: config.severity(suggestion.detector),
+                path: path.to_owned(),
+                span: origin.absolute_span(suggestion.span),
+                text: util::sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                replacements: suggestion.replacements.clone(),
+                description: suggestion.description.clone(),
+                replacement: None,
+            }));
+        }
+        Ok(Self { entries })
+    }
+
+    /// Load a report from disk.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read report {}", path.display()).context(e))?;
+        Self::parse(&content)
+            .map_err(|e| e.context(anyhow!("Failed to parse report {}", path.display())))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let json = Json::parse(content)?;
+        match json.get("version").and_then(Json::as_usize) {
+            Some(VERSION) => {}
+            Some(version) => bail!("Unsupported report version {}", version),
+            None => bail!("Missing report version"),
+        }
+        let entries = json
+            .get("findings")
+            .and_then(Json::as_array)
+            .ok_or_else(|| anyhow!("Missing `findings`"))?
+            .iter()
+            .enumerate()
+            .map(|(idx, finding)| {
+                Entry::from_json(finding).map_err(|e| e.context(anyhow!("Finding #{}", idx + 1)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { entries })
+    }
+
+    /// All findings of the report.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Files with at least one picked replacement.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths = self
+            .entries
+            .iter()
+            .filter(|entry| entry.replacement.is_some())
+            .map(|entry| entry.path.clone())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// Convert the picked replacements into bandaids for `docs`, which must
+    /// be freshly extracted from the files.
+    ///
+    /// Returns the picked bandaids, the number of findings they cover,
+    /// and all findings which can not be applied with the reason. This
+    /// includes replacements the literal can not represent, i.e. a newline
+    /// in a `///` comment.
+    pub fn pick<'r>(
+        &'r self,
+        docs: &Documentation,
+    ) -> (UserPicked, usize, Vec<(&'r Entry, String)>) {
+        let cwd = traverse::cwd().unwrap_or_default();
+        let mut applicable = IndexMap::<&ContentOrigin, Vec<(&Entry, Vec<BandAid>)>>::new();
+        let mut skipped = Vec::new();
+        for entry in self.entries.iter() {
+            let replacement = if let Some(replacement) = entry.replacement.as_ref() {
+                replacement
+            } else {
+                continue;
+            };
+            let path = cwd.join(&entry.path);
+            let path = path.canonicalize().unwrap_or(path);
+            let found = docs
+                .iter()
+                .filter(|(origin, _)| origin.as_path() == path)
+                .find_map(|(origin, chunks)| {
+                    // spans of a doc-test are relative to its excerpt
+                    let span = origin.relative_span(entry.span).ok()?;
+                    chunks.iter().find_map(|chunk| {
+                        let start = Span {
+                            start: span.start,
+                            end: span.start,
+                        }
+                        .to_content_range(chunk)
+                        .ok()?;
+                        let end = Span {
+                            start: span.end,
+                            end: span.end,
+                        }
+                        .to_content_range(chunk)
+                        .ok()?;
+                        Some((origin, chunk, span, start.start..end.end))
+                    })
+                });
+            let (origin, chunk, span, range) = if let Some(found) = found {
+                found
+            } else {
+                skipped.push((entry, "the span is not part of a doc comment".to_owned()));
+                continue;
+            };
+            if util::sub_chars(chunk.as_str(), range.clone()) != entry.text {
+                skipped.push((entry, "the text under the span changed".to_owned()));
+                continue;
+            }
+            let suggestion = Suggestion {
+                detector: entry.detector,
+                origin: origin.clone(),
+                chunk,
+                span,
+                range,
+                replacements: vec![replacement.clone()],
+                description: None,
+            };
+            match BandAid::distribute(&suggestion, replacement) {
+                Ok(bandaids) => applicable
+                    .entry(origin)
+                    .or_insert_with(Vec::new)
+                    .push((entry, bandaids)),
+                Err(e) => skipped.push((entry, e.to_string())),
+            }
+        }
+
+        let mut picked = UserPicked::default();
+        let mut applied = 0usize;
+        for (origin, mut fixes) in applicable {
+            fixes.sort_by_key(|(entry, _)| (entry.span.start.line, entry.span.start.column));
+            let mut covered: Option<LineColumn> = None;
+            for (entry, bandaids) in fixes {
+                let start = entry.span.start;
+                if let Some(end) = covered {
+                    if (start.line, start.column) <= (end.line, end.column) {
+                        skipped.push((entry, "it overlaps another picked finding".to_owned()));
+                        continue;
+                    }
+                }
+                covered = Some(entry.span.end);
+                applied += 1;
+                picked
+                    .bandaids
+                    .entry(origin.clone())
+                    .or_insert_with(Vec::new)
+                    .push(bandaids);
+            }
+        }
+        (picked, applied, skipped)
+    }
+}
+
+impl Report {
+    /// Apply the picked replacements which still match the files of `docs`,
+    /// all others are listed with the reason.
+    pub fn apply(
+        &self,
+        docs: &Documentation,
+        config: &Config,
+        writeback: &Writeback,
+    ) -> Result<Finish> {
+        let (picked, applied, skipped) = self.pick(docs);
+        for (entry, reason) in skipped.iter() {
+            // stdout might carry a diff
+            eprintln!(
+                "{}:{}: not applied, {}",
+                entry.path.display(),
+                entry.span.start.line,
+                reason
+            );
+        }
+        Action::Fix.write_changes_to_disk(picked, config, writeback)?;
+        Ok(Finish::Fixed {
+            applied,
+            remaining: skipped.len(),
+        })
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let report = Json::Object(vec![
+            ("version".to_owned(), Json::Number(VERSION as f64)),
+            (
+                "findings".to_owned(),
+                Json::Array(self.entries.iter().map(Entry::to_json).collect()),
+            ),
+        ]);
+        write!(formatter, "{}", report)
+    }
+}
+
+impl Entry {
+    fn to_json(&self) -> Json {
+        let position = |position: LineColumn| {
+            Json::Object(vec![
+                ("line".to_owned(), Json::Number(position.line as f64)),
+                ("column".to_owned(), Json::Number(position.column as f64)),
+            ])
+        };
+        let string = |s: &str| Json::String(s.to_owned());
+        Json::Object(vec![
+            ("detector".to_owned(), string(&self.detector.to_string())),
+            ("severity".to_owned(), string(&self.severity.to_string())),
+            (
+                "path".to_owned(),
+                string(&self.path.to_string_lossy().replace('\\', "/")),
+            ),
+            (
+                "span".to_owned(),
+                Json::Object(vec![
+                    ("start".to_owned(), position(self.span.start)),
+                    ("end".to_owned(), position(self.span.end)),
+                ]),
+            ),
+            ("text".to_owned(), string(&self.text)),
+            (
+                "replacements".to_owned(),
+                Json::Array(self.replacements.iter().map(|s| string(s)).collect()),
+            ),
+            (
+                "description".to_owned(),
+                self.description.as_deref().map_or(Json::Null, string),
+            ),
+            (
+                "replacement".to_owned(),
+                self.replacement.as_deref().map_or(Json::Null, string),
+            ),
+        ])
+    }
+
+    fn from_json(json: &Json) -> Result<Self> {
+        let field = |name: &str| json.get(name).ok_or_else(|| anyhow!("Missing `{}`", name));
+        let string = |name: &str| {
+            field(name)?
+                .as_str()
+                .map(ToOwned::to_owned)
+                .ok_or_else(|| anyhow!("`{}` must be a string", name))
+        };
+        let position = |name: &str| -> Result<LineColumn> {
+            let position = field("span")?
+                .get(name)
+                .ok_or_else(|| anyhow!("Missing `span.{}`", name))?;
+            let number = |key: &str| {
+                position
+                    .get(key)
+                    .and_then(Json::as_usize)
+                    .ok_or_else(|| anyhow!("`span.{}.{}` must be a number", name, key))
+            };
+            Ok(LineColumn {
+                line: number("line")?,
+                column: number("column")?,
+            })
+        };
+
+        let detector = toml::Value::String(string("detector")?.to_lowercase())
+            .try_into::<Detector>()
+            .map_err(|e| anyhow!("Unknown `detector`").context(e))?;
+        let severity = match json.get("severity") {
+            None | Some(Json::Null) => Severity::Error,
+            Some(_) => toml::Value::String(string("severity")?.to_lowercase())
+                .try_into::<Severity>()
+                .map_err(|e| anyhow!("Unknown `severity`").context(e))?,
+        };
+        let replacements = field("replacements")?
+            .as_array()
+            .ok_or_else(|| anyhow!("`replacements` must be an array"))?
+            .iter()
+            .map(|replacement| {
+                replacement
+                    .as_str()
+                    .map(ToOwned::to_owned)
+                    .ok_or_else(|| anyhow!("`replacements` must only contain strings"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let description = match json.get("description") {
+            None | Some(Json::Null) => None,
+            Some(_) => Some(string("description")?),
+        };
+        // either the replacement itself or its index
+        let replacement = match json.get("replacement") {
+            None | Some(Json::Null) => None,
+            Some(Json::String(replacement)) => Some(replacement.clone()),
+            Some(Json::Number(_)) => {
+                let idx = field("replacement")?
+                    .as_usize()
+                    .ok_or_else(|| anyhow!("`replacement` must be a valid index"))?;
+                let replacement = replacements.get(idx).ok_or_else(|| {
+                    anyhow!(
+                        "`replacement` index {} exceeds the {} replacements",
+                        idx,
+                        replacements.len()
+                    )
+                })?;
+                Some(replacement.clone())
+            }
+            Some(_) => bail!("`replacement` must be a string, an index or `null`"),
+        };
+        Ok(Self {
+            detector,
+            severity,
+            path: PathBuf::from(string("path")?),
+            span: Span {
+                start: position("start")?,
+                end: position("end")?,
+            },
+            text: string("text")?,
+            replacements,
+            description,
+            replacement,
+        })
+    }
+}
+
+/// A JSON value, as far as needed for the reports.
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Members in order of appearance.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Self::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    fn as_usize(&self) -> Option<usize> {
+        match *self {
+            Self::Number(n) if n >= 0. && n.fract() == 0. => Some(n as usize),
+            _ => None,
+        }
+    }
+
+    fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Self::Array(values) => Some(values.as_slice()),
+            _ => None,
+        }
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let mut parser = Parser {
+            content: content.as_bytes(),
+            pos: 0,
+        };
+        let value = parser.value()?;
+        parser.whitespace();
+        if parser.pos < parser.content.len() {
+            bail!("Trailing content at byte {}", parser.pos);
+        }
+        Ok(value)
+    }
+
+    /// Serialize into `out`, pretty printed at the given indentation or
+    /// compact without one. Arrays and objects which fit into a line are
+    /// kept compact regardless.
+    fn write(&self, out: &mut String, indent: Option<usize>) {
+        match self {
+            Self::Null => out.push_str("null"),
+            Self::Bool(b) => out.push_str(&b.to_string()),
+            Self::Number(n) => out.push_str(&n.to_string()),
+            Self::String(s) => {
+                out.push('"');
+                for c in s.chars() {
+                    match c {
+                        '"' => out.push_str("\\\""),
+                        '\\' => out.push_str("\\\\"),
+                        '\n' => out.push_str("\\n"),
+                        '\r' => out.push_str("\\r"),
+                        '\t' => out.push_str("\\t"),
+                        c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+                        c => out.push(c),
+                    }
+                }
+                out.push('"');
+            }
+            Self::Array(_) | Self::Object(_) => {
+                let mut compact = String::new();
+                self.write_members(&mut compact, None);
+                match indent {
+                    Some(indent) if indent + compact.len() > 80 => {
+                        self.write_members(out, Some(indent))
+                    }
+                    _ => out.push_str(&compact),
+                }
+            }
+        }
+    }
+
+    fn write_members(&self, out: &mut String, indent: Option<usize>) {
+        let (open, close, len) = match self {
+            Self::Array(values) => ('[', ']', values.len()),
+            Self::Object(members) => ('{', '}', members.len()),
+            _ => unreachable!("Only arrays and objects have members"),
+        };
+        out.push(open);
+        for idx in 0..len {
+            match indent {
+                Some(indent) => {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(indent + 2));
+                }
+                None if idx > 0 => out.push(' '),
+                None if open == '{' => out.push(' '),
+                None => {}
+            }
+            let indent = indent.map(|indent| indent + 2);
+            match self {
+                Self::Array(values) => values[idx].write(out, indent),
+                Self::Object(members) => {
+                    Self::String(members[idx].0.clone()).write(out, None);
+                    out.push_str(": ");
+                    members[idx].1.write(out, indent);
+                }
+                _ => unreachable!("Only arrays and objects have members"),
+            }
+            if idx + 1 < len {
+                out.push(',');
+            }
+        }
+        match indent {
+            Some(indent) if len > 0 => {
+                out.push('\n');
+                out.push_str(&" ".repeat(indent));
+            }
+            None if len > 0 && open == '{' => out.push(' '),
+            _ => {}
+        }
+        out.push(close);
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        self.write(&mut out, Some(0));
+        formatter.write_str(&out)
+    }
+}
+
+/// Recursive descent parser of a JSON document.
+struct Parser<'a> {
+    content: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.content.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if self.content[self.pos..].starts_with(token.as_bytes()) {
+            self.pos += token.len();
+            Ok(())
+        } else {
+            bail!("Expected `{}` at byte {}", token, self.pos)
+        }
+    }
+
+    fn value(&mut self) -> Result<Json> {
+        self.whitespace();
+        match self.peek() {
+            Some(b'n') => self.expect("null").map(|_| Json::Null),
+            Some(b't') => self.expect("true").map(|_| Json::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                self.whitespace();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Json::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    self.whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Json::Array(values));
+                        }
+                        _ => bail!("Expected `,` or `]` at byte {}", self.pos),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                self.whitespace();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Json::Object(members));
+                }
+                loop {
+                    self.whitespace();
+                    let name = self.string()?;
+                    self.whitespace();
+                    self.expect(":")?;
+                    members.push((name, self.value()?));
+                    self.whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Json::Object(members));
+                        }
+                        _ => bail!("Expected `,` or `}}` at byte {}", self.pos),
+                    }
+                }
+            }
+            Some(b'-') | Some(b'0'..=b'9') => {
+                let start = self.pos;
+                while let Some(b'-') | Some(b'+') | Some(b'.') | Some(b'e') | Some(b'E')
+                | Some(b'0'..=b'9') = self.peek()
+                {
+                    self.pos += 1;
+                }
+                let number = std::str::from_utf8(&self.content[start..self.pos])?;
+                Ok(Json::Number(number.parse().map_err(|_| {
+                    anyhow!("Invalid number `{}` at byte {}", number, start)
+                })?))
+            }
+            Some(_) => bail!("Unexpected character at byte {}", self.pos),
+            None => bail!("Unexpected end of input"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.expect("\"")?;
+        let mut s = Vec::new();
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(String::from_utf8(s)?);
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escaped = if self.peek() == Some(b'u') {
+                        let high = self.hex()?;
+                        let code = if (0xD800..0xDC00).contains(&high) {
+                            // a surrogate pair
+                            self.expect("\\")?;
+                            let low = self.hex()?;
+                            0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF)
+                        } else {
+                            high
+                        };
+                        std::char::from_u32(code)
+                            .ok_or_else(|| anyhow!("Invalid escape at byte {}", self.pos))?
+                    } else {
+                        let escaped = match self.peek() {
+                            Some(b'"') => '"',
+                            Some(b'\\') => '\\',
+                            Some(b'/') => '/',
+                            Some(b'b') => '\u{8}',
+                            Some(b'f') => '\u{c}',
+                            Some(b'n') => '\n',
+                            Some(b'r') => '\r',
+                            Some(b't') => '\t',
+                            _ => bail!("Invalid escape at byte {}", self.pos),
+                        };
+                        self.pos += 1;
+                        escaped
+                    };
+                    let mut buf = [0u8; 4];
+                    s.extend_from_slice(escaped.encode_utf8(&mut buf).as_bytes());
+                }
+                Some(b) => {
+                    self.pos += 1;
+                    s.push(b);
+                }
+                None => bail!("Unterminated string"),
+            }
+        }
+    }
+
+    /// Parse `uXXXX`, the position is past it afterwards.
+    fn hex(&mut self) -> Result<u32> {
+        self.expect("u")?;
+        let digits = self
+            .content
+            .get(self.pos..self.pos + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| anyhow!("Invalid unicode escape at byte {}", self.pos))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fluff_up;
+
+    #[test]
+    fn json_roundtrip() {
+        const CONTENT: &str = r#" {"a": [1, -2.5e1, true, false, null, {}, []],
+            "b\"\\": "x\nyä😀/" } "#;
+        let json = Json::parse(CONTENT).expect("Must be valid JSON");
+        assert_eq!(json.get("b\"\\").and_then(Json::as_str), Some("x\nyä😀/"));
+        assert_eq!(
+            json.get("a").and_then(Json::as_array).map(|a| a[1].clone()),
+            Some(Json::Number(-25.))
+        );
+        assert_eq!(Json::parse(&json.to_string()).unwrap(), json);
+
+        assert!(Json::parse(r#"{"a": 1,}"#).is_err());
+        assert!(Json::parse(r#"{"a": "#).is_err());
+        assert!(Json::parse(r#"[1] 2"#).is_err());
+    }
+
+    #[test]
+    fn pick_replacements() {
+        const TEXT: &str = fluff_up!["I like unicrons every", "second Mondays."];
+        let docs = Documentation::from((ContentOrigin::TestEntityRust, TEXT));
+        let path = ContentOrigin::TestEntityRust.as_path().to_owned();
+        let finding = |line: usize, columns: Range, text: &str, replacement: &str| {
+            format!(
+                r#"{{
+                    "detector": "Hunspell",
+                    "path": "{}",
+                    "span": {{
+                        "start": {{ "line": {}, "column": {} }},
+                        "end": {{ "line": {}, "column": {} }}
+                    }},
+                    "text": "{}",
+                    "replacements": ["alpha", "beta"],
+                    "description": null,
+                    "replacement": {}
+                }}"#,
+                path.display(),
+                line,
+                columns.start,
+                line,
+                columns.end - 1,
+                text,
+                replacement
+            )
+        };
+        let report = format!(
+            r#"{{ "version": 1, "findings": [{}] }}"#,
+            [
+                finding(1, 11..19, "unicrons", r#""unicorns""#),
+                // not picked
+                finding(1, 20..25, "every", "null"),
+                // the text changed
+                finding(2, 4..10, "first", r#""third""#),
+                // picked by index
+                finding(2, 11..19, "Mondays.", "1"),
+                // a line comment can not span multiple lines
+                finding(1, 6..10, "like", r#""really\nlike""#),
+            ]
+            .join(",")
+        );
+        let report = Report::parse(&report).expect("Must be a valid report");
+        assert_eq!(report.entries().len(), 5);
+        assert_eq!(report.entries()[3].replacement, Some("beta".to_owned()));
+        assert_eq!(
+            report.entries()[4].replacement,
+            Some("really\nlike".to_owned())
+        );
+        assert_eq!(report.paths(), vec![path.clone()]);
+
+        let (picked, applied, skipped) = report.pick(&docs);
+        assert_eq!(applied, 2);
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(skipped[0].0.text, "first");
+        assert_eq!(skipped[1].0.text, "like");
+        assert!(skipped[1].1.contains("newline"));
+        let replacements = picked
+            .bandaids
+            .values()
+            .flatten()
+            .flatten()
+            .map(|bandaid| bandaid.replacement.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(replacements, vec!["unicorns", "beta"]);
+
+        // exported and loaded again
+        let exported = Report::parse(&report.to_string()).expect("Must parse the export");
+        assert_eq!(exported.entries(), report.entries());
+
+        assert!(Report::parse(r#"{ "version": 2, "findings": [] }"#).is_err());
+        assert!(Report::parse(&format!(
+            r#"{{ "version": 1, "findings": [{}] }}"#,
+            finding(1, 11..19, "unicrons", "2")
+        ))
+        .is_err());
+    }
+}
@@ -0,0 +1,407 @@
+//! Decisions of an interactive fix session.
+//!
+//! Every decision is written to `target/spellcheck/session.toml` as soon as
+//! it is made, so quitting or getting interrupted does not lose the work done
+//! so far. Decisions are stored per file, together with the digest of its
+//! content; those of a file which changed in the meantime are dropped.
+
+use super::*;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Name of the file within the cache directory.
+const FILE_NAME: &str = "session.toml";
+
+/// What the user decided on a finding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// Fix it with these bandaids.
+    Accepted(Vec<BandAid>),
+    /// Leave the finding as is.
+    Skipped,
+    /// Leave the finding as is, as well as all other ones in the file.
+    Ignored,
+}
+
+/// How a decision is persisted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    Accepted,
+    Skipped,
+    Ignored,
+}
+
+/// A persisted bandaid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Fix {
+    /// Start line and column.
+    start: [usize; 2],
+    /// End line and column, inclusive.
+    end: [usize; 2],
+    replacement: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    decision: Kind,
+    bandaids: Vec<Fix>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileEntry {
+    path: PathBuf,
+    digest: String,
+    decisions: Vec<Entry>,
+}
+
+/// The persisted content of a session.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Store {
+    version: String,
+    files: Vec<FileEntry>,
+}
+
+/// Decisions on the findings of a single file.
+#[derive(Debug, Clone, Default)]
+struct Decisions {
+    digest: u64,
+    decisions: IndexMap<String, Decision>,
+}
+
+/// The decisions made so far, by file.
+///
+/// The default session is not persisted.
+#[derive(Debug, Default)]
+pub struct Session {
+    /// Where the session is stored.
+    path: Option<PathBuf>,
+    /// Digests of the file contents at the time they were parsed.
+    digests: IndexMap<PathBuf, u64>,
+    files: IndexMap<PathBuf, Decisions>,
+}
+
+impl Session {
+    /// Start a new session stored in `dir`.
+    ///
+    /// Errors if there is an unfinished session, which must be resumed or
+    /// discarded explicitly.
+    pub fn start(dir: &Path, digests: &IndexMap<PathBuf, u64>) -> Result<Self> {
+        let path = dir.join(FILE_NAME);
+        if path.exists() {
+            bail!(
+                "There is an unfinished fix session in {}, continue it with `--resume` or discard it with `--restart`",
+                dir.display()
+            );
+        }
+        Ok(Self {
+            path: Some(path),
+            digests: digests.clone(),
+            files: IndexMap::new(),
+        })
+    }
+
+    /// Start a new session stored in `dir`, discarding any previous one.
+    pub fn restart(dir: &Path, digests: &IndexMap<PathBuf, u64>) -> Result<Self> {
+        let path = dir.join(FILE_NAME);
+        if let Err(e) = fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Err(anyhow!("Failed to remove session {}", path.display()).context(e));
+            }
+        }
+        Self::start(dir, digests)
+    }
+
+    /// Continue the session stored in `dir`.
+    ///
+    /// Decisions on files which changed since are dropped.
+    pub fn resume(dir: &Path, digests: &IndexMap<PathBuf, u64>) -> Result<Self> {
+        let path = dir.join(FILE_NAME);
+        let content = fs::read_to_string(&path).map_err(|e| {
+            anyhow!("There is no fix session to resume in {}", dir.display()).context(e)
+        })?;
+        let store = toml::from_str::<Store>(&content)
+            .with_context(|| format!("Failed to parse session {}", path.display()))?;
+        if store.version != env!("CARGO_PKG_VERSION") {
+            bail!(
+                "Session {} was written by version {}, it can not be resumed",
+                path.display(),
+                store.version
+            );
+        }
+        let mut session = Self {
+            path: Some(path),
+            digests: digests.clone(),
+            files: IndexMap::new(),
+        };
+        for file in store.files {
+            let digest = u64::from_str_radix(&file.digest, 16)
+                .with_context(|| format!("Invalid digest of {}", file.path.display()))?;
+            if session.digest(&file.path) != Some(digest) {
+                warn!(
+                    "{} changed since the session was saved, reviewing it again",
+                    file.path.display()
+                );
+                continue;
+            }
+            let decisions = file
+                .decisions
+                .into_iter()
+                .map(|entry| {
+                    let decision = match entry.decision {
+                        Kind::Accepted => Decision::Accepted(
+                            entry
+                                .bandaids
+                                .into_iter()
+                                .map(|fix| BandAid {
+                                    span: Span {
+                                        start: LineColumn {
+                                            line: fix.start[0],
+                                            column: fix.start[1],
+                                        },
+                                        end: LineColumn {
+                                            line: fix.end[0],
+                                            column: fix.end[1],
+                                        },
+                                    },
+                                    replacement: fix.replacement,
+                                })
+                                .collect(),
+                        ),
+                        Kind::Skipped => Decision::Skipped,
+                        Kind::Ignored => Decision::Ignored,
+                    };
+                    (entry.key, decision)
+                })
+                .collect();
+            session
+                .files
+                .insert(file.path, Decisions { digest, decisions });
+        }
+        debug!("Resuming {} decisions", session.len());
+        Ok(session)
+    }
+
+    /// Keep further decisions in memory only, the stored session is neither
+    /// updated nor removed.
+    pub fn in_memory(mut self) -> Self {
+        self.path = None;
+        self
+    }
+
+    /// Total number of decisions.
+    pub fn len(&self) -> usize {
+        self.files.values().map(|file| file.decisions.len()).sum()
+    }
+
+    /// If no decision was made yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Digest of the file at `path` as parsed, or as it is now.
+    fn digest(&self, path: &Path) -> Option<u64> {
+        self.digests
+            .get(path)
+            .copied()
+            .or_else(|| fs::read(path).ok().map(|content| util::digest(&content)))
+    }
+
+    /// Identifies the finding within the file.
+    ///
+    /// A detector may report several findings on the same span, these are
+    /// told apart by their replacements and description.
+    fn key(suggestion: &Suggestion) -> String {
+        let span = suggestion.origin.absolute_span(suggestion.span);
+        let details = format!("{:?}{:?}", suggestion.replacements, suggestion.description);
+        format!(
+            "{}@{}:{}-{}:{}#{}..{}#{:016x}",
+            suggestion.detector,
+            span.start.line,
+            span.start.column,
+            span.end.line,
+            span.end.column,
+            suggestion.range.start,
+            suggestion.range.end,
+            util::digest(details.as_bytes())
+        )
+    }
+
+    /// The decision made on the finding in a previous run, if any.
+    pub fn decision(&self, suggestion: &Suggestion) -> Option<&Decision> {
+        self.files
+            .get(suggestion.origin.as_path())
+            .and_then(|file| file.decisions.get(&Self::key(suggestion)))
+    }
+
+    /// Note the decision, without storing it yet.
+    pub fn decide(&mut self, suggestion: &Suggestion, decision: Decision) {
+        let path = suggestion.origin.as_path();
+        let digest = match self.digest(path) {
+            Some(digest) => digest,
+            None => {
+                debug!("Not keeping decisions on unreadable {}", path.display());
+                return;
+            }
+        };
+        self.files
+            .entry(path.to_owned())
+            .or_insert_with(|| Decisions {
+                digest,
+                decisions: IndexMap::new(),
+            })
+            .decisions
+            .insert(Self::key(suggestion), decision);
+    }
+
+    /// Note the decision and store the session.
+    pub fn record(&mut self, suggestion: &Suggestion, decision: Decision) -> Result<()> {
+        self.decide(suggestion, decision);
+        self.save()
+    }
+
+    /// Store the session, if it is persisted at all.
+    pub fn save(&self) -> Result<()> {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let files = self
+            .files
+            .iter()
+            .map(|(path, file)| FileEntry {
+                path: path.clone(),
+                digest: format!("{:016x}", file.digest),
+                decisions: file
+                    .decisions
+                    .iter()
+                    .map(|(key, decision)| {
+                        let (kind, bandaids) = match decision {
+                            Decision::Accepted(bandaids) => (Kind::Accepted, bandaids.as_slice()),
+                            Decision::Skipped => (Kind::Skipped, &[][..]),
+                            Decision::Ignored => (Kind::Ignored, &[][..]),
+                        };
+                        Entry {
+                            key: key.clone(),
+                            decision: kind,
+                            bandaids: bandaids
+                                .iter()
+                                .map(|bandaid| Fix {
+                                    start: [bandaid.span.start.line, bandaid.span.start.column],
+                                    end: [bandaid.span.end.line, bandaid.span.end.column],
+                                    replacement: bandaid.replacement.clone(),
+                                })
+                                .collect(),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        let store = Store {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            files,
+        };
+        let dir = path
+            .parent()
+            .expect("Session file always resides in a directory");
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create session directory {}", dir.display()))?;
+        // an interrupt must not leave a partially written session behind
+        let tmp = path.with_extension("toml.tmp");
+        fs::write(&tmp, toml::to_string(&store)?)?;
+        fs::rename(&tmp, path)?;
+        trace!("Stored {} decisions in {}", self.len(), path.display());
+        Ok(())
+    }
+
+    /// The session is complete, remove it.
+    pub fn finish(self) -> Result<()> {
+        if let Some(path) = self.path {
+            if let Err(e) = fs::remove_file(&path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    return Err(anyhow!(
+                        "Failed to remove the completed session {}",
+                        path.display()
+                    )
+                    .context(e));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unfinished_session() {
+        let dir =
+            std::env::temp_dir().join(format!("spellcheck-unfinished-{}", std::process::id()));
+        let path = dir.join(FILE_NAME);
+        let digests = IndexMap::new();
+
+        let session = Session::start(&dir, &digests).expect("There is no session yet");
+        session.save().unwrap();
+        let stored = fs::read_to_string(&path).expect("Must store the session");
+
+        // neither replaced silently, nor touched by a dry run
+        assert!(Session::start(&dir, &digests).is_err());
+        let session = Session::resume(&dir, &digests)
+            .expect("Must resume the session")
+            .in_memory();
+        session.save().unwrap();
+        session.finish().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), stored);
+
+        // discarded on request only
+        let session = Session::restart(&dir, &digests).expect("Must discard the session");
+        assert!(!path.exists());
+        session.finish().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn same_span() {
+        let dir = std::env::temp_dir().join(format!("spellcheck-same-span-{}", std::process::id()));
+        let path = dir.join("lib.rs");
+        const SOURCE: &str = "/// Use teh api.\nstruct X;\n";
+        let mut digests = IndexMap::new();
+        digests.insert(path.clone(), util::digest(SOURCE.as_bytes()));
+
+        let origin = ContentOrigin::RustSourceFile(path);
+        let docs = Documentation::from((origin.clone(), SOURCE));
+        let (_, chunks) = docs.iter().next().expect("Must contain one origin");
+        let chunk = &chunks[0];
+        let range = chunk.as_str().find("teh").unwrap()..chunk.as_str().find(" api").unwrap();
+        let (_, span) = chunk.find_spans(range.clone()).into_iter().next().unwrap();
+        let finding = |replacement: &str, description: &str| Suggestion {
+            detector: Detector::Terminology,
+            origin: origin.clone(),
+            chunk,
+            span,
+            range: range.clone(),
+            replacements: vec![replacement.to_owned()],
+            description: Some(description.to_owned()),
+        };
+        let misspelled = finding("the", "Misspelled");
+        let preferred = finding("this", "Preferred term");
+
+        let mut session = Session::start(&dir, &digests).unwrap();
+        let accepted = Decision::Accepted(vec![BandAid::new("the", &span)]);
+        session.record(&misspelled, accepted.clone()).unwrap();
+        session.record(&preferred, Decision::Skipped).unwrap();
+
+        let session = Session::resume(&dir, &digests).expect("Must resume the session");
+        assert_eq!(session.len(), 2);
+        assert_eq!(session.decision(&misspelled), Some(&accepted));
+        assert_eq!(session.decision(&preferred), Some(&Decision::Skipped));
+
+        session.finish().unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
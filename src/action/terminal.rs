@@ -0,0 +1,258 @@
+//! Where the interactive selection reads keys from and draws to.
+//!
+//! Usually that is the terminal, but the keys can also be replayed
+//! from a recorded key script.
+//!
+//! A key script consists of whitespace separated keys, where
+//!
+//! * a bare word is typed character by character, so `nny` skips two
+//!   suggestions and applies the third one,
+//! * `<name>` is a named key, one of `enter`, `esc`, `up`, `down`, `left`,
+//!   `right`, `pageup`, `pagedown`, `backspace`, `tab`, `space` or `c-c` for
+//!   `Ctrl+C`,
+//! * `"text"` is typed as is, including whitespace, `\"` and `\\` escape a
+//!   quote or backslash,
+//! * a line starting with `#` is a comment.
+
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, Stdout, Write};
+use std::path::Path;
+
+/// Source of key events and sink of the rendered prompt.
+pub trait Terminal {
+    /// Wait for the next event.
+    fn read(&mut self) -> Result<Event>;
+
+    /// Where the prompt is drawn to.
+    fn sink(&mut self) -> &mut dyn Write;
+
+    /// If the terminal must be in raw mode while waiting for keys.
+    fn raw_mode(&self) -> bool;
+
+    /// Number of columns and rows, if the full screen can be used.
+    ///
+    /// Otherwise only the last lines are redrawn.
+    fn size(&self) -> Option<(u16, u16)>;
+}
+
+/// The terminal attached to `stdin` and `stdout`.
+#[derive(Debug)]
+pub struct Tty {
+    stdout: Stdout,
+}
+
+impl Default for Tty {
+    fn default() -> Self {
+        Self {
+            stdout: io::stdout(),
+        }
+    }
+}
+
+impl Terminal for Tty {
+    fn read(&mut self) -> Result<Event> {
+        crossterm::event::read()
+            .map_err(|e| anyhow!("Something unexpected happened on the CLI: {}", e))
+    }
+
+    fn sink(&mut self) -> &mut dyn Write {
+        &mut self.stdout
+    }
+
+    fn raw_mode(&self) -> bool {
+        true
+    }
+
+    fn size(&self) -> Option<(u16, u16)> {
+        if console::Term::stdout().is_term() {
+            crossterm::terminal::size().ok()
+        } else {
+            None
+        }
+    }
+}
+
+/// Replays the keys of a key script.
+#[derive(Debug)]
+pub struct KeyScript<W> {
+    keys: VecDeque<KeyEvent>,
+    sink: W,
+    size: Option<(u16, u16)>,
+}
+
+impl<W: Write> KeyScript<W> {
+    /// Parse the key script `script`, the prompt is drawn to `sink`.
+    pub fn parse(script: &str, sink: W) -> Result<Self> {
+        let mut keys = VecDeque::new();
+        for (lineno, line) in script.lines().enumerate() {
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+            parse_line(line, &mut keys)
+                .map_err(|e| e.context(format!("Invalid key script line {}", lineno + 1)))?;
+        }
+        Ok(Self {
+            keys,
+            sink,
+            size: None,
+        })
+    }
+
+    /// Load the key script stored at `path`.
+    pub fn load(path: impl AsRef<Path>, sink: W) -> Result<Self> {
+        let path = path.as_ref();
+        let script = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read key script {}", path.display()).context(e))?;
+        Self::parse(&script, sink)
+    }
+
+    /// Draw on a full screen of the given size, rather than in line mode.
+    pub fn with_size(mut self, columns: u16, rows: u16) -> Self {
+        self.size = Some((columns, rows));
+        self
+    }
+
+    /// The number of keys not replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Access whatever was drawn so far.
+    pub fn output(&self) -> &W {
+        &self.sink
+    }
+}
+
+impl<W: Write> Terminal for KeyScript<W> {
+    fn read(&mut self) -> Result<Event> {
+        self.keys
+            .pop_front()
+            .map(Event::Key)
+            .ok_or_else(|| anyhow!("The key script ended before all suggestions were reviewed"))
+    }
+
+    fn sink(&mut self) -> &mut dyn Write {
+        &mut self.sink
+    }
+
+    fn raw_mode(&self) -> bool {
+        false
+    }
+
+    fn size(&self) -> Option<(u16, u16)> {
+        self.size
+    }
+}
+
+/// A key without modifiers.
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent {
+        code,
+        modifiers: KeyModifiers::NONE,
+    }
+}
+
+/// Resolve the name of a key as written in between `<` and `>`.
+fn named(name: &str) -> Result<KeyEvent> {
+    let code = match name.to_lowercase().as_str() {
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "backspace" => KeyCode::Backspace,
+        "tab" => KeyCode::Tab,
+        "space" => KeyCode::Char(' '),
+        "c-c" => {
+            return Ok(KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+            })
+        }
+        _ => bail!("Unknown key <{}>", name),
+    };
+    Ok(key(code))
+}
+
+/// Append all keys of a single line of a key script.
+fn parse_line(line: &str, keys: &mut VecDeque<KeyEvent>) -> Result<()> {
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '"' => loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some(c @ '"') | Some(c @ '\\') => keys.push_back(key(KeyCode::Char(c))),
+                        Some(c) => bail!("Unknown escape sequence \\{}", c),
+                        None => bail!("Unterminated text"),
+                    },
+                    Some(c) => keys.push_back(key(KeyCode::Char(c))),
+                    None => bail!("Unterminated text"),
+                }
+            },
+            '<' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('>') => break,
+                        Some(c) => name.push(c),
+                        None => bail!("Unterminated key name <{}", name),
+                    }
+                }
+                keys.push_back(named(&name)?);
+            }
+            c => keys.push_back(key(KeyCode::Char(c))),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_script() {
+        let script = r#"
+# skip two, then enter a custom replacement
+nn e "a b\"" <Enter>
+<c-c>
+"#;
+        let mut script = KeyScript::parse(script, Vec::new()).expect("Must parse");
+        let mut keys = Vec::new();
+        while let Ok(Event::Key(event)) = script.read() {
+            keys.push(event);
+        }
+        let ch = |c| key(KeyCode::Char(c));
+        assert_eq!(
+            keys,
+            vec![
+                ch('n'),
+                ch('n'),
+                ch('e'),
+                ch('a'),
+                ch(' '),
+                ch('b'),
+                ch('"'),
+                key(KeyCode::Enter),
+                KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
+                },
+            ]
+        );
+        assert_eq!(script.remaining(), 0);
+
+        assert!(KeyScript::parse("<enter", Vec::new()).is_err());
+        assert!(KeyScript::parse("<f13>", Vec::new()).is_err());
+        assert!(KeyScript::parse("\"abc", Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,585 @@
+//! Parsing of hunspell `.aff` and `.dic` files
+//!
+//! Covers the subset of `man 5 hunspell` which is required for checking
+//! and suggesting: encodings, flag formats and flag aliases, prefix and
+//! suffix rules, the suggestion tables and the basic compounding flags.
+
+use anyhow::{anyhow, bail, Result};
+use log::trace;
+use std::collections::HashMap;
+
+/// A single affix or property flag, independent of the flag format.
+pub(crate) type Flag = u32;
+
+/// A sorted set of flags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct FlagSet(Vec<Flag>);
+
+impl FlagSet {
+    fn new(mut flags: Vec<Flag>) -> Self {
+        flags.sort_unstable();
+        flags.dedup();
+        Self(flags)
+    }
+
+    pub(crate) fn contains(&self, flag: Option<Flag>) -> bool {
+        flag.map(|flag| self.0.binary_search(&flag).is_ok())
+            .unwrap_or(false)
+    }
+}
+
+/// How flags are represented in the affix and dictionary files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlagFormat {
+    /// One character per flag, the default.
+    Short,
+    /// Two characters per flag.
+    Long,
+    /// Comma separated decimal numbers.
+    Numeric,
+}
+
+impl FlagFormat {
+    fn parse(self, s: &str) -> Result<Vec<Flag>> {
+        match self {
+            Self::Short => Ok(s.chars().map(|c| c as Flag).collect()),
+            Self::Long => {
+                let chars = s.chars().collect::<Vec<char>>();
+                if chars.len() % 2 != 0 {
+                    bail!("Long flags must consist of two characters each: {}", s)
+                }
+                Ok(chars
+                    .chunks(2)
+                    .map(|pair| ((pair[0] as Flag) << 16) | pair[1] as Flag)
+                    .collect())
+            }
+            Self::Numeric => s
+                .split(',')
+                .map(|flag| {
+                    flag.trim()
+                        .parse::<Flag>()
+                        .map_err(|e| anyhow!("Invalid numeric flag {}: {}", flag, e))
+                })
+                .collect(),
+        }
+    }
+
+    fn parse_single(self, s: &str) -> Result<Flag> {
+        if self == Self::Short {
+            // like `libhunspell`, only the first character counts
+            return s
+                .chars()
+                .next()
+                .map(|c| c as Flag)
+                .ok_or_else(|| anyhow!("Expected a flag"));
+        }
+        let flags = self.parse(s)?;
+        if flags.len() != 1 {
+            bail!("Expected exactly one flag, found {}", s)
+        }
+        Ok(flags[0])
+    }
+}
+
+/// A single element of an affix condition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CondElem {
+    /// `.`
+    Any,
+    /// A literal character.
+    Char(char),
+    /// `[abc]` or `[^abc]`.
+    Class { negated: bool, chars: Vec<char> },
+}
+
+impl CondElem {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Char(x) => *x == c,
+            Self::Class { negated, chars } => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+/// The condition the root has to fulfil for an affix to apply,
+/// at its start for prefixes and at its end for suffixes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Condition(Vec<CondElem>);
+
+impl Condition {
+    fn parse(s: &str) -> Result<Self> {
+        if s == "." {
+            return Ok(Self::default());
+        }
+        let mut elems = Vec::with_capacity(s.len());
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            let elem = match c {
+                '.' => CondElem::Any,
+                '[' => {
+                    let mut class = Vec::with_capacity(8);
+                    let mut negated = false;
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some('^') if class.is_empty() && !negated => negated = true,
+                            Some(c) => class.push(c),
+                            None => bail!("Unterminated character class in condition {}", s),
+                        }
+                    }
+                    CondElem::Class {
+                        negated,
+                        chars: class,
+                    }
+                }
+                c => CondElem::Char(c),
+            };
+            elems.push(elem);
+        }
+        Ok(Self(elems))
+    }
+
+    pub(crate) fn matches_start(&self, word: &str) -> bool {
+        let mut chars = word.chars();
+        self.0
+            .iter()
+            .all(|elem| chars.next().map(|c| elem.matches(c)).unwrap_or(false))
+    }
+
+    pub(crate) fn matches_end(&self, word: &str) -> bool {
+        let mut chars = word.chars().rev();
+        self.0
+            .iter()
+            .rev()
+            .all(|elem| chars.next().map(|c| elem.matches(c)).unwrap_or(false))
+    }
+}
+
+/// A single prefix or suffix rule.
+#[derive(Debug, Clone)]
+pub(crate) struct Affix {
+    /// The flag a root must carry for this affix to apply.
+    pub(crate) flag: Flag,
+    /// Allow combining prefixes and suffixes.
+    pub(crate) cross_product: bool,
+    /// Characters removed from the root before adding `add`.
+    pub(crate) strip: String,
+    /// Characters added to the root.
+    pub(crate) add: String,
+    /// Continuation flags, affixes that may be applied on top of this one.
+    pub(crate) cont: FlagSet,
+    /// Condition of the root.
+    pub(crate) condition: Condition,
+}
+
+/// The relevant content of an `.aff` file.
+#[derive(Debug, Clone)]
+pub(crate) struct Aff {
+    flag_format: FlagFormat,
+    /// Flag aliases as defined by `AF`, referenced by their 1-based index.
+    aliases: Vec<FlagSet>,
+    pub(crate) prefixes: Vec<Affix>,
+    pub(crate) suffixes: Vec<Affix>,
+    /// Characters used to construct suggestions, ordered by frequency.
+    pub(crate) try_chars: Vec<char>,
+    /// Rows of neighbouring keys on the keyboard.
+    pub(crate) keys: Vec<Vec<char>>,
+    /// Common misspellings and their replacements.
+    pub(crate) replacements: Vec<(String, String)>,
+    /// Characters to be removed before checking.
+    pub(crate) ignore: Vec<char>,
+    pub(crate) need_affix: Option<Flag>,
+    pub(crate) forbidden: Option<Flag>,
+    pub(crate) no_suggest: Option<Flag>,
+    pub(crate) keep_case: Option<Flag>,
+    pub(crate) only_in_compound: Option<Flag>,
+    pub(crate) compound: Option<Flag>,
+    pub(crate) compound_begin: Option<Flag>,
+    pub(crate) compound_middle: Option<Flag>,
+    pub(crate) compound_end: Option<Flag>,
+    /// Minimal length of a compound part in characters.
+    pub(crate) compound_min: usize,
+    /// Allow affixes to strip the whole root.
+    pub(crate) full_strip: bool,
+}
+
+impl Default for Aff {
+    fn default() -> Self {
+        Self {
+            flag_format: FlagFormat::Short,
+            aliases: Vec::new(),
+            prefixes: Vec::new(),
+            suffixes: Vec::new(),
+            try_chars: Vec::new(),
+            keys: Vec::new(),
+            replacements: Vec::new(),
+            ignore: Vec::new(),
+            need_affix: None,
+            forbidden: None,
+            no_suggest: None,
+            keep_case: None,
+            only_in_compound: None,
+            compound: None,
+            compound_begin: None,
+            compound_middle: None,
+            compound_end: None,
+            compound_min: 3,
+            full_strip: false,
+        }
+    }
+}
+
+/// Decode the raw bytes of an `.aff` or `.dic` file with the encoding
+/// given by the `SET` directive.
+pub(crate) fn decode(bytes: &[u8], encoding: &str) -> Result<String> {
+    match encoding.to_uppercase().as_str() {
+        "UTF-8" | "UTF8" => Ok(std::str::from_utf8(bytes)
+            .map_err(|e| anyhow!("Content is not valid UTF-8: {}", e))?
+            .trim_start_matches('\u{FEFF}')
+            .to_owned()),
+        "ISO8859-1" | "ISO-8859-1" => Ok(bytes.iter().map(|b| *b as char).collect()),
+        "ISO8859-15" | "ISO-8859-15" => Ok(bytes
+            .iter()
+            .map(|b| match b {
+                0xA4 => '€',
+                0xA6 => 'Š',
+                0xA8 => 'š',
+                0xB4 => 'Ž',
+                0xB8 => 'ž',
+                0xBC => 'Œ',
+                0xBD => 'œ',
+                0xBE => 'Ÿ',
+                b => *b as char,
+            })
+            .collect()),
+        other => bail!("Unsupported dictionary encoding {}", other),
+    }
+}
+
+/// Extract the encoding given by `SET`, which defaults to `ISO8859-1`
+/// unless `FLAG UTF-8` is given.
+pub(crate) fn encoding(aff: &[u8]) -> String {
+    let mut fallback = "ISO8859-1";
+    for line in aff.split(|b| *b == b'\n') {
+        let line = String::from_utf8_lossy(line);
+        let mut tokens = line.trim_start_matches('\u{FEFF}').split_whitespace();
+        match (tokens.next(), tokens.next()) {
+            (Some("SET"), Some(encoding)) => return encoding.to_owned(),
+            (Some("FLAG"), Some("UTF-8")) => fallback = "UTF-8",
+            _ => {}
+        }
+    }
+    fallback.to_owned()
+}
+
+/// Kind of the directive a table entry belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Table {
+    Prefix(String),
+    Suffix(String),
+    Replacement,
+    Alias,
+}
+
+impl Aff {
+    /// Parse the decoded content of an `.aff` file.
+    pub(crate) fn parse(content: &str) -> Result<Self> {
+        let mut aff = Self::default();
+        // remaining entries of the currently started tables, and their cross product flag
+        let mut pending = HashMap::<Table, (usize, bool)>::with_capacity(8);
+
+        for (lineno, line) in content.lines().enumerate() {
+            let tokens = line.split_whitespace().collect::<Vec<&str>>();
+            let directive = match tokens.first() {
+                Some(directive) if !directive.starts_with('#') => *directive,
+                _ => continue,
+            };
+            let arg = tokens.get(1).copied();
+            let flag_format = aff.flag_format;
+            let flag = |arg: Option<&str>| -> Result<Option<Flag>> {
+                arg.map(|arg| flag_format.parse_single(arg)).transpose()
+            };
+            let res: Result<()> = (|| {
+                match directive {
+                    "FLAG" => {
+                        aff.flag_format = match arg {
+                            Some("long") => FlagFormat::Long,
+                            Some("num") => FlagFormat::Numeric,
+                            // characters are already decoded
+                            Some("UTF-8") | Some("UTF8") => FlagFormat::Short,
+                            other => bail!("Unknown flag format {:?}", other),
+                        }
+                    }
+                    "TRY" => aff.try_chars = arg.unwrap_or_default().chars().collect(),
+                    "KEY" => {
+                        aff.keys = arg
+                            .unwrap_or_default()
+                            .split('|')
+                            .map(|row| row.chars().collect())
+                            .collect()
+                    }
+                    "IGNORE" => aff.ignore = arg.unwrap_or_default().chars().collect(),
+                    "FULLSTRIP" => aff.full_strip = true,
+                    "NEEDAFFIX" | "PSEUDOROOT" => aff.need_affix = flag(arg)?,
+                    "FORBIDDENWORD" => aff.forbidden = flag(arg)?,
+                    "NOSUGGEST" => aff.no_suggest = flag(arg)?,
+                    "KEEPCASE" => aff.keep_case = flag(arg)?,
+                    "ONLYINCOMPOUND" => aff.only_in_compound = flag(arg)?,
+                    "COMPOUNDFLAG" => aff.compound = flag(arg)?,
+                    "COMPOUNDBEGIN" => aff.compound_begin = flag(arg)?,
+                    "COMPOUNDMIDDLE" => aff.compound_middle = flag(arg)?,
+                    "COMPOUNDEND" | "COMPOUNDLAST" => aff.compound_end = flag(arg)?,
+                    "COMPOUNDMIN" => {
+                        let min = arg
+                            .unwrap_or_default()
+                            .parse::<usize>()
+                            .map_err(|e| anyhow!("Invalid COMPOUNDMIN: {}", e))?;
+                        aff.compound_min = std::cmp::max(min, 1);
+                    }
+                    "REP" => aff.parse_table_line(Table::Replacement, &tokens, &mut pending)?,
+                    "AF" => aff.parse_table_line(Table::Alias, &tokens, &mut pending)?,
+                    "PFX" => aff.parse_table_line(
+                        Table::Prefix(arg.unwrap_or_default().to_owned()),
+                        &tokens,
+                        &mut pending,
+                    )?,
+                    "SFX" => aff.parse_table_line(
+                        Table::Suffix(arg.unwrap_or_default().to_owned()),
+                        &tokens,
+                        &mut pending,
+                    )?,
+                    other => trace!("Ignoring affix directive {}", other),
+                }
+                Ok(())
+            })();
+            res.map_err(|e| anyhow!("Failed to parse affix file line {}: {}", lineno + 1, e))?;
+        }
+        Ok(aff)
+    }
+
+    /// Parse the header or an entry of a table, such as `REP`, `AF`, `PFX` or `SFX`.
+    fn parse_table_line(
+        &mut self,
+        table: Table,
+        tokens: &[&str],
+        pending: &mut HashMap<Table, (usize, bool)>,
+    ) -> Result<()> {
+        let remaining = pending.get(&table).copied().filter(|(count, _)| *count > 0);
+        let (count, cross_product) = if let Some(remaining) = remaining {
+            remaining
+        } else {
+            // a table header
+            let (count, cross_product) = match table {
+                Table::Prefix(_) | Table::Suffix(_) => {
+                    (tokens.get(3).copied(), tokens.get(2).copied() == Some("Y"))
+                }
+                Table::Replacement | Table::Alias => (tokens.get(1).copied(), false),
+            };
+            let count = count
+                .ok_or_else(|| anyhow!("Table header lacks the number of entries"))?
+                .parse::<usize>()
+                .map_err(|e| anyhow!("Invalid number of table entries: {}", e))?;
+            pending.insert(table, (count, cross_product));
+            return Ok(());
+        };
+        pending.insert(table.clone(), (count - 1, cross_product));
+
+        let token = |idx: usize| {
+            tokens
+                .get(idx)
+                .copied()
+                .ok_or_else(|| anyhow!("Table entry is lacking field {}", idx))
+        };
+        match table {
+            Table::Replacement => {
+                let from = token(1)?.replace('_', " ");
+                let to = token(2)?.replace('_', " ");
+                self.replacements.push((from, to));
+            }
+            Table::Alias => {
+                let flags = self.flag_format.parse(token(1)?)?;
+                self.aliases.push(FlagSet::new(flags));
+            }
+            Table::Prefix(ref flag) | Table::Suffix(ref flag) => {
+                let flag = self.flag_format.parse_single(flag)?;
+                let strip = match token(2)? {
+                    "0" => String::new(),
+                    strip => strip.to_owned(),
+                };
+                let mut add = token(3)?.splitn(2, '/');
+                let (add, cont) = (add.next().unwrap_or_default(), add.next());
+                let add = match add {
+                    "0" => String::new(),
+                    add => self.without_ignored(add),
+                };
+                let cont = cont
+                    .map(|cont| self.flags(cont))
+                    .transpose()?
+                    .unwrap_or_default();
+                let condition = Condition::parse(tokens.get(4).copied().unwrap_or("."))?;
+                let affix = Affix {
+                    flag,
+                    cross_product,
+                    strip,
+                    add,
+                    cont,
+                    condition,
+                };
+                if let Table::Prefix(_) = table {
+                    self.prefixes.push(affix);
+                } else {
+                    self.suffixes.push(affix);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove all characters listed by `IGNORE`.
+    pub(crate) fn without_ignored(&self, s: &str) -> String {
+        s.chars().filter(|c| !self.ignore.contains(c)).collect()
+    }
+
+    /// Parse a set of flags, which might be an alias.
+    pub(crate) fn flags(&self, s: &str) -> Result<FlagSet> {
+        if self.aliases.is_empty() {
+            return Ok(FlagSet::new(self.flag_format.parse(s)?));
+        }
+        let idx = s
+            .parse::<usize>()
+            .map_err(|e| anyhow!("Invalid flag alias {}: {}", s, e))?;
+        self.aliases
+            .get(idx.wrapping_sub(1))
+            .cloned()
+            .ok_or_else(|| anyhow!("Flag alias {} is not defined", idx))
+    }
+
+    /// Parse the decoded content of a `.dic` file into `words`.
+    pub(crate) fn parse_dic(
+        &self,
+        content: &str,
+        words: &mut HashMap<String, Vec<FlagSet>>,
+    ) -> Result<()> {
+        let mut lines = content.lines().enumerate().peekable();
+        // the first line is the approximate number of words
+        if let Some((_, line)) = lines.peek() {
+            if line.trim().parse::<usize>().is_ok() {
+                let _ = lines.next();
+            }
+        }
+        for (lineno, line) in lines {
+            if line.starts_with('\t') || line.trim().is_empty() {
+                continue;
+            }
+            let (word, flags) = split_dic_entry(line);
+            let word = self.without_ignored(&word);
+            let flags = match flags {
+                Some(flags) => self.flags(flags.as_str()).map_err(|e| {
+                    anyhow!("Failed to parse dictionary line {}: {}", lineno + 1, e)
+                })?,
+                None => FlagSet::default(),
+            };
+            words.entry(word).or_insert_with(Vec::new).push(flags);
+        }
+        Ok(())
+    }
+}
+
+/// Split a dictionary line into the word and its flags, dropping
+/// morphological fields and resolving escaped slashes.
+fn split_dic_entry(line: &str) -> (String, Option<String>) {
+    let mut word = String::with_capacity(line.len());
+    let mut flags = None::<String>;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if flags.is_none() => {
+                if let Some(c) = chars.next() {
+                    word.push(c);
+                }
+            }
+            '/' if flags.is_none() && !word.is_empty() => flags = Some(String::new()),
+            c if c.is_whitespace() => break,
+            c => flags.as_mut().unwrap_or(&mut word).push(c),
+        }
+    }
+    (word, flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn condition() {
+        let cond = Condition::parse("[^aeiou]y").unwrap();
+        assert!(cond.matches_end("fly"));
+        assert!(!cond.matches_end("day"));
+        assert!(!cond.matches_end("y"));
+        assert!(Condition::parse(".").unwrap().matches_end(""));
+        assert!(Condition::parse("[ab").is_err());
+        assert!(Condition::parse("re").unwrap().matches_start("redo"));
+    }
+
+    #[test]
+    fn flag_formats() {
+        assert_eq!(FlagFormat::Short.parse("AB").unwrap(), vec![65, 66]);
+        assert_eq!(
+            FlagFormat::Long.parse("AaBb").unwrap(),
+            vec![(65 << 16) | 97, (66 << 16) | 98]
+        );
+        assert!(FlagFormat::Long.parse("AaB").is_err());
+        assert_eq!(FlagFormat::Numeric.parse("1,20").unwrap(), vec![1, 20]);
+    }
+
+    #[test]
+    fn tables() {
+        let aff = Aff::parse(
+            r#"
+SET UTF-8
+FLAG long
+TRY esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'
+REP 2
+REP f ph
+REP alot a_lot
+AF 2
+AF AaBb # 1
+AF Cc # 2
+SFX Aa Y 2
+SFX Aa   y     ies        [^aeiou]y
+SFX Aa   0     s/2        [aeiou]y
+PFX Bb N 1
+PFX Bb 0 re .
+"#,
+        )
+        .unwrap();
+        assert_eq!(aff.replacements.len(), 2);
+        assert_eq!(aff.replacements[1], ("alot".to_owned(), "a lot".to_owned()));
+        assert_eq!(aff.suffixes.len(), 2);
+        assert_eq!(aff.prefixes.len(), 1);
+        assert!(aff.suffixes[0].cross_product);
+        assert!(!aff.prefixes[0].cross_product);
+        assert_eq!(aff.suffixes[0].strip, "y");
+        assert_eq!(aff.suffixes[1].strip, "");
+        assert_ne!(aff.suffixes[1].cont, FlagSet::default());
+        assert_eq!(aff.flags("1").unwrap(), aff.flags("1").unwrap());
+        assert!(aff.flags("3").is_err());
+
+        let mut words = HashMap::new();
+        aff.parse_dic("2\nfly/1\nAC\\/DC\tpo:noun\n", &mut words)
+            .unwrap();
+        assert_eq!(words.len(), 2);
+        assert!(words["fly"][0].contains(Some(aff.suffixes[0].flag)));
+        assert_eq!(words["AC/DC"][0], FlagSet::default());
+    }
+
+    #[test]
+    fn encodings() {
+        assert_eq!(encoding(b"# comment\nSET ISO8859-15\n"), "ISO8859-15");
+        assert_eq!(encoding(b"TRY abc\n"), "ISO8859-1");
+        assert_eq!(decode(&[0x66, 0xFC, 0xA4], "ISO8859-15").unwrap(), "fü€");
+        assert!(decode(&[0xFC], "UTF-8").is_err());
+        assert!(decode(&[0x66], "KOI8-R").is_err());
+    }
+}
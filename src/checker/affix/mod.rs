@@ -0,0 +1,766 @@
+//! A dictionary check with affixes, implemented in Rust
+//!
+//! Reads the same `.dic` and `.aff` files as `libhunspell` and shares its
+//! configuration, but does not require a C++ toolchain. Supports prefixes,
+//! suffixes, twofold suffixes, the common flags and basic compounding,
+//! which covers the bulk of the available dictionaries.
+
+use super::dictionary::{self, Dictionaries, Dictionary, PerThread};
+use super::{Checker, Detector, SuggestionSet};
+
+use crate::documentation::{CheckableChunk, ContentOrigin};
+use log::debug;
+
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+
+mod aff;
+mod suggest;
+
+use self::aff::{Aff, Affix, FlagSet};
+
+thread_local! {
+    /// Parsing the dictionary files is expensive, so every worker does it once.
+    static DICTIONARIES: PerThread<AffixDictionary> = Default::default();
+}
+
+pub struct AffixChecker;
+
+impl AffixChecker {
+    fn inner_init(config: &<Self as Checker>::Config) -> Result<Rc<Dictionaries<AffixDictionary>>> {
+        dictionary::per_thread(&DICTIONARIES, config, |lang| {
+            Self::inner_init_lang(config, lang)
+        })
+    }
+
+    fn inner_init_lang(config: &<Self as Checker>::Config, lang: &str) -> Result<AffixDictionary> {
+        let (dic, aff) = dictionary::lookup(config, lang)?;
+        let mut dictionary = AffixDictionary::from_files(&aff, &dic)?;
+
+        // be more strict about the extra dictionaries, they have to exist
+        for extra_dic in config.extra_dictionaries().iter() {
+            debug!("Adding extra dictionary {}", extra_dic.display());
+            if !extra_dic.is_file() {
+                bail!("Extra dictionary {} is not a file", extra_dic.display())
+            }
+            dictionary.add_dictionary(extra_dic)?;
+        }
+        debug!("Dictionary setup completed successfully.");
+        Ok(dictionary)
+    }
+}
+
+impl Checker for AffixChecker {
+    type Config = crate::config::HunspellConfig;
+    fn check<'a, 's, D>(docu: D, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+        D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
+    {
+        let dictionaries = Self::inner_init(config)?;
+        dictionary::check(docu, config, &dictionaries, Detector::Affix)
+    }
+}
+
+/// Capitalization of a word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Casing {
+    /// `word`
+    Lower,
+    /// `Word`
+    Capitalized,
+    /// `WORD`
+    Upper,
+    /// `wOrD`
+    Mixed,
+}
+
+impl Casing {
+    fn of(word: &str) -> Self {
+        let mut letters = word.chars().filter(|c| c.is_alphabetic());
+        let first = letters.next();
+        let (upper, total) = letters.fold((0usize, 0usize), |(upper, total), c| {
+            (upper + c.is_uppercase() as usize, total + 1)
+        });
+        match first.map(char::is_uppercase) {
+            None | Some(false) if upper == 0 => Self::Lower,
+            Some(true) if upper == 0 => Self::Capitalized,
+            Some(true) if upper == total => Self::Upper,
+            _ => Self::Mixed,
+        }
+    }
+
+    /// Apply the casing to a lowercase word.
+    fn apply(self, word: &str) -> String {
+        match self {
+            Self::Lower | Self::Mixed => word.to_owned(),
+            Self::Capitalized => capitalize(word),
+            Self::Upper => word.to_uppercase(),
+        }
+    }
+}
+
+/// Uppercase the first character of a word, keeping the remainder as is.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// A dictionary including its affixes, parsed from `.dic` and `.aff` files.
+pub(crate) struct AffixDictionary {
+    aff: Aff,
+    /// Encoding of the `.aff` file, which also applies to all `.dic` files.
+    encoding: String,
+    /// All roots with the flags of all their homonyms.
+    words: HashMap<String, Vec<FlagSet>>,
+}
+
+impl AffixDictionary {
+    /// Load the affix and dictionary file.
+    pub(crate) fn from_files(aff: &Path, dic: &Path) -> Result<Self> {
+        let read = |path: &Path| {
+            std::fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))
+        };
+        Self::from_bytes(&read(aff)?, &read(dic)?)
+            .map_err(|e| anyhow!("Failed to load dictionary {}: {}", dic.display(), e))
+    }
+
+    /// Load the affix and dictionary content, encoded as declared in the affix content.
+    pub(crate) fn from_bytes(aff: &[u8], dic: &[u8]) -> Result<Self> {
+        let encoding = aff::encoding(aff);
+        let aff = Aff::parse(&aff::decode(aff, &encoding)?)?;
+        let mut me = Self {
+            aff,
+            encoding,
+            words: HashMap::with_capacity(1024),
+        };
+        me.add_dictionary_bytes(dic)?;
+        Ok(me)
+    }
+
+    /// Add the words of an additional `.dic` file, which uses the same affixes.
+    pub(crate) fn add_dictionary(&mut self, dic: &Path) -> Result<()> {
+        let content =
+            std::fs::read(dic).map_err(|e| anyhow!("Failed to read {}: {}", dic.display(), e))?;
+        self.add_dictionary_bytes(&content)
+            .map_err(|e| anyhow!("Failed to add extra dictionary {}: {}", dic.display(), e))
+    }
+
+    fn add_dictionary_bytes(&mut self, dic: &[u8]) -> Result<()> {
+        let content = aff::decode(dic, &self.encoding)?;
+        self.aff.parse_dic(&content, &mut self.words)
+    }
+
+    /// Check a single word, considering the casing variants.
+    fn check_word(&self, word: &str) -> bool {
+        let word = self.aff.without_ignored(word);
+        if word.is_empty() || is_number(&word) {
+            return true;
+        }
+        match Casing::of(&word) {
+            Casing::Lower | Casing::Mixed => self.check_cased(&word, false),
+            Casing::Capitalized => {
+                self.check_cased(&word, false) || self.check_cased(&word.to_lowercase(), true)
+            }
+            Casing::Upper => {
+                let lower = word.to_lowercase();
+                self.check_cased(&word, false)
+                    || self.check_cased(&capitalize(&lower), true)
+                    || self.check_cased(&lower, true)
+            }
+        }
+    }
+
+    /// Check a word as is, where `folded` denotes that the casing was changed
+    /// from the original word.
+    fn check_cased(&self, word: &str, folded: bool) -> bool {
+        if self.is_forbidden(word) {
+            return false;
+        }
+        self.has_root(word, folded, |flags| !flags.contains(self.aff.need_affix))
+            || self.check_affixed(word, folded)
+            || self.check_compound(word)
+    }
+
+    /// A word is forbidden, if all of its homonyms carry the forbidden flag.
+    fn is_forbidden(&self, word: &str) -> bool {
+        self.aff.forbidden.is_some()
+            && self
+                .words
+                .get(word)
+                .map(|homonyms| {
+                    homonyms
+                        .iter()
+                        .all(|flags| flags.contains(self.aff.forbidden))
+                })
+                .unwrap_or(false)
+    }
+
+    /// Check if `root` is in the dictionary with flags accepted by `accept`.
+    fn has_root<F>(&self, root: &str, folded: bool, accept: F) -> bool
+    where
+        F: Fn(&FlagSet) -> bool,
+    {
+        let aff = &self.aff;
+        self.words
+            .get(root)
+            .map(|homonyms| {
+                homonyms.iter().any(|flags| {
+                    !flags.contains(aff.forbidden)
+                        && !flags.contains(aff.only_in_compound)
+                        && !(folded && flags.contains(aff.keep_case))
+                        && accept(flags)
+                })
+            })
+            .unwrap_or(false)
+    }
+
+    /// Check if the word is a root with prefixes and / or suffixes.
+    fn check_affixed(&self, word: &str, folded: bool) -> bool {
+        let aff = &self.aff;
+        if self.check_suffixed(word, folded, None) {
+            return true;
+        }
+        aff.prefixes.iter().any(|pfx| {
+            if let Some(stem) = strip_prefix(aff, pfx, word) {
+                (!pfx.cont.contains(aff.need_affix)
+                    && self.has_root(&stem, folded, |flags| flags.contains(Some(pfx.flag))))
+                    || (pfx.cross_product && self.check_suffixed(&stem, folded, Some(pfx)))
+            } else {
+                false
+            }
+        })
+    }
+
+    /// Check if the word is a root with one or two suffixes. If the word is
+    /// the stem of a prefixed word, the prefix must be allowed by the root or
+    /// any of the suffixes.
+    fn check_suffixed(&self, word: &str, folded: bool, pfx: Option<&Affix>) -> bool {
+        let aff = &self.aff;
+        let allows_prefix = |flags: &FlagSet, affixes: &[&Affix]| {
+            pfx.map(|pfx| {
+                flags.contains(Some(pfx.flag))
+                    || affixes
+                        .iter()
+                        .any(|affix| affix.cont.contains(Some(pfx.flag)))
+            })
+            .unwrap_or(true)
+        };
+        for sfx in aff.suffixes.iter() {
+            if pfx.is_some() && !sfx.cross_product {
+                continue;
+            }
+            let stem = if let Some(stem) = strip_suffix(aff, sfx, word) {
+                stem
+            } else {
+                continue;
+            };
+            if sfx.cont.contains(aff.only_in_compound) {
+                continue;
+            }
+            if (pfx.is_some() || !sfx.cont.contains(aff.need_affix))
+                && self.has_root(&stem, folded, |flags| {
+                    flags.contains(Some(sfx.flag)) && allows_prefix(flags, &[sfx])
+                })
+            {
+                return true;
+            }
+            // twofold suffixes, where the inner suffix allows the outer one
+            for inner in aff
+                .suffixes
+                .iter()
+                .filter(|inner| inner.cont.contains(Some(sfx.flag)))
+            {
+                if let Some(root) = strip_suffix(aff, inner, &stem) {
+                    if self.has_root(&root, folded, |flags| {
+                        flags.contains(Some(inner.flag)) && allows_prefix(flags, &[sfx, inner])
+                    }) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Check if the word is a compound of roots carrying the compound flags.
+    fn check_compound(&self, word: &str) -> bool {
+        let aff = &self.aff;
+        if aff.compound.is_none()
+            && aff.compound_begin.is_none()
+            && aff.compound_middle.is_none()
+            && aff.compound_end.is_none()
+        {
+            return false;
+        }
+        let chars = word.chars().collect::<Vec<char>>();
+        self.check_compound_from(&chars, 0)
+    }
+
+    fn check_compound_from(&self, chars: &[char], start: usize) -> bool {
+        let aff = &self.aff;
+        let min = aff.compound_min;
+        let position = if start == 0 {
+            aff.compound_begin
+        } else {
+            aff.compound_middle
+        };
+        for end in (start + min)..=chars.len().saturating_sub(min) {
+            let part = chars[start..end].iter().collect::<String>();
+            if !self.is_compound_part(&part, position) {
+                continue;
+            }
+            let last = chars[end..].iter().collect::<String>();
+            if self.is_compound_part(&last, aff.compound_end)
+                || self.is_suffixed_compound_end(&last)
+                || self.check_compound_from(chars, end)
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Check if the part is a root allowed at the given position of a compound.
+    ///
+    /// Roots with any forbidden homonym are never part of a compound.
+    fn is_compound_part(&self, part: &str, position: Option<aff::Flag>) -> bool {
+        let aff = &self.aff;
+        self.words
+            .get(part)
+            .map(|homonyms| {
+                homonyms.iter().all(|flags| !flags.contains(aff.forbidden))
+                    && homonyms
+                        .iter()
+                        .any(|flags| flags.contains(aff.compound) || flags.contains(position))
+            })
+            .unwrap_or(false)
+    }
+
+    /// Check if the last part of a compound is a suffixed root, where either
+    /// the root or the suffix carries the compound flag.
+    fn is_suffixed_compound_end(&self, part: &str) -> bool {
+        let aff = &self.aff;
+        let compound =
+            |flags: &FlagSet| flags.contains(aff.compound) || flags.contains(aff.compound_end);
+        aff.suffixes.iter().any(|sfx| {
+            strip_suffix(aff, sfx, part)
+                .filter(|stem| !self.is_forbidden(stem))
+                .map(|stem| {
+                    self.words
+                        .get(&stem)
+                        .map(|homonyms| {
+                            homonyms.iter().any(|flags| {
+                                flags.contains(Some(sfx.flag))
+                                    && (compound(flags) || compound(&sfx.cont))
+                            })
+                        })
+                        .unwrap_or(false)
+                })
+                .unwrap_or(false)
+        })
+    }
+
+    /// Check if all homonyms of the word must not be suggested.
+    fn is_no_suggest(&self, word: &str) -> bool {
+        self.aff.no_suggest.is_some()
+            && self
+                .words
+                .get(word)
+                .map(|homonyms| {
+                    homonyms
+                        .iter()
+                        .all(|flags| flags.contains(self.aff.no_suggest))
+                })
+                .unwrap_or(false)
+    }
+}
+
+impl Dictionary for AffixDictionary {
+    fn check(&self, word: &str) -> bool {
+        self.check_word(word)
+    }
+
+    fn suggest(&self, word: &str) -> Vec<String> {
+        suggest::suggest(self, word)
+    }
+}
+
+/// Numbers, including single separators between digits, are always valid.
+fn is_number(word: &str) -> bool {
+    let is_separator = |c: char| c == '.' || c == ',' || c == '-';
+    let mut previous = None::<char>;
+    word.chars().any(|c| c.is_ascii_digit())
+        && word.chars().all(|c| {
+            let valid = c.is_ascii_digit()
+                || (is_separator(c) && !previous.map(is_separator).unwrap_or(false));
+            previous = Some(c);
+            valid
+        })
+}
+
+/// Remove the suffix from the word and restore the stripped characters,
+/// if the suffix applies to the word.
+fn strip_suffix(aff: &Aff, sfx: &Affix, word: &str) -> Option<String> {
+    if word.len() < sfx.add.len()
+        || (word.len() == sfx.add.len() && !aff.full_strip)
+        || !word.ends_with(sfx.add.as_str())
+    {
+        return None;
+    }
+    let stem = word[..word.len() - sfx.add.len()].to_owned() + sfx.strip.as_str();
+    Some(stem).filter(|stem| !stem.is_empty() && sfx.condition.matches_end(stem))
+}
+
+/// Remove the prefix from the word and restore the stripped characters,
+/// if the prefix applies to the word.
+fn strip_prefix(aff: &Aff, pfx: &Affix, word: &str) -> Option<String> {
+    if word.len() < pfx.add.len()
+        || (word.len() == pfx.add.len() && !aff.full_strip)
+        || !word.starts_with(pfx.add.as_str())
+    {
+        return None;
+    }
+    let stem = pfx.strip.clone() + &word[pfx.add.len()..];
+    Some(stem).filter(|stem| !stem.is_empty() && pfx.condition.matches_start(stem))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fluff_up;
+
+    const AFF: &str = r#"
+SET UTF-8
+TRY esianrtolcdugmphbyfvkwz'
+KEY qwertyuiop|asdfghjkl|zxcvbnm
+REP 1
+REP f ph
+NEEDAFFIX X
+FORBIDDENWORD !
+KEEPCASE K
+NOSUGGEST N
+COMPOUNDFLAG C
+COMPOUNDMIN 3
+
+PFX U Y 1
+PFX U 0 un .
+
+PFX R N 1
+PFX R 0 re .
+
+SFX S Y 3
+SFX S y ies [^aeiou]y
+SFX S 0 s [aeiou]y
+SFX S 0 s [^y]
+
+SFX D Y 3
+SFX D 0 ed [^y]
+SFX D y ied [^aeiou]y
+SFX D 0 ed [aeiou]y
+
+SFX L N 1
+SFX L 0 ly/S .
+
+SFX Z N 1
+SFX Z 0 ness/X .
+"#;
+
+    const DIC: &str = r#"10
+fly/S
+try/SDU
+play/SDU
+phone/S
+do/R
+kind/ULZ
+foo/C
+bar/C
+OpenSSL/K
+damn/N
+"#;
+
+    fn dictionary() -> AffixDictionary {
+        AffixDictionary::from_bytes(AFF.as_bytes(), DIC.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn check_affixes() {
+        let dict = dictionary();
+        for word in &[
+            "fly", "flies", "plays", "played", "tried", "untried", "unplays", "redo", "kind",
+            "kindly", "unkind", "foobar", "barfoo", "foo", "1.5", "Flies", "FLIES", "OpenSSL",
+            "damn",
+        ] {
+            assert!(dict.check(word), "{} must be accepted", word);
+        }
+        for word in &[
+            "flys", "plaies", "tryed", "undo", "kinds", "foob", "fo", "fooba", "openssl",
+            "Openssl", "OPENSSL", "fLies", "kindness",
+        ] {
+            assert!(!dict.check(word), "{} must be rejected", word);
+        }
+    }
+
+    #[test]
+    fn casing() {
+        assert_eq!(Casing::of("word"), Casing::Lower);
+        assert_eq!(Casing::of("Word"), Casing::Capitalized);
+        assert_eq!(Casing::of("A"), Casing::Capitalized);
+        assert_eq!(Casing::of("WORD"), Casing::Upper);
+        assert_eq!(Casing::of("wOrd"), Casing::Mixed);
+        assert_eq!(Casing::Capitalized.apply("word"), "Word");
+    }
+
+    #[test]
+    fn suggestions() {
+        let dict = dictionary();
+        let suggest = |word: &str| dict.suggest(word);
+        // swapped
+        assert_eq!(suggest("fyl").first().map(String::as_str), Some("fly"));
+        // missing character
+        assert!(suggest("plyed").contains(&"played".to_owned()));
+        // superfluous character
+        assert!(suggest("fliess").contains(&"flies".to_owned()));
+        // replacement table
+        assert_eq!(suggest("fone").first().map(String::as_str), Some("phone"));
+        // keeps the casing
+        assert!(suggest("Fyl").contains(&"Fly".to_owned()));
+        // split into two words
+        assert!(suggest("playfly").contains(&"play fly".to_owned()));
+        // never suggest words marked so
+        assert!(!suggest("damm").contains(&"damn".to_owned()));
+        // far off, found by similarity
+        assert!(suggest("phoonnee").contains(&"phone".to_owned()));
+        assert!(suggest("xxxxxxxxxxxxxxx").is_empty());
+    }
+
+    #[test]
+    fn flag_aliases() {
+        let dict = AffixDictionary::from_bytes(
+            b"FLAG long\nAF 1\nAF SsUu\nSFX Ss Y 1\nSFX Ss 0 s .\nPFX Uu Y 1\nPFX Uu 0 un .\n",
+            b"1\ndo/1\n",
+        )
+        .unwrap();
+        assert!(dict.check("undos"));
+        assert!(!dict.check("doun"));
+    }
+
+    /// Directory of the trimmed `en_US` dictionary.
+    fn fixtures() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/checker/affix/fixtures")
+    }
+
+    fn en_us() -> AffixDictionary {
+        let dir = fixtures();
+        AffixDictionary::from_files(&dir.join("en_US.aff"), &dir.join("en_US.dic"))
+            .expect("Bundled dictionary must load")
+    }
+
+    /// Accepted by `libhunspell` with the bundled `en_US` dictionary.
+    const EN_US_ACCEPTED: &[&str] = &[
+        "unhappiness",
+        "happiest",
+        "Happiest",
+        "UNHAPPINESS",
+        "kindly",
+        "unkindly",
+        "removable",
+        "movement",
+        "replayed",
+        "cried",
+        "cries",
+        "crier",
+        "eaten",
+        "boxes",
+        "apple's",
+        "mice",
+        "running",
+        "reruns",
+        "approachable",
+        "approaches",
+        "recreated",
+        "creations",
+        "creative",
+        "undoings",
+        "rewritten",
+        "unwritten",
+        "damned",
+    ];
+
+    /// Misspellings and the top suggestion of `libhunspell` with the bundled
+    /// `en_US` dictionary.
+    const EN_US_MISSPELLED: &[(&str, &str)] = &[
+        ("movements", "movement"),
+        ("mouses", "mouse"),
+        ("cryed", "cried"),
+        ("happyness", "happiness"),
+        ("boxs", "box"),
+        ("eated", "eaten"),
+        ("replaied", "replayed"),
+        ("runing", "running"),
+        ("aple", "apple"),
+        ("teh", "the"),
+        ("Spel", "Spell"),
+        ("wrod", "word"),
+        ("writting", "writing"),
+        ("kindnes", "kindness"),
+        ("plaied", "played"),
+        ("fixible", "fixable"),
+    ];
+
+    #[test]
+    fn en_us_affixes() {
+        let dict = en_us();
+        for word in EN_US_ACCEPTED {
+            assert!(dict.check(word), "{} must be accepted", word);
+        }
+        for (word, _) in EN_US_MISSPELLED {
+            assert!(!dict.check(word), "{} must be rejected", word);
+        }
+    }
+
+    #[test]
+    fn en_us_suggestions() {
+        let dict = en_us();
+        for (word, expected) in EN_US_MISSPELLED {
+            assert_eq!(
+                dict.suggest(word).first().map(String::as_str),
+                Some(*expected),
+                "Top suggestion for {}",
+                word
+            );
+        }
+        // marked as never to be suggested
+        assert!(dict.suggest("damm").is_empty());
+    }
+
+    #[test]
+    fn en_us_checker() {
+        let config = crate::Config::parse(&format!(
+            "[hunspell]\nlang = \"en_US\"\nsearch_dirs = [{:?}]\n",
+            fixtures()
+        ))
+        .expect("Must be a valid config");
+        let config = config.hunspell.expect("Contains hunspell");
+        let docs = crate::Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!["The boy cryed, the happyness of teh dogs."],
+        ));
+        let suggestions = AffixChecker::check(&docs, &config).expect("Must check");
+        let found = suggestions
+            .suggestions(&ContentOrigin::TestEntityRust)
+            .map(|suggestion| {
+                (
+                    suggestion.detector,
+                    suggestion.replacements.first().cloned().unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                (Detector::Affix, "cried".to_owned()),
+                (Detector::Affix, "happiness".to_owned()),
+                (Detector::Affix, "the".to_owned()),
+            ]
+        );
+    }
+
+    // requires `--features affix` on top of the default `hunspell` one
+    #[cfg(feature = "hunspell")]
+    #[test]
+    fn same_as_hunspell() {
+        use hunspell_rs::Hunspell;
+
+        let dir = std::env::temp_dir().join(format!("spellcheck-affix-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let aff = dir.join("test.aff");
+        let dic = dir.join("test.dic");
+        std::fs::write(&aff, AFF).unwrap();
+        std::fs::write(&dic, DIC).unwrap();
+
+        let hunspell = Hunspell::new(aff.to_str().unwrap(), dic.to_str().unwrap());
+        let dict = AffixDictionary::from_files(&aff, &dic).unwrap();
+
+        for word in &[
+            "fly",
+            "flies",
+            "flys",
+            "Flies",
+            "FLIES",
+            "fLies",
+            "plays",
+            "played",
+            "plaies",
+            "tried",
+            "tryed",
+            "untried",
+            "unplays",
+            "redo",
+            "undo",
+            "kind",
+            "kinds",
+            "kindly",
+            "unkind",
+            "kindness",
+            "foobar",
+            "barfoo",
+            "foobarfoo",
+            "foo",
+            "fo",
+            "fooba",
+            "OpenSSL",
+            "openssl",
+            "OPENSSL",
+            "damn",
+        ] {
+            assert_eq!(
+                hunspell.check(word),
+                dict.check(word),
+                "Backends disagree on {}",
+                word
+            );
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[cfg(feature = "hunspell")]
+    #[test]
+    fn same_suggestions_as_hunspell() {
+        use hunspell_rs::Hunspell;
+
+        let dir = fixtures();
+        let aff = dir.join("en_US.aff");
+        let dic = dir.join("en_US.dic");
+        let hunspell = Hunspell::new(aff.to_str().unwrap(), dic.to_str().unwrap());
+        let dict = en_us();
+
+        for word in EN_US_ACCEPTED {
+            assert_eq!(
+                hunspell.check(word),
+                dict.check(word),
+                "Backends disagree on {}",
+                word
+            );
+        }
+        for (word, _) in EN_US_MISSPELLED {
+            assert_eq!(
+                hunspell.check(word),
+                dict.check(word),
+                "Backends disagree on {}",
+                word
+            );
+            assert_eq!(
+                hunspell.suggest(word).first(),
+                dict.suggest(word).first(),
+                "Backends suggest differently for {}",
+                word
+            );
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Replacement suggestions for misspelled words
+//!
+//! First tries the replacement table and all variants with an edit distance
+//! of one, similar to `libhunspell`, and falls back to the most similar
+//! roots of the dictionary based on common n-grams.
+
+use super::{AffixDictionary, Casing};
+
+/// Upper bound of suggestions per word.
+const MAX_SUGGESTIONS: usize = 15;
+
+/// Upper bound of suggestions based on n-gram similarity.
+const MAX_NGRAM_SUGGESTIONS: usize = 4;
+
+/// Upper bound of the length difference of roots considered similar.
+const MAX_NGRAM_LENGTH_DIFF: usize = 3;
+
+/// Collects valid and unique suggestions.
+struct Suggestions<'d> {
+    dict: &'d AffixDictionary,
+    word: &'d str,
+    list: Vec<String>,
+}
+
+impl<'d> Suggestions<'d> {
+    fn add(&mut self, candidate: String) {
+        if self.list.len() >= MAX_SUGGESTIONS
+            || candidate == self.word
+            || candidate.is_empty()
+            || self.list.contains(&candidate)
+        {
+            return;
+        }
+        let valid = candidate
+            .split(' ')
+            .all(|part| self.dict.check_word(part) && !self.dict.is_no_suggest(part));
+        if valid {
+            self.list.push(candidate);
+        }
+    }
+}
+
+pub(super) fn suggest(dict: &AffixDictionary, word: &str) -> Vec<String> {
+    let mut acc = Suggestions {
+        dict,
+        word,
+        list: Vec::with_capacity(MAX_SUGGESTIONS),
+    };
+
+    let casing = Casing::of(word);
+    let lower = word.to_lowercase();
+    match casing {
+        Casing::Lower => acc.add(Casing::Capitalized.apply(word)),
+        _ => {
+            acc.add(lower.clone());
+            acc.add(Casing::Capitalized.apply(&lower));
+        }
+    }
+
+    for candidate in edits(dict, word) {
+        acc.add(candidate);
+    }
+    if casing != Casing::Lower && casing != Casing::Mixed {
+        for candidate in edits(dict, &lower) {
+            acc.add(casing.apply(&candidate));
+        }
+    }
+
+    if acc.list.is_empty() {
+        for candidate in similar(dict, &lower) {
+            acc.add(casing.apply(&candidate));
+        }
+    }
+    acc.list
+}
+
+/// All candidates derived from the word by the replacement table
+/// or with an edit distance of one, in order of likelihood.
+fn edits(dict: &AffixDictionary, word: &str) -> Vec<String> {
+    let aff = &dict.aff;
+    let chars = word.chars().collect::<Vec<char>>();
+    let n = chars.len();
+    let collect = |chars: &[char]| chars.iter().collect::<String>();
+    let mut candidates = Vec::with_capacity(64 * (n + 1));
+
+    // common misspellings
+    for (from, to) in aff.replacements.iter() {
+        for (idx, _) in word.match_indices(from.as_str()) {
+            candidates.push(format!(
+                "{}{}{}",
+                &word[..idx],
+                to,
+                &word[(idx + from.len())..]
+            ));
+        }
+    }
+
+    // neighbouring keys
+    for idx in 0..n {
+        for row in aff.keys.iter() {
+            if let Some(pos) = row.iter().position(|c| *c == chars[idx]) {
+                let neighbours = [pos.checked_sub(1), Some(pos + 1)];
+                for neighbour in neighbours.iter().filter_map(|pos| row.get((*pos)?)) {
+                    let mut candidate = chars.clone();
+                    candidate[idx] = *neighbour;
+                    candidates.push(collect(&candidate));
+                }
+            }
+        }
+    }
+
+    // swapped adjacent characters
+    for idx in 1..n {
+        let mut candidate = chars.clone();
+        candidate.swap(idx - 1, idx);
+        candidates.push(collect(&candidate));
+    }
+
+    // superfluous characters
+    for idx in 0..n {
+        let mut candidate = chars.clone();
+        candidate.remove(idx);
+        candidates.push(collect(&candidate));
+    }
+
+    // missing characters
+    for idx in 0..=n {
+        for c in aff.try_chars.iter() {
+            let mut candidate = chars.clone();
+            candidate.insert(idx, *c);
+            candidates.push(collect(&candidate));
+        }
+    }
+
+    // wrong characters
+    for idx in 0..n {
+        for c in aff.try_chars.iter().filter(|c| **c != chars[idx]) {
+            let mut candidate = chars.clone();
+            candidate[idx] = *c;
+            candidates.push(collect(&candidate));
+        }
+    }
+
+    // missing space
+    for idx in 1..n {
+        candidates.push(format!(
+            "{} {}",
+            collect(&chars[..idx]),
+            collect(&chars[idx..])
+        ));
+    }
+
+    candidates
+}
+
+/// Number of common n-grams of `a` found in `b`, for all lengths up to `n`.
+fn ngram(n: usize, a: &[char], b: &[char]) -> usize {
+    (1..=n)
+        .map(|len| {
+            a.windows(len)
+                .filter(|gram| b.windows(len).any(|other| other == *gram))
+                .count()
+        })
+        .sum()
+}
+
+/// Roots of the dictionary which are most similar to the word.
+fn similar(dict: &AffixDictionary, word: &str) -> Vec<String> {
+    let aff = &dict.aff;
+    let chars = word.chars().collect::<Vec<char>>();
+    let reference = 2 * ngram(3, &chars, &chars);
+
+    let mut scored = dict
+        .words
+        .iter()
+        .filter(|(_root, homonyms)| {
+            homonyms.iter().any(|flags| {
+                !flags.contains(aff.forbidden)
+                    && !flags.contains(aff.no_suggest)
+                    && !flags.contains(aff.need_affix)
+                    && !flags.contains(aff.only_in_compound)
+            })
+        })
+        .filter_map(|(root, _)| {
+            let lower = root.to_lowercase().chars().collect::<Vec<char>>();
+            let diff = if lower.len() > chars.len() {
+                lower.len() - chars.len()
+            } else {
+                chars.len() - lower.len()
+            };
+            if diff > MAX_NGRAM_LENGTH_DIFF {
+                return None;
+            }
+            let score =
+                (ngram(3, &chars, &lower) + ngram(3, &lower, &chars)).saturating_sub(2 * diff);
+            // at least a third of the similarity of the word with itself
+            if 3 * score >= reference {
+                Some((score, root))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // best first, ties in lexical order to stay deterministic
+    scored.sort_by(|(a_score, a_root), (b_score, b_root)| {
+        b_score.cmp(a_score).then_with(|| a_root.cmp(b_root))
+    });
+    scored
+        .into_iter()
+        .take(MAX_NGRAM_SUGGESTIONS)
+        .map(|(_, root)| root.to_owned())
+        .collect()
+}
@@ -0,0 +1,435 @@
+//! Incremental result cache
+//!
+//! Stores the findings of every checker per chunk under `target/spellcheck/`,
+//! keyed by a hash of the chunk content, the effective checker configuration
+//! and the tool version. Unchanged chunks skip the checkers entirely, the
+//! cached spans are re-based onto the current location of the chunk.
+//! Only the chunks looked up during a run are stored again, so entries of
+//! edited or removed chunks do not pile up.
+
+use crate::documentation::{CheckableChunk, ContentOrigin};
+use crate::{Detector, LineColumn, Span, Suggestion, SuggestionSet};
+
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Name of the file within the cache directory.
+const FILE_NAME: &str = "results.toml";
+
+/// A finding without references to the chunk it was found in.
+///
+/// The span is stored relative to the start of the chunk fragment the
+/// finding starts in, so it remains valid if the chunk moved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Finding {
+    detector: Detector,
+    /// Range relative to the chunk.
+    start: usize,
+    end: usize,
+    /// Index of the chunk fragment the span is relative to.
+    fragment: usize,
+    /// Start and end line of the span, relative to the fragment.
+    lines: [i64; 2],
+    /// Start and end column of the span, relative to the fragment.
+    columns: [i64; 2],
+    replacements: Vec<String>,
+    description: Option<String>,
+}
+
+impl Finding {
+    fn new(suggestion: &Suggestion<'_>) -> Self {
+        let chunk = suggestion.chunk;
+        let start = suggestion.range.start;
+        let fragment = chunk
+            .iter()
+            .position(|(range, _span)| start < range.end)
+            .unwrap_or_default();
+        let base = anchor(chunk, fragment).unwrap_or(LineColumn { line: 1, column: 0 });
+        let relative = |lc: LineColumn| {
+            (
+                lc.line as i64 - base.line as i64,
+                lc.column as i64 - base.column as i64,
+            )
+        };
+        let (start_line, start_column) = relative(suggestion.span.start);
+        let (end_line, end_column) = relative(suggestion.span.end);
+        Self {
+            detector: suggestion.detector,
+            start,
+            end: suggestion.range.end,
+            fragment,
+            lines: [start_line, end_line],
+            columns: [start_column, end_column],
+            replacements: suggestion.replacements.clone(),
+            description: suggestion.description.clone(),
+        }
+    }
+
+    /// Re-create the suggestion for the current location of `chunk`.
+    fn rebase<'s>(
+        &self,
+        origin: &ContentOrigin,
+        chunk: &'s CheckableChunk,
+    ) -> Option<Suggestion<'s>> {
+        let base = anchor(chunk, self.fragment)?;
+        let absolute = |line: i64, column: i64| {
+            let line = base.line as i64 + line;
+            let column = base.column as i64 + column;
+            if line < 1 || column < 0 {
+                None
+            } else {
+                Some(LineColumn {
+                    line: line as usize,
+                    column: column as usize,
+                })
+            }
+        };
+        Some(Suggestion {
+            detector: self.detector,
+            origin: origin.clone(),
+            chunk,
+            span: Span {
+                start: absolute(self.lines[0], self.columns[0])?,
+                end: absolute(self.lines[1], self.columns[1])?,
+            },
+            range: self.start..self.end,
+            replacements: self.replacements.clone(),
+            description: self.description.clone(),
+        })
+    }
+}
+
+/// Start of the span of the `fragment`th fragment of the chunk.
+fn anchor(chunk: &CheckableChunk, fragment: usize) -> Option<LineColumn> {
+    chunk.iter().nth(fragment).map(|(_range, span)| span.start)
+}
+
+/// The persisted content of the cache.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Store {
+    version: String,
+    chunks: Vec<Entry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    findings: Vec<Finding>,
+}
+
+/// Cached findings of all chunks checked so far, by their key.
+pub struct Cache {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, Vec<Finding>>>,
+    /// Keys of all chunks looked up during this run.
+    used: Mutex<HashSet<String>>,
+    dirty: Mutex<bool>,
+}
+
+impl Cache {
+    /// The default cache directory `target/spellcheck`, respecting `CARGO_TARGET_DIR`.
+    pub fn dir() -> Result<PathBuf> {
+        let target = match std::env::var_os("CARGO_TARGET_DIR") {
+            Some(target) => PathBuf::from(target),
+            None => crate::traverse::cwd()?.join("target"),
+        };
+        Ok(target.join("spellcheck"))
+    }
+
+    /// Load the cache from `dir`, starts out empty if there is none yet or
+    /// it was written by a different version.
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(FILE_NAME);
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(content) => match toml::from_str::<Store>(&content) {
+                Ok(store) if store.version == env!("CARGO_PKG_VERSION") => store
+                    .chunks
+                    .into_iter()
+                    .map(|entry| (entry.key, entry.findings))
+                    .collect(),
+                Ok(store) => {
+                    debug!(
+                        "Discarding cache {} of version {}",
+                        path.display(),
+                        store.version
+                    );
+                    HashMap::new()
+                }
+                Err(e) => {
+                    warn!("Discarding unreadable cache {}: {}", path.display(), e);
+                    HashMap::new()
+                }
+            },
+            Err(_) => HashMap::new(),
+        };
+        debug!(
+            "Loaded {} cached chunks from {}",
+            entries.len(),
+            path.display()
+        );
+        Self {
+            path,
+            entries: Mutex::new(entries),
+            used: Mutex::new(HashSet::new()),
+            dirty: Mutex::new(false),
+        }
+    }
+
+    /// Write the cache back to disk, if any chunk was added or not looked
+    /// up during this run. The latter are dropped.
+    pub fn save(&self) -> Result<()> {
+        let entries = self.entries.lock().expect("Cache lock is never poisoned");
+        let used = self.used.lock().expect("Cache lock is never poisoned");
+        if !*self.dirty.lock().expect("Cache lock is never poisoned") && used.len() == entries.len()
+        {
+            return Ok(());
+        }
+        let mut chunks = entries
+            .iter()
+            .filter(|(key, _findings)| used.contains(key.as_str()))
+            .map(|(key, findings)| Entry {
+                key: key.clone(),
+                findings: findings.clone(),
+            })
+            .collect::<Vec<_>>();
+        chunks.sort_by(|a, b| a.key.cmp(&b.key));
+        let store = Store {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            chunks,
+        };
+        let dir = self
+            .path
+            .parent()
+            .expect("Cache file always resides in a directory");
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+        // never leave a partially written cache behind
+        let tmp = self.path.with_extension("toml.tmp");
+        std::fs::write(&tmp, toml::to_string(&store)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        debug!(
+            "Stored {} chunks in {}",
+            store.chunks.len(),
+            self.path.display()
+        );
+        Ok(())
+    }
+
+    /// Remove the cache directory including all its content.
+    pub fn clean(dir: &Path) -> Result<()> {
+        if dir.is_dir() {
+            std::fs::remove_dir_all(dir)
+                .with_context(|| format!("Failed to remove cache {}", dir.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Prepare lookups of a checker with the given effective configuration.
+    pub(crate) fn scope<T: Debug>(&self, detector: Detector, config: &T) -> Scope<'_> {
+        let mut hasher = DefaultHasher::new();
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        detector.hash(&mut hasher);
+        format!("{:?}", config).hash(&mut hasher);
+        Scope {
+            cache: self,
+            seed: hasher.finish(),
+        }
+    }
+}
+
+/// The cache as seen by a single checker and configuration.
+pub(crate) struct Scope<'c> {
+    cache: &'c Cache,
+    seed: u64,
+}
+
+impl<'c> Scope<'c> {
+    /// The key of a chunk, independent of its location within the file.
+    fn key(&self, origin: &ContentOrigin, chunk: &CheckableChunk) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.seed.hash(&mut hasher);
+        std::mem::discriminant(origin).hash(&mut hasher);
+        chunk.as_str().hash(&mut hasher);
+        chunk
+            .iter()
+            .for_each(|(range, _span)| range.hash(&mut hasher));
+        chunk.context().hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// Check the chunks of `origin` with `check`, only those which are not
+    /// cached yet are passed on.
+    pub(crate) fn check<'a, 's, F>(
+        &self,
+        origin: &'a ContentOrigin,
+        chunks: &'a Vec<CheckableChunk>,
+        check: F,
+    ) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+        F: for<'x> FnOnce(&'x ContentOrigin, &'x Vec<CheckableChunk>) -> Result<SuggestionSet<'x>>,
+    {
+        let keys = chunks
+            .iter()
+            .map(|chunk| self.key(origin, chunk))
+            .collect::<Vec<_>>();
+        self.cache
+            .used
+            .lock()
+            .expect("Cache lock is never poisoned")
+            .extend(keys.iter().cloned());
+
+        let mut cached = {
+            let entries = self
+                .cache
+                .entries
+                .lock()
+                .expect("Cache lock is never poisoned");
+            keys.iter()
+                .map(|key| entries.get(key).cloned())
+                .collect::<Vec<_>>()
+        };
+
+        let mut acc = SuggestionSet::new();
+        let missing = (0..chunks.len())
+            .filter(|&idx| cached[idx].is_none())
+            .collect::<Vec<_>>();
+        debug!(
+            "Cache hit for {} of {} chunks of {}",
+            chunks.len() - missing.len(),
+            chunks.len(),
+            origin
+        );
+        if !missing.is_empty() {
+            let subset = missing
+                .iter()
+                .map(|&idx| chunks[idx].clone())
+                .collect::<Vec<_>>();
+            let mut fresh = vec![Vec::new(); subset.len()];
+            for (_origin, suggestions) in check(origin, &subset)? {
+                for suggestion in suggestions {
+                    if let Some(pos) = subset
+                        .iter()
+                        .position(|chunk| std::ptr::eq(chunk, suggestion.chunk))
+                    {
+                        fresh[pos].push(Finding::new(&suggestion));
+                    }
+                }
+            }
+            let mut entries = self
+                .cache
+                .entries
+                .lock()
+                .expect("Cache lock is never poisoned");
+            for (findings, idx) in fresh.into_iter().zip(missing) {
+                entries.insert(keys[idx].clone(), findings.clone());
+                cached[idx] = Some(findings);
+            }
+            *self
+                .cache
+                .dirty
+                .lock()
+                .expect("Cache lock is never poisoned") = true;
+        }
+
+        for (chunk, findings) in chunks.iter().zip(cached) {
+            for finding in findings.unwrap_or_default() {
+                match finding.rebase(origin, chunk) {
+                    Some(suggestion) => acc.add(origin.clone(), suggestion),
+                    None => warn!("Dropping cached finding {:?} of {}", finding, origin),
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy::DummyChecker;
+    use crate::checker::Checker;
+    use crate::fluff_up;
+    use crate::Documentation;
+
+    fn check<'a>(cache: &Cache, docs: &'a Documentation) -> Vec<(Span, Vec<String>)> {
+        let scope = cache.scope(Detector::Dummy, &());
+        let mut acc = Vec::new();
+        for (origin, chunks) in docs.iter() {
+            let set = scope
+                .check(origin, chunks, |origin, chunks| {
+                    DummyChecker::check(std::iter::once((origin, chunks)), &())
+                })
+                .expect("Dummy checks never fail");
+            for (_origin, suggestions) in set {
+                acc.extend(
+                    suggestions
+                        .into_iter()
+                        .map(|suggestion| (suggestion.span, suggestion.replacements)),
+                );
+            }
+        }
+        acc
+    }
+
+    #[test]
+    fn rebased() {
+        let dir = std::env::temp_dir().join(format!("spellcheck-cache-{}", std::process::id()));
+        let _ = Cache::clean(&dir);
+
+        let original =
+            Documentation::from((ContentOrigin::TestEntityRust, fluff_up!["Alpha beta"]));
+        let cache = Cache::load(&dir);
+        let expected = check(&cache, &original);
+        assert_eq!(expected.len(), 2);
+        cache.save().expect("Cache must be writable");
+
+        // the same chunk, two lines further down
+        let moved = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            format!("\n\n{}", fluff_up!["Alpha beta"]).as_str(),
+        ));
+        let cache = Cache::load(&dir);
+        assert_eq!(cache.entries.lock().unwrap().len(), 1);
+        let rebased = check(&cache, &moved);
+        let uncached = check(&Cache::load(&dir.join("empty")), &moved);
+        assert_eq!(rebased, uncached);
+        assert_eq!(rebased[0].0.start.line, expected[0].0.start.line + 2);
+
+        Cache::clean(&dir).expect("Cache must be removable");
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn pruned() {
+        let dir = std::env::temp_dir().join(format!("spellcheck-pruned-{}", std::process::id()));
+        let _ = Cache::clean(&dir);
+
+        let kept = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!["Alpha beta"]));
+        let edited = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!["Gamma"]));
+        let cache = Cache::load(&dir);
+        check(&cache, &kept);
+        check(&cache, &edited);
+        cache.save().expect("Cache must be writable");
+        assert_eq!(Cache::load(&dir).entries.lock().unwrap().len(), 2);
+
+        // nothing new is found, but the edited chunk is no longer looked up
+        let cache = Cache::load(&dir);
+        let expected = check(&cache, &kept);
+        cache.save().expect("Cache must be writable");
+        let cache = Cache::load(&dir);
+        assert_eq!(cache.entries.lock().unwrap().len(), 1);
+        assert_eq!(check(&cache, &kept), expected);
+        assert!(!*cache.dirty.lock().unwrap());
+
+        Cache::clean(&dir).expect("Cache must be removable");
+    }
+}
@@ -0,0 +1,618 @@
+//! Common ground of the dictionary based checkers
+//!
+//! Looks up the `.dic` / `.aff` files of the configured languages, tokenizes
+//! the documentation chunks and checks every word against a set of
+//! dictionaries, independent of the backend that does the actual lookup.
+
+use super::{tokenize, Detector, Suggestion, SuggestionSet};
+
+use crate::config::{HunspellConfig, LangPolicy, WrappedRegex};
+use crate::documentation::{CheckableChunk, ContentOrigin, PlainOverlay};
+use crate::util::sub_chars;
+use crate::Range;
+use log::{debug, trace};
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::thread::LocalKey;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+
+use super::quirks::{
+    replacements_contain_dashed, replacements_contain_dashless, transform, Transformed,
+};
+
+/// A single dictionary of one language, including its affixes.
+pub(crate) trait Dictionary {
+    /// Check if the word is a valid word of the dictionary.
+    fn check(&self, word: &str) -> bool;
+    /// Replacement suggestions for a word, ordered by relevance.
+    fn suggest(&self, word: &str) -> Vec<String>;
+}
+
+lazy_static::lazy_static! {
+    /// Suggestions of all words looked up so far during this run, by the
+    /// identity of the dictionaries and the word.
+    static ref SUGGESTIONS: Mutex<HashMap<(String, String), Vec<String>>> =
+        Mutex::new(HashMap::new());
+    /// Time spent looking up suggestions during this run, by the identity of
+    /// the dictionaries.
+    static ref SPENT: Mutex<HashMap<String, Duration>> = Mutex::new(HashMap::new());
+}
+
+/// A set of dictionaries, one per configured language, in the configured order.
+pub(crate) struct Dictionaries<T: Dictionary> {
+    langs: Vec<(String, T)>,
+    policy: LangPolicy,
+    /// Identifies the dictionaries, so suggestions are shared with other sets
+    /// loaded from the same files.
+    identity: String,
+    /// Attach suggestions to the reported mistakes.
+    suggestions: bool,
+    max_suggestions: Option<usize>,
+    budget: Option<Duration>,
+}
+
+impl<T: Dictionary> Dictionaries<T> {
+    /// Load the dictionaries of all configured languages with `load`.
+    pub(crate) fn load<F>(config: &HunspellConfig, load: F) -> Result<Self>
+    where
+        F: Fn(&str) -> Result<T>,
+    {
+        let langs = config
+            .lang()
+            .iter()
+            .map(|lang| Ok((lang.to_owned(), load(lang.as_str())?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            langs,
+            policy: config.lang_policy(),
+            identity: identity(config),
+            suggestions: config.suggestions(),
+            max_suggestions: config.max_suggestions,
+            budget: config.suggestion_budget(),
+        })
+    }
+
+    /// Indices of all languages which accept the word.
+    fn accepted_by(&self, word: &str) -> Vec<usize> {
+        self.langs
+            .iter()
+            .enumerate()
+            .filter(|(_idx, (_lang, dictionary))| dictionary.check(word))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Suggestions of all dictionaries, merged in the order of the
+    /// configured languages and deduplicated, or of the dictionary of a
+    /// single language.
+    ///
+    /// Looking up suggestions is expensive, so the result is cached for
+    /// the whole run. The time budget is shared by all lookups of the run,
+    /// a lookup in progress can not be interrupted, but once the budget is
+    /// spent no further dictionaries are asked. Suggestions cut short by the
+    /// budget are not cached.
+    fn suggest(&self, word: &str, lang: Option<usize>) -> Vec<String> {
+        let key = (format!("{}{:?}", self.identity, lang), word.to_owned());
+        if let Some(cached) = SUGGESTIONS
+            .lock()
+            .expect("Suggestion cache lock is never poisoned. qed")
+            .get(&key)
+        {
+            trace!("Using cached suggestions for >{}<", word);
+            return cached.clone();
+        }
+
+        let mut acc = Vec::<String>::with_capacity(16);
+        let mut complete = true;
+        for (idx, (lang_name, dictionary)) in self.langs.iter().enumerate() {
+            if lang.map_or(false, |lang| lang != idx) {
+                continue;
+            }
+            if self.budget_spent() {
+                debug!(
+                    "Suggestion time budget is spent, skipping {} for >{}<",
+                    lang_name, word
+                );
+                complete = false;
+                break;
+            }
+            let start = Instant::now();
+            for replacement in dictionary.suggest(word) {
+                if !acc.contains(&replacement) {
+                    acc.push(replacement);
+                }
+            }
+            *SPENT
+                .lock()
+                .expect("Spent time lock is never poisoned. qed")
+                .entry(self.identity.clone())
+                .or_default() += start.elapsed();
+        }
+        if complete {
+            SUGGESTIONS
+                .lock()
+                .expect("Suggestion cache lock is never poisoned. qed")
+                .insert(key, acc.clone());
+        }
+        acc
+    }
+
+    /// If the time budget for suggestion lookups of this run is spent.
+    fn budget_spent(&self) -> bool {
+        self.budget.map_or(false, |budget| {
+            SPENT
+                .lock()
+                .expect("Spent time lock is never poisoned. qed")
+                .get(&self.identity)
+                .map_or(false, |spent| *spent >= budget)
+        })
+    }
+
+    /// Drop the replacements if suggestions are disabled, or the excess ones.
+    fn limit(&self, mut replacements: Vec<String>) -> Vec<String> {
+        if !self.suggestions {
+            return Vec::new();
+        }
+        if let Some(max) = self.max_suggestions {
+            replacements.truncate(max);
+        }
+        replacements
+    }
+
+    /// Determine the language which accepts most of the words of all chunks,
+    /// only considering words that are not accepted by all languages alike.
+    ///
+    /// Returns `None` unless `LangPolicy::Consistent` is configured with multiple
+    /// languages. Ties are resolved in favour of the language listed first.
+    fn dominant(
+        &self,
+        chunks: &[CheckableChunk],
+        transform_regex: &[WrappedRegex],
+    ) -> Option<usize> {
+        if self.policy != LangPolicy::Consistent || self.langs.len() < 2 {
+            return None;
+        }
+        let mut counts = vec![0usize; self.langs.len()];
+        for chunk in chunks {
+            let plain = chunk.erase_markdown();
+            for (_range, word) in words(plain.as_str(), transform_regex) {
+                let accepted = self.accepted_by(word.as_str());
+                if !accepted.is_empty() && accepted.len() < self.langs.len() {
+                    accepted.into_iter().for_each(|idx| counts[idx] += 1);
+                }
+            }
+        }
+        let (dominant, _) =
+            counts
+                .iter()
+                .enumerate()
+                .fold((0usize, 0usize), |(best, best_count), (idx, count)| {
+                    if *count > best_count {
+                        (idx, *count)
+                    } else {
+                        (best, best_count)
+                    }
+                });
+        trace!(
+            "Dominant language is {} with counts {:?}",
+            self.langs[dominant].0,
+            counts
+        );
+        Some(dominant)
+    }
+}
+
+/// Identifies the dictionary files of a configuration.
+fn identity(config: &HunspellConfig) -> String {
+    format!(
+        "{:?}",
+        (
+            config.lang(),
+            config.search_dirs(),
+            config.extra_dictionaries()
+        )
+    )
+}
+
+/// Digest of the contents of all dictionary files of a configuration, so
+/// cached results are invalidated once a dictionary changes.
+pub(crate) fn fingerprint(config: &HunspellConfig) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let files = config
+        .lang()
+        .iter()
+        .filter_map(|lang| lookup(config, lang).ok())
+        .flat_map(|(dic, aff)| vec![dic, aff])
+        .chain(config.extra_dictionaries().iter().cloned());
+    for path in files {
+        path.hash(&mut hasher);
+        // missing files are reported by the checks themselves
+        std::fs::read(&path).unwrap_or_default().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Dictionaries loaded by a worker thread, by their configuration.
+pub(crate) type PerThread<T> = RefCell<HashMap<String, Rc<Dictionaries<T>>>>;
+
+/// Load the dictionaries once per worker thread and configuration, the
+/// backends are not necessarily thread safe and loading is expensive.
+pub(crate) fn per_thread<T, F>(
+    cache: &'static LocalKey<PerThread<T>>,
+    config: &HunspellConfig,
+    load: F,
+) -> Result<Rc<Dictionaries<T>>>
+where
+    T: Dictionary + 'static,
+    F: Fn(&str) -> Result<T>,
+{
+    let key = format!("{:?}", config);
+    cache.with(|cache| {
+        if let Some(dictionaries) = cache.borrow().get(&key) {
+            return Ok(Rc::clone(dictionaries));
+        }
+        debug!("Loading dictionaries for {:?}", std::thread::current().id());
+        let dictionaries = Rc::new(Dictionaries::load(config, load)?);
+        cache.borrow_mut().insert(key, Rc::clone(&dictionaries));
+        Ok(dictionaries)
+    })
+}
+
+/// Find the `.dic` and `.aff` file of `lang` in the configured search dirs.
+pub(crate) fn lookup(config: &HunspellConfig, lang: &str) -> Result<(PathBuf, PathBuf)> {
+    let search_dirs = config.search_dirs();
+
+    // lookup paths are really just an attempt to provide a dictionary, so be more forgiving
+    // when encountering errors here
+    search_dirs
+        .into_iter()
+        .filter(|search_dir| {
+            let keep = search_dir.is_dir();
+            if !keep {
+                // search_dir also contains the default paths, so just silently ignore these
+                debug!(
+                    "Dictionary search path is not a directory {}",
+                    search_dir.display()
+                );
+            } else {
+                debug!("Found dictionary search path {}", search_dir.display());
+            }
+            keep
+        })
+        .find_map(|search_dir| {
+            let dic = search_dir.join(lang).with_extension("dic");
+            if !dic.is_file() {
+                debug!(
+                    "Dictionary path dervied from search dir is not a file {}",
+                    dic.display()
+                );
+                return None;
+            }
+            let aff = search_dir.join(lang).with_extension("aff");
+            if !aff.is_file() {
+                debug!(
+                    "Affixes path dervied from search dir is not a file {}",
+                    aff.display()
+                );
+                return None;
+            }
+            debug!("Using dic {} and aff {}", dic.display(), aff.display());
+            Some((dic, aff))
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Failed to find any {lang}.dic / {lang}.aff in any search dir or no search provided",
+                lang = lang
+            )
+        })
+}
+
+/// Check all chunks against the given dictionaries, reporting
+/// mistakes as found by `detector`.
+pub(crate) fn check<'a, 's, D, T>(
+    docu: D,
+    config: &HunspellConfig,
+    dictionaries: &Dictionaries<T>,
+    detector: Detector,
+) -> Result<SuggestionSet<'s>>
+where
+    'a: 's,
+    D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
+    T: Dictionary,
+{
+    let (transform_regex, allow_concatenated, allow_dashed) = if let Some(quirks) = &config.quirks {
+        (
+            quirks.transform_regex(),
+            quirks.allow_concatenated(),
+            quirks.allow_dashed(),
+        )
+    } else {
+        (&[][..], false, false)
+    };
+
+    let suggestions = docu.into_iter().try_fold::<SuggestionSet, _, Result<_>>(
+        SuggestionSet::new(),
+        move |mut acc, (origin, chunks)| {
+            debug!("Processing {}", origin.as_path().display());
+
+            let dominant = dictionaries.dominant(chunks.as_slice(), transform_regex);
+
+            for chunk in chunks {
+                let plain = chunk.erase_markdown();
+                trace!("{:?}", &plain);
+                for (range, word) in words(plain.as_str(), transform_regex) {
+                    obtain_suggestions(
+                        &plain,
+                        chunk,
+                        dictionaries,
+                        dominant,
+                        detector,
+                        origin,
+                        word,
+                        range,
+                        allow_concatenated,
+                        allow_dashed,
+                        &mut acc,
+                    );
+                }
+            }
+            Ok(acc)
+        },
+    )?;
+
+    // TODO sort spans by file and line + column
+    Ok(suggestions)
+}
+
+/// Tokenize the plain text and split the tokens into checkable words
+/// according to the configured transform regular expressions.
+fn words(txt: &str, transform_regex: &[WrappedRegex]) -> Vec<(Range, String)> {
+    let mut acc = Vec::with_capacity(64);
+    for range in tokenize(txt) {
+        let word = sub_chars(txt, range.clone());
+        if transform_regex.is_empty() {
+            acc.push((range, word));
+            continue;
+        }
+        match transform(transform_regex, word.as_str(), range.clone()) {
+            Transformed::Fragments(word_fragments) => {
+                acc.extend(
+                    word_fragments
+                        .into_iter()
+                        .map(|(range, word_fragment)| (range, word_fragment.to_owned())),
+                );
+            }
+            Transformed::Atomic((range, word)) => {
+                acc.push((range, word.to_owned()));
+            }
+            Transformed::Whitelisted(_) => {}
+        }
+    }
+    acc
+}
+
+fn obtain_suggestions<'s, T: Dictionary>(
+    plain: &PlainOverlay,
+    chunk: &'s CheckableChunk,
+    dictionaries: &Dictionaries<T>,
+    dominant: Option<usize>,
+    detector: Detector,
+    origin: &ContentOrigin,
+    word: String,
+    range: Range,
+    allow_concatenated: bool,
+    allow_dashed: bool,
+    acc: &mut SuggestionSet<'s>,
+) {
+    let accepted = dictionaries.accepted_by(&word);
+    let (replacements, description) = if accepted.is_empty() {
+        trace!("No match for word (plain range: {:?}): >{}<", &range, &word);
+        // the quirks decide based on the suggestions, even if those are not reported
+        let replacements = if dictionaries.suggestions || allow_concatenated || allow_dashed {
+            dictionaries
+                .suggest(&word, None)
+                .into_iter()
+                .filter(|x| x.len() > 1) // single char suggestions tend to be useless
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+
+        if allow_concatenated && replacements_contain_dashless(&word, replacements.as_slice()) {
+            trace!(target: "quirks", "Found dashless word in replacement suggestions, treating {} as ok", &word);
+            return;
+        }
+        if allow_dashed && replacements_contain_dashed(&word, replacements.as_slice()) {
+            trace!(target: "quirks", "Found dashed word in replacement suggestions, treating {} as ok", &word);
+            return;
+        }
+        (
+            dictionaries.limit(replacements),
+            "Possible spelling mistake found.".to_owned(),
+        )
+    } else if let Some(dominant) = dominant.filter(|dominant| !accepted.contains(dominant)) {
+        trace!(
+            "Word (plain range: {:?}) >{}< is a spelling variant not accepted by the dominant language",
+            &range,
+            &word
+        );
+        let dominant_lang = &dictionaries.langs[dominant].0;
+        let variant_lang = &dictionaries.langs[accepted[0]].0;
+        let replacements = if dictionaries.suggestions {
+            dictionaries.suggest(&word, Some(dominant))
+        } else {
+            Vec::new()
+        };
+        (
+            dictionaries.limit(replacements),
+            format!(
+                "Spelling variant of {} mixed into a document which mostly uses {}.",
+                variant_lang, dominant_lang
+            ),
+        )
+    } else {
+        trace!(
+            "Found a match for word (plain range: {:?}): >{}<",
+            &range,
+            word
+        );
+        return;
+    };
+
+    for (range, span) in plain.find_spans(range.clone()) {
+        acc.add(
+            origin.clone(),
+            Suggestion {
+                detector,
+                range,
+                span,
+                origin: origin.clone(),
+                replacements: replacements.clone(),
+                chunk,
+                description: Some(description.clone()),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fluff_up, Config, Documentation};
+    use std::cell::Cell;
+
+    /// Accepts nothing but `fine`, counts the suggestion lookups which take
+    /// `delay` each.
+    struct Counting {
+        lookups: Cell<usize>,
+        delay: Duration,
+    }
+
+    impl Dictionary for Counting {
+        fn check(&self, word: &str) -> bool {
+            word == "fine"
+        }
+
+        fn suggest(&self, word: &str) -> Vec<String> {
+            self.lookups.set(self.lookups.get() + 1);
+            std::thread::sleep(self.delay);
+            vec![word.to_uppercase(), "fine".to_owned(), "good".to_owned()]
+        }
+    }
+
+    fn suggested(cfg: &str) -> (Vec<Vec<String>>, usize) {
+        suggested_slowly(cfg, Duration::default())
+    }
+
+    fn suggested_slowly(cfg: &str, delay: Duration) -> (Vec<Vec<String>>, usize) {
+        let config = Config::parse(cfg).expect("Must parse");
+        let config = config.hunspell.expect("Contains hunspell");
+        let dictionaries = Dictionaries::load(&config, |_lang| {
+            Ok(Counting {
+                lookups: Cell::new(0),
+                delay,
+            })
+        })
+        .expect("Loading never fails");
+
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::from((origin.clone(), fluff_up!["fine bad worse bad fine"]));
+        let suggestions =
+            check(&docs, &config, &dictionaries, Detector::Hunspell).expect("Check never fails");
+        let lookups = dictionaries
+            .langs
+            .iter()
+            .map(|(_lang, dictionary)| dictionary.lookups.get())
+            .sum();
+        (
+            suggestions
+                .suggestions(&origin)
+                .map(|suggestion| suggestion.replacements.clone())
+                .collect(),
+            lookups,
+        )
+    }
+
+    #[test]
+    fn cached() {
+        let (replacements, lookups) = suggested(
+            r#"
+[hunspell]
+lang = "xx_CACHED"
+"#,
+        );
+        assert_eq!(replacements.len(), 3);
+        assert_eq!(replacements[0], vec!["BAD", "fine", "good"]);
+        assert_eq!(replacements[0], replacements[2]);
+        // `bad` is looked up once
+        assert_eq!(lookups, 2);
+
+        // shared with other dictionaries loaded from the same files
+        let (_replacements, lookups) = suggested(
+            r#"
+[hunspell]
+lang = "xx_CACHED"
+"#,
+        );
+        assert_eq!(lookups, 0);
+    }
+
+    #[test]
+    fn limited() {
+        let (replacements, _lookups) = suggested(
+            r#"
+[hunspell]
+lang = "xx_LIMITED"
+max_suggestions = 2
+"#,
+        );
+        assert_eq!(replacements[1], vec!["WORSE", "fine"]);
+
+        let (replacements, lookups) = suggested(
+            r#"
+[hunspell]
+lang = "xx_DISABLED"
+suggestions = false
+"#,
+        );
+        assert_eq!(replacements, vec![Vec::<String>::new(); 3]);
+        assert_eq!(lookups, 0);
+    }
+
+    #[test]
+    fn budget_spent() {
+        let cfg = r#"
+[hunspell]
+lang = "xx_BUDGET"
+suggestion_budget = 10
+"#;
+        let (replacements, lookups) = suggested_slowly(cfg, Duration::from_millis(20));
+        // `bad` is looked up, which takes longer than the whole budget
+        assert_eq!(lookups, 1);
+        assert_eq!(
+            replacements,
+            vec![
+                vec!["BAD".to_owned(), "fine".to_owned(), "good".to_owned()],
+                vec![],
+                vec!["BAD".to_owned(), "fine".to_owned(), "good".to_owned()],
+            ]
+        );
+
+        // the suggestions cut short are not cached
+        let cached = SUGGESTIONS
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|(identity, _word)| identity.contains("xx_BUDGET"))
+            .map(|(_identity, word)| word.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(cached, vec!["bad".to_owned()]);
+    }
+}
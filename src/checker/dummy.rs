@@ -4,7 +4,7 @@
 
 use super::tokenize;
 use super::Checker;
-use crate::documentation::Documentation;
+use crate::documentation::{CheckableChunk, ContentOrigin};
 use crate::suggestion::{Detector, Suggestion, SuggestionSet};
 use crate::util::sub_chars;
 use anyhow::Result;
@@ -16,11 +16,12 @@ pub struct DummyChecker;
 impl Checker for DummyChecker {
     type Config = ();
 
-    fn check<'a, 's>(docu: &'a Documentation, _: &Self::Config) -> Result<SuggestionSet<'s>>
+    fn check<'a, 's, D>(docu: D, _: &Self::Config) -> Result<SuggestionSet<'s>>
     where
         'a: 's,
+        D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
     {
-        let suggestions = docu.iter().try_fold::<SuggestionSet, _, Result<_>>(
+        let suggestions = docu.into_iter().try_fold::<SuggestionSet, _, Result<_>>(
             SuggestionSet::new(),
             |mut acc, (origin, chunks)| {
                 let chunk = chunks
@@ -0,0 +1,312 @@
+//! Inclusive language
+//!
+//! Flags terms which are commonly perceived as exclusionary, i.e. `whitelist`
+//! or `master`, and suggests alternatives. The built-in list can be extended,
+//! overridden and individual terms allowed by configuration.
+
+use super::terminology::escape;
+use super::{grouped, Checker};
+use crate::config::InclusiveConfig;
+use crate::documentation::{CheckableChunk, ContentOrigin};
+use crate::suggestion::{Detector, Suggestion, SuggestionSet};
+use crate::Range;
+
+use anyhow::{anyhow, Result};
+use fancy_regex::Regex;
+use indexmap::IndexMap;
+use log::trace;
+
+/// Built-in terms, their alternatives and the explanation.
+const BUILTIN: &[(&str, &[&str], &str)] = &[
+    (
+        "whitelist",
+        &["allowlist", "allow list"],
+        "Avoid associating white with good and black with bad.",
+    ),
+    (
+        "whitelisted",
+        &["allowlisted", "allowed"],
+        "Avoid associating white with good and black with bad.",
+    ),
+    (
+        "blacklist",
+        &["denylist", "blocklist"],
+        "Avoid associating white with good and black with bad.",
+    ),
+    (
+        "blacklisted",
+        &["denylisted", "blocked"],
+        "Avoid associating white with good and black with bad.",
+    ),
+    (
+        "master",
+        &["main", "primary", "leader"],
+        "Avoid terms with a history of slavery.",
+    ),
+    (
+        "slave",
+        &["replica", "secondary", "follower"],
+        "Avoid terms with a history of slavery.",
+    ),
+    (
+        "slaves",
+        &["replicas", "secondaries", "followers"],
+        "Avoid terms with a history of slavery.",
+    ),
+    (
+        "grandfathered",
+        &["legacy", "exempt"],
+        "The term originates from laws disenfranchising black voters.",
+    ),
+    (
+        "sanity check",
+        &["coherence check", "confidence check"],
+        "Avoid terms stigmatizing mental health.",
+    ),
+    (
+        "man-hours",
+        &["person-hours", "work hours"],
+        "Prefer gender neutral terms.",
+    ),
+];
+
+/// A term to avoid with its alternatives.
+struct Term {
+    regex: Regex,
+    alternatives: Vec<String>,
+    explanation: Option<String>,
+}
+
+impl Term {
+    fn new(word: &str, alternatives: Vec<String>, explanation: Option<String>) -> Result<Self> {
+        let regex = Regex::new(&format!(r"(?i)(?<!\w){}(?!\w)", escape(word)))
+            .map_err(|e| anyhow!("Failed to create a regex for term {}", word).context(e))?;
+        Ok(Self {
+            regex,
+            alternatives,
+            explanation,
+        })
+    }
+
+    /// All occurrences as byte ranges.
+    fn find(&self, txt: &str) -> Result<Vec<Range>> {
+        let mut acc = Vec::new();
+        let mut pos = 0;
+        while pos < txt.len() {
+            let captures = if let Some(captures) = self.regex.captures_from_pos(txt, pos)? {
+                captures
+            } else {
+                break;
+            };
+            let found = captures
+                .get(0)
+                .expect("The whole match is always present. qed");
+            acc.push(found.range());
+            pos = found.end().max(found.start() + 1);
+        }
+        Ok(acc)
+    }
+}
+
+/// The built-in terms without the allowed ones, overridden and extended by the
+/// configured terms.
+fn terms(config: &InclusiveConfig) -> Result<Vec<Term>> {
+    let mut all = IndexMap::<String, (Vec<String>, Option<String>)>::new();
+    if config.builtin() {
+        for (word, alternatives, explanation) in BUILTIN {
+            all.insert(
+                (*word).to_owned(),
+                (
+                    alternatives.iter().map(|x| (*x).to_owned()).collect(),
+                    Some((*explanation).to_owned()),
+                ),
+            );
+        }
+    }
+    for allowed in config.allow.iter().flatten() {
+        let _ = all.shift_remove(&allowed.to_lowercase());
+    }
+    for (word, term) in config.terms.iter().flatten() {
+        all.insert(
+            word.to_lowercase(),
+            (term.alternatives.clone(), term.explanation.clone()),
+        );
+    }
+    all.into_iter()
+        .map(|(word, (alternatives, explanation))| Term::new(&word, alternatives, explanation))
+        .collect()
+}
+
+/// Adjust the alternative to the casing of the first character of the found term.
+fn capitalize_like(alternative: &str, found: &str) -> String {
+    if found.chars().next().map_or(false, char::is_uppercase) {
+        let mut chars = alternative.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        alternative.to_owned()
+    }
+}
+
+pub(crate) struct InclusiveChecker;
+
+impl Checker for InclusiveChecker {
+    type Config = InclusiveConfig;
+
+    fn check<'a, 's, D>(docu: D, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+        D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
+    {
+        let terms = terms(config)?;
+        let mut acc = SuggestionSet::new();
+        if terms.is_empty() {
+            return Ok(acc);
+        }
+        for (origin, chunks) in docu {
+            for chunk in chunks {
+                let plain = chunk.erase_markdown();
+                let txt = plain.as_str();
+
+                let mut found = Vec::<(Range, &Term)>::new();
+                for term in terms.iter() {
+                    for range in term.find(txt)? {
+                        // the first term matching a range wins
+                        if !found
+                            .iter()
+                            .any(|(other, _)| other.start < range.end && range.start < other.end)
+                        {
+                            found.push((range, term));
+                        }
+                    }
+                }
+                found.sort_by_key(|(range, _)| range.start);
+
+                for (bytes, term) in found {
+                    let word = &txt[bytes.clone()];
+                    let start = txt[..bytes.start].chars().count();
+                    let range = start..(start + word.chars().count());
+                    let description = match term.explanation {
+                        Some(ref explanation) => {
+                            format!("Consider avoiding `{}`. {}", word, explanation)
+                        }
+                        None => format!("Consider avoiding `{}`.", word),
+                    };
+                    let spans = grouped(plain.find_spans(range.clone()));
+                    trace!("Non-inclusive term at {:?} maps to {:?}", &range, &spans);
+                    // a partial replacement would garble the text
+                    let replacements = if spans.len() == 1 {
+                        term.alternatives
+                            .iter()
+                            .map(|alternative| capitalize_like(alternative, word))
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+                    for (range, span) in spans {
+                        acc.add(
+                            origin.clone(),
+                            Suggestion {
+                                detector: Detector::Inclusive,
+                                origin: origin.clone(),
+                                chunk,
+                                span,
+                                range,
+                                replacements: replacements.clone(),
+                                description: Some(description.clone()),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::sub_chars;
+    use crate::{fluff_up, Config, Documentation};
+
+    fn found(config: &InclusiveConfig, source: &str) -> Vec<(String, Vec<String>)> {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::from((origin.clone(), source));
+        let suggestions =
+            InclusiveChecker::check(&docs, config).expect("Inclusive checker never fails");
+        suggestions
+            .suggestions(&origin)
+            .map(|suggestion| {
+                (
+                    sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                    suggestion.replacements.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn builtin() {
+        let source = fluff_up![
+            "Whitelist the master branch, after a sanity check.",
+            "Mastered by `slave`, not a blacklisted_name."
+        ];
+        assert_eq!(
+            found(&InclusiveConfig::default(), source),
+            vec![
+                (
+                    "Whitelist".to_owned(),
+                    vec!["Allowlist".to_owned(), "Allow list".to_owned()]
+                ),
+                (
+                    "master".to_owned(),
+                    vec!["main".to_owned(), "primary".to_owned(), "leader".to_owned()]
+                ),
+                (
+                    "sanity check".to_owned(),
+                    vec!["coherence check".to_owned(), "confidence check".to_owned()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn configured() {
+        let config = Config::parse(
+            r#"
+[inclusive]
+allow = ["master"]
+
+[inclusive.terms.whitelist]
+alternatives = ["safelist"]
+
+[inclusive.terms.guys]
+alternatives = ["folks"]
+			"#,
+        )
+        .expect("Must parse");
+        let config = config.inclusive.expect("Contains inclusive");
+
+        let source = fluff_up!["Hey guys, whitelist the master branch."];
+        assert_eq!(
+            found(&config, source),
+            vec![
+                ("guys".to_owned(), vec!["folks".to_owned()]),
+                ("whitelist".to_owned(), vec!["safelist".to_owned()]),
+            ]
+        );
+
+        let config = InclusiveConfig {
+            builtin: Some(false),
+            ..Default::default()
+        };
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::from((origin, fluff_up!["The master branch."]));
+        let suggestions =
+            InclusiveChecker::check(&docs, &config).expect("Inclusive checker never fails");
+        assert_eq!(suggestions.total_count(), 0);
+    }
+}
@@ -14,12 +14,13 @@ pub struct LanguageToolChecker;
 
 impl Checker for LanguageToolChecker {
     type Config = crate::config::LanguageToolConfig;
-    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    fn check<'a, 's, D>(docu: D, config: &Self::Config) -> Result<SuggestionSet<'s>>
     where
         'a: 's,
+        D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
     {
         let lt = LanguageTool::new(config.url.as_str())?;
-        let suggestions = docu.iter().try_fold::<SuggestionSet, _, Result<_>>(
+        let suggestions = docu.into_iter().try_fold::<SuggestionSet, _, Result<_>>(
             SuggestionSet::new(),
             |mut acc, (origin, chunks)| {
                 for chunk in chunks {
@@ -43,10 +44,11 @@ impl Checker for LanguageToolChecker {
                             trace!("item.message: {:?}", item.message);
                             trace!("item.short_message: {:?}", item.short_message);
                             // TODO convert response to offsets and errors with the matching literal
-                            for (range, span) in plain.find_spans(Range {
+                            // a match wrapping over lines is still a single suggestion
+                            for (range, span) in grouped(plain.find_spans(Range {
                                 start: item.offset as usize,
                                 end: (item.offset + item.length) as usize,
-                            }) {
+                            })) {
                                 acc.add(
                                     origin.clone(),
                                     Suggestion {
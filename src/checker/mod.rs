@@ -4,27 +4,52 @@
 //! Contains also helpers to avoid re-implementing generic
 //! algorithms again and again, i.e. tokenization.
 
-use crate::{Config, Detector, Documentation, Suggestion, SuggestionSet};
+use crate::documentation::{CheckableChunk, ContentOrigin};
+use crate::{Config, Detector, Documentation, Span, Suggestion, SuggestionSet, Vocabulary};
 
 use anyhow::Result;
+use indexmap::IndexMap;
+use rayon::prelude::*;
 
 use crate::Range;
 use log::debug;
 
+// only used if the `hunspell` backend is not available
+#[cfg(feature = "affix")]
+#[cfg_attr(feature = "hunspell", allow(dead_code))]
+mod affix;
 #[cfg(feature = "hunspell")]
 mod hunspell;
 #[cfg(feature = "languagetool")]
 mod languagetool;
 
-#[cfg(any(feature = "languagetool", feature = "hunspell"))]
+#[cfg(any(feature = "hunspell", feature = "affix"))]
+mod dictionary;
+
+mod cache;
+mod inclusive;
+mod readability;
+mod reflow;
+mod rustdoc;
+mod terminology;
+mod typography;
+mod variants;
+
+#[cfg(any(feature = "languagetool", feature = "hunspell", feature = "affix"))]
 mod quirks;
 
+pub use self::cache::Cache;
+use self::cache::Scope;
+
 /// Implementation for a checker
 pub(crate) trait Checker {
     type Config;
-    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    /// Check all content origins with their chunks yielded by `docu`,
+    /// which commonly is a `&Documentation`.
+    fn check<'a, 's, D>(docu: D, config: &Self::Config) -> Result<SuggestionSet<'s>>
     where
-        'a: 's;
+        'a: 's,
+        D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>;
 }
 
 /// Returns absolute offsets and the data with the token in question.
@@ -40,8 +65,8 @@ fn tokenize(s: &str) -> Vec<Range> {
     let mut bananasplit = Vec::with_capacity(32);
     let _fin_char_idx = 0usize;
 
-    let blacklist = "\";:,.?!#(){}[]-\n\r/`".to_owned();
-    let is_ignore_char = |c: char| c.is_whitespace() || blacklist.contains(c);
+    let separators = "\";:,.?!#(){}[]-\n\r/`".to_owned();
+    let is_ignore_char = |c: char| c.is_whitespace() || separators.contains(c);
 
     for (c_idx, (_byte_offset, c)) in s.char_indices().enumerate() {
         if is_ignore_char(c) {
@@ -76,12 +101,189 @@ fn tokenize(s: &str) -> Vec<Range> {
     bananasplit
 }
 
+/// Group the fragments of a single finding, as returned by `find_spans`.
+///
+/// A finding wrapping over several lines of a doc comment maps to one
+/// fragment per line, one interrupted by inline markup to one fragment per
+/// piece of text. These are merged into a single range and span, from the
+/// start of the first to the end of the last fragment, so the finding is
+/// reported and fixed as one. A replacement covers the markup in between.
+fn grouped(spans: IndexMap<Range, Span>) -> IndexMap<Range, Span> {
+    if spans.len() < 2 {
+        return spans;
+    }
+    let (first_range, first_span) = spans.iter().next().expect("Contains at least two items");
+    let (last_range, last_span) = spans.iter().last().expect("Contains at least two items");
+    let mut merged = IndexMap::with_capacity(1);
+    merged.insert(
+        first_range.start..last_range.end,
+        Span {
+            start: first_span.start,
+            end: last_span.end,
+        },
+    );
+    merged
+}
+
 /// Check a full document for violations using the tools we have.
-pub fn check<'a, 's>(documentation: &'a Documentation, config: &Config) -> Result<SuggestionSet<'s>>
+///
+/// Content origins are grouped by the `[[override]]` sections matching
+/// their path, and each group is checked with its effective configuration.
+/// Chunks with findings in `cache` are not checked again.
+pub fn check<'a, 's>(
+    documentation: &'a Documentation,
+    config: &Config,
+    cache: Option<&Cache>,
+) -> Result<SuggestionSet<'s>>
 where
     'a: 's,
 {
+    let groups = groups(documentation, config)?;
     let mut collective = SuggestionSet::<'s>::new();
+    // spelling variants are consistent or not across all origins at once,
+    // overrides can only exclude origins, not change the base settings
+    let mut project = Vec::new();
+    for (matching, group) in groups {
+        if matching.is_empty() {
+            if config.is_enabled(Detector::Variants) {
+                project.extend(group.iter().cloned());
+            }
+            check_group(
+                group,
+                config,
+                documentation.vocabulary(),
+                cache,
+                &mut collective,
+            )?;
+        } else {
+            debug!(
+                "Applying overrides {:?} to {} origins",
+                matching,
+                group.len()
+            );
+            let config = config.with_overrides(matching.as_slice());
+            if config.is_enabled(Detector::Variants) {
+                project.extend(group.iter().cloned());
+            }
+            check_group(
+                group,
+                &config,
+                documentation.vocabulary(),
+                cache,
+                &mut collective,
+            )?;
+        }
+    }
+
+    if let Some(variants) = config.variants.as_ref() {
+        debug!("Running Variants checks");
+        let suggestions = self::variants::VariantsChecker::check(project, variants)?;
+        collective.join(suggestions);
+    }
+    Ok(collective)
+}
+
+/// Rewrap the paragraphs of all doc comments, no other checks are run.
+///
+/// Groups are rewrapped with their effective configuration, unless it
+/// lacks a `[reflow]` section.
+pub fn reflow<'a, 's>(
+    documentation: &'a Documentation,
+    config: &Config,
+) -> Result<SuggestionSet<'s>>
+where
+    'a: 's,
+{
+    let mut collective = SuggestionSet::<'s>::new();
+    for (matching, group) in groups(documentation, config)? {
+        let config = config.with_overrides(matching.as_slice());
+        if let Some(reflow) = config.reflow.as_ref() {
+            let suggestions = check_each::<self::reflow::ReflowChecker>(&group, reflow, None)?;
+            collective.join(suggestions);
+        }
+    }
+    Ok(collective)
+}
+
+/// Group the content origins by the `[[override]]` sections matching their path.
+fn groups<'a>(
+    documentation: &'a Documentation,
+    config: &Config,
+) -> Result<IndexMap<Vec<usize>, Vec<(&'a ContentOrigin, &'a Vec<CheckableChunk>)>>> {
+    let base = std::env::current_dir()?;
+    let mut groups =
+        IndexMap::<Vec<usize>, Vec<(&'a ContentOrigin, &'a Vec<CheckableChunk>)>>::new();
+    for (origin, chunks) in documentation.iter() {
+        let matching = config.matching_overrides(origin.as_path(), base.as_path());
+        groups
+            .entry(matching)
+            .or_insert_with(Vec::new)
+            .push((origin, chunks));
+    }
+    Ok(groups)
+}
+
+/// Readability metrics of every section of the documentation.
+pub fn readability<'a>(
+    documentation: &'a Documentation,
+) -> Vec<self::readability::ReadabilityReport<'a>> {
+    self::readability::reports(documentation.iter())
+}
+
+/// Run the checker on every content origin of the group on its own, spread
+/// across the worker threads. The suggestions keep the order of the group.
+///
+/// With a `scope`, only chunks without cached findings are checked.
+fn check_each<'a, 's, C>(
+    group: &[(&'a ContentOrigin, &'a Vec<CheckableChunk>)],
+    config: &C::Config,
+    scope: Option<Scope<'_>>,
+) -> Result<SuggestionSet<'s>>
+where
+    'a: 's,
+    C: Checker,
+    C::Config: Sync,
+{
+    let sets = group
+        .par_iter()
+        .map(|&(origin, chunks)| match scope.as_ref() {
+            Some(scope) => scope.check(origin, chunks, |origin, chunks| {
+                C::check(std::iter::once((origin, chunks)), config)
+            }),
+            None => C::check(std::iter::once((origin, chunks)), config),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut acc = SuggestionSet::new();
+    for set in sets {
+        acc.join(set);
+    }
+    Ok(acc)
+}
+
+/// Check a group of content origins which share the same effective configuration.
+fn check_group<'a, 's>(
+    group: Vec<(&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
+    config: &Config,
+    vocabulary: &Vocabulary,
+    cache: Option<&Cache>,
+    collective: &mut SuggestionSet<'s>,
+) -> Result<()>
+where
+    'a: 's,
+{
+    // the crate's identifiers and the extra dictionaries help to pick the right replacement
+    #[cfg(any(feature = "hunspell", feature = "affix"))]
+    let vocabulary = {
+        let mut vocabulary = vocabulary.clone();
+        if let Some(hunspell) = config.hunspell.as_ref() {
+            for dic in hunspell.extra_dictionaries() {
+                if let Err(e) = vocabulary.add_dictionary(dic) {
+                    log::warn!("Vocabulary lacks the extra dictionary: {}", e);
+                }
+            }
+        }
+        vocabulary
+    };
 
     #[cfg(feature = "languagetool")]
     {
@@ -92,8 +294,11 @@ where
                 .as_ref()
                 .expect("Must be Some(LanguageToolConfig) if is_enabled returns true");
 
-            let mut suggestions =
-                self::languagetool::LanguageToolChecker::check(documentation, config)?;
+            let suggestions = check_each::<self::languagetool::LanguageToolChecker>(
+                &group,
+                config,
+                cache.map(|cache| cache.scope(Detector::LanguageTool, config)),
+            )?;
             collective.join(suggestions);
         }
     }
@@ -106,12 +311,131 @@ where
                 .hunspell
                 .as_ref()
                 .expect("Must be Some(HunspellConfig) if is_enabled returns true");
-            let suggestions = self::hunspell::HunspellChecker::check(documentation, config)?;
+            let mut suggestions = check_each::<self::hunspell::HunspellChecker>(
+                &group,
+                config,
+                cache.map(|cache| {
+                    cache.scope(
+                        Detector::Hunspell,
+                        &(config, self::dictionary::fingerprint(config)),
+                    )
+                }),
+            )?;
+            vocabulary.rank_all(&mut suggestions);
             collective.join(suggestions);
         }
     }
 
-    Ok(collective)
+    #[cfg(all(feature = "affix", not(feature = "hunspell")))]
+    {
+        if config.is_enabled(Detector::Affix) {
+            debug!("Running Affix checks");
+            let config = config
+                .hunspell
+                .as_ref()
+                .expect("Must be Some(HunspellConfig) if is_enabled returns true");
+            let mut suggestions = check_each::<self::affix::AffixChecker>(
+                &group,
+                config,
+                cache.map(|cache| {
+                    cache.scope(
+                        Detector::Affix,
+                        &(config, self::dictionary::fingerprint(config)),
+                    )
+                }),
+            )?;
+            vocabulary.rank_all(&mut suggestions);
+            collective.join(suggestions);
+        }
+    }
+
+    if config.is_enabled(Detector::Typography) {
+        debug!("Running Typography checks");
+        let config = config
+            .typography
+            .as_ref()
+            .expect("Must be Some(TypographyConfig) if is_enabled returns true");
+        let suggestions = check_each::<self::typography::TypographyChecker>(
+            &group,
+            config,
+            cache.map(|cache| cache.scope(Detector::Typography, config)),
+        )?;
+        collective.join(suggestions);
+    }
+
+    if config.is_enabled(Detector::Rustdoc) {
+        debug!("Running Rustdoc checks");
+        let config = config
+            .rustdoc
+            .as_ref()
+            .expect("Must be Some(RustdocConfig) if is_enabled returns true");
+        // identifiers of the crate are valid in inline code
+        let config = (config.clone(), vocabulary.clone());
+        let suggestions = check_each::<self::rustdoc::RustdocChecker>(
+            &group,
+            &config,
+            cache.map(|cache| cache.scope(Detector::Rustdoc, &config)),
+        )?;
+        collective.join(suggestions);
+    }
+
+    if config.is_enabled(Detector::Terminology) {
+        debug!("Running Terminology checks");
+        let config = config
+            .terminology
+            .as_ref()
+            .expect("Must be Some(TerminologyConfig) if is_enabled returns true");
+        let suggestions = check_each::<self::terminology::TerminologyChecker>(
+            &group,
+            config,
+            cache.map(|cache| cache.scope(Detector::Terminology, config)),
+        )?;
+        collective.join(suggestions);
+    }
+
+    if config.is_enabled(Detector::Inclusive) {
+        debug!("Running Inclusive checks");
+        let config = config
+            .inclusive
+            .as_ref()
+            .expect("Must be Some(InclusiveConfig) if is_enabled returns true");
+        let suggestions = check_each::<self::inclusive::InclusiveChecker>(
+            &group,
+            config,
+            cache.map(|cache| cache.scope(Detector::Inclusive, config)),
+        )?;
+        collective.join(suggestions);
+    }
+
+    if config.is_enabled(Detector::Readability) {
+        debug!("Running Readability checks");
+        let config = config
+            .readability
+            .as_ref()
+            .expect("Must be Some(ReadabilityConfig) if is_enabled returns true");
+        let suggestions = check_each::<self::readability::ReadabilityChecker>(
+            &group,
+            config,
+            cache.map(|cache| cache.scope(Detector::Readability, config)),
+        )?;
+        collective.join(suggestions);
+    }
+
+    if config.is_enabled(Detector::Reflow) {
+        debug!("Running Reflow checks");
+        let config = config
+            .reflow
+            .as_ref()
+            .expect("Must be Some(ReflowConfig) if is_enabled returns true");
+        let suggestions = check_each::<self::reflow::ReflowChecker>(
+            &group,
+            config,
+            cache.map(|cache| cache.scope(Detector::Reflow, config)),
+        )?;
+        collective.join(suggestions);
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -243,4 +567,88 @@ pub mod tests {
         ];
         extraction_test_body(dbg!(SIMPLE), EXPECTED_SPANS);
     }
+    #[test]
+    fn group_wrapped_fragments() {
+        let d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!["A finding which", "wraps, twice"],
+        ));
+        let (_, chunks) = d.iter().next().expect("Must contain one origin");
+        let chunk = &chunks[0];
+        let plain = chunk.erase_markdown();
+        let start = plain.as_str().find("which").unwrap();
+        let end = plain.as_str().find(",").unwrap();
+
+        // one fragment per line, the returned ranges are those of the chunk
+        let fragments = plain.find_spans(start..end);
+        assert_eq!(fragments.len(), 2);
+
+        let spans = grouped(fragments);
+        assert_eq!(spans.len(), 1);
+        let (range, span) = spans.into_iter().next().unwrap();
+        assert_eq!(
+            range,
+            chunk.as_str().find("which").unwrap()..chunk.as_str().find(",").unwrap()
+        );
+        assert_eq!(
+            span.start,
+            LineColumn {
+                line: 1,
+                column: 14
+            }
+        );
+        assert_eq!(span.end, LineColumn { line: 2, column: 8 });
+
+        // a single fragment is left alone
+        let spans = plain.find_spans(start..(start + 3));
+        assert_eq!(spans.len(), 1);
+        assert_eq!(grouped(spans.clone()), spans);
+    }
+
+    #[test]
+    fn group_fragments_around_markup() {
+        let d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!["A *finding* which is emphasized"],
+        ));
+        let (_, chunks) = d.iter().next().expect("Must contain one origin");
+        let chunk = &chunks[0];
+        let plain = chunk.erase_markdown();
+        let start = plain.as_str().find("A finding").unwrap();
+
+        // one fragment per piece of text, all on the same line
+        let fragments = plain.find_spans(start..(start + "A finding".len()));
+        assert_eq!(fragments.len(), 2);
+
+        let spans = grouped(fragments);
+        assert_eq!(spans.len(), 1);
+        let (range, span) = spans.into_iter().next().unwrap();
+        assert_eq!(crate::util::sub_chars(chunk.as_str(), range), "A *finding");
+        assert_eq!(span.start, LineColumn { line: 1, column: 4 });
+        assert_eq!(
+            span.end,
+            LineColumn {
+                line: 1,
+                column: 13
+            }
+        );
+    }
+
+    #[test]
+    fn variants_respect_overrides() {
+        let d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!["We organize and organize, but organise once."],
+        ));
+        let config = |overrides: &str| {
+            Config::parse(&format!("[variants]\n{}", overrides)).expect("Must be a valid config")
+        };
+
+        let suggestions = check(&d, &config(""), None).expect("Must check");
+        assert_eq!(suggestions.total_count(), 1);
+
+        let disabled = config("[[override]]\nglob = \"/tmp/test/**\"\ndisable = [\"variants\"]\n");
+        let suggestions = check(&d, &disabled, None).expect("Must check");
+        assert_eq!(suggestions.total_count(), 0);
+    }
 }
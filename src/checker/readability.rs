@@ -0,0 +1,520 @@
+//! Readability
+//!
+//! Computes the Flesch reading ease, the average and the longest sentence
+//! length of every section of a chunk, where sections are separated by
+//! headings, i.e. the `# Examples` section of an item or the sections of a
+//! `README.md`. Sections exceeding the configured thresholds are reported with
+//! a suggestion spanning the offending sentence.
+
+use super::Checker;
+use crate::config::ReadabilityConfig;
+use crate::documentation::{CheckableChunk, ContentOrigin, PlainOverlay};
+use crate::suggestion::{Detector, Suggestion, SuggestionSet};
+use crate::{Range, Span};
+
+use anyhow::Result;
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use std::fmt;
+
+/// Abbreviations which end with a period, but not the sentence.
+const ABBREVIATIONS: &[&str] = &["e.g", "i.e", "vs", "cf", "approx"];
+
+/// A sentence of the plain text with its counts.
+#[derive(Debug, Clone)]
+struct Sentence {
+    /// Range in characters of the plain text.
+    range: Range,
+    words: usize,
+    syllables: usize,
+}
+
+impl Sentence {
+    fn reading_ease(&self) -> f64 {
+        reading_ease(1, self.words, self.syllables)
+    }
+}
+
+/// Flesch reading ease, higher is easier, below 30 is considered very difficult.
+fn reading_ease(sentences: usize, words: usize, syllables: usize) -> f64 {
+    if sentences == 0 || words == 0 {
+        return 100.;
+    }
+    206.835 - 1.015 * (words as f64 / sentences as f64) - 84.6 * (syllables as f64 / words as f64)
+}
+
+/// Approximate the number of syllables by the groups of vowels.
+fn syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    let chars = word.chars().collect::<Vec<char>>();
+    let is_vowel = |c: char| "aeiouy".contains(c);
+    let mut count = 0usize;
+    let mut previous = false;
+    for c in chars.iter().copied() {
+        let vowel = is_vowel(c);
+        if vowel && !previous {
+            count += 1;
+        }
+        previous = vowel;
+    }
+    // a silent trailing `e`, but not in `-le` as in `table`
+    if chars.len() > 2
+        && chars[chars.len() - 1] == 'e'
+        && chars[chars.len() - 2] != 'l'
+        && !is_vowel(chars[chars.len() - 2])
+    {
+        count = count.saturating_sub(1);
+    }
+    count.max(1)
+}
+
+/// Split the plain text into sentences, terminated by `.`, `!` or `?`
+/// followed by whitespace, or by the end of a paragraph.
+fn sentences(plain: &str) -> Vec<Sentence> {
+    let chars = plain.chars().collect::<Vec<char>>();
+    let mut acc = Vec::new();
+    let mut start = 0usize;
+    for idx in 0..=chars.len() {
+        let end = if idx == chars.len() {
+            Some(idx)
+        } else if chars[idx] == '\n' && chars.get(idx + 1) == Some(&'\n') {
+            Some(idx)
+        } else if ".!?".contains(chars[idx])
+            && chars.get(idx + 1).map_or(true, |c| c.is_whitespace())
+        {
+            let word = chars[start..idx]
+                .iter()
+                .rev()
+                .take_while(|c| !c.is_whitespace())
+                .collect::<String>()
+                .chars()
+                .rev()
+                .collect::<String>()
+                .to_lowercase();
+            if chars[idx] == '.' && ABBREVIATIONS.contains(&word.as_str()) {
+                None
+            } else {
+                Some(idx + 1)
+            }
+        } else {
+            None
+        };
+        let end = if let Some(end) = end {
+            end
+        } else {
+            continue;
+        };
+        if end <= start {
+            continue;
+        }
+        let txt = chars[start..end].iter().collect::<String>();
+        let leading = txt.chars().take_while(|c| c.is_whitespace()).count();
+        let trailing = txt.chars().rev().take_while(|c| c.is_whitespace()).count();
+        let words = txt
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphabetic))
+            .collect::<Vec<&str>>();
+        if !words.is_empty() {
+            acc.push(Sentence {
+                range: (start + leading)..(end - trailing),
+                words: words.len(),
+                syllables: words.iter().map(|word| syllables(word)).sum(),
+            });
+        }
+        start = end;
+    }
+    acc
+}
+
+/// Readability metrics of a section of documentation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Readability {
+    /// Number of sentences.
+    pub sentences: usize,
+    /// Number of words.
+    pub words: usize,
+    /// Approximated number of syllables.
+    pub syllables: usize,
+    /// Number of words of the longest sentence.
+    pub longest: usize,
+}
+
+impl Readability {
+    fn add(&mut self, sentence: &Sentence) {
+        self.sentences += 1;
+        self.words += sentence.words;
+        self.syllables += sentence.syllables;
+        self.longest = self.longest.max(sentence.words);
+    }
+
+    /// Flesch reading ease, higher is easier, below 30 is considered very difficult.
+    pub fn reading_ease(&self) -> f64 {
+        reading_ease(self.sentences, self.words, self.syllables)
+    }
+
+    /// Average number of words per sentence.
+    pub fn average_sentence_length(&self) -> f64 {
+        if self.sentences == 0 {
+            0.
+        } else {
+            self.words as f64 / self.sentences as f64
+        }
+    }
+}
+
+/// A section of a chunk, starting at a heading or the beginning of the chunk.
+struct Section {
+    title: Option<String>,
+    sentences: Vec<Sentence>,
+}
+
+impl Section {
+    fn readability(&self) -> Readability {
+        self.sentences
+            .iter()
+            .fold(Readability::default(), |mut readability, sentence| {
+                readability.add(sentence);
+                readability
+            })
+    }
+}
+
+/// Map a plain range to the range and span within the chunk, covering all lines.
+fn locate(plain: &PlainOverlay, range: Range) -> Option<(Range, Span)> {
+    if range.is_empty() {
+        return None;
+    }
+    let (first, first_span) = plain
+        .find_spans(range.start..(range.start + 1))
+        .into_iter()
+        .next()?;
+    let (last, last_span) = plain
+        .find_spans((range.end - 1)..range.end)
+        .into_iter()
+        .next()?;
+    Some((
+        first.start..last.end,
+        Span {
+            start: first_span.start,
+            end: last_span.end,
+        },
+    ))
+}
+
+/// Headings of the chunk as range in characters and their title.
+fn headings(chunk: &CheckableChunk) -> Vec<(Range, String)> {
+    let s = chunk.as_str();
+    let mut acc = Vec::new();
+    let mut current: Option<(Range, String)> = None;
+    for (event, bytes) in Parser::new_ext(s, Options::all()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading(_)) => {
+                let start = s[..bytes.start].chars().count();
+                let end = start + s[bytes].chars().count();
+                current = Some((start..end, String::new()));
+            }
+            Event::Text(txt) | Event::Code(txt) => {
+                if let Some((_, ref mut title)) = current {
+                    title.push_str(&txt);
+                }
+            }
+            Event::End(Tag::Heading(_)) => acc.extend(current.take()),
+            _ => {}
+        }
+    }
+    acc
+}
+
+/// Split the chunk into sections at its headings, headings themselves are
+/// not part of any section.
+fn sections(chunk: &CheckableChunk, plain: &PlainOverlay) -> Vec<Section> {
+    let headings = headings(chunk);
+    let mut acc = vec![Section {
+        title: None,
+        sentences: Vec::new(),
+    }];
+    acc.extend(headings.iter().map(|(_range, title)| Section {
+        title: Some(title.clone()),
+        sentences: Vec::new(),
+    }));
+    for sentence in sentences(plain.as_str()) {
+        let start = if let Some((range, _span)) = locate(plain, sentence.range.clone()) {
+            range.start
+        } else {
+            continue;
+        };
+        if headings.iter().any(|(range, _)| range.contains(&start)) {
+            continue;
+        }
+        let idx = headings
+            .iter()
+            .filter(|(range, _)| range.start <= start)
+            .count();
+        acc[idx].sentences.push(sentence);
+    }
+    acc.retain(|section| !section.sentences.is_empty());
+    acc
+}
+
+/// Readability metrics of a section, as printed in the summary.
+pub struct ReadabilityReport<'a> {
+    origin: &'a ContentOrigin,
+    /// Line of the first sentence of the section.
+    line: usize,
+    /// Title of the section or the name of the documented item.
+    title: Option<String>,
+    readability: Readability,
+}
+
+impl<'a> fmt::Display for ReadabilityReport<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{}:{}",
+            self.origin.as_path().display(),
+            self.line
+        )?;
+        if let Some(ref title) = self.title {
+            write!(formatter, " {}", title)?;
+        }
+        write!(
+            formatter,
+            ": reading ease {:.1}, {:.1} words per sentence, longest sentence {} words",
+            self.readability.reading_ease(),
+            self.readability.average_sentence_length(),
+            self.readability.longest,
+        )
+    }
+}
+
+/// Readability metrics of all sections of all chunks.
+pub(crate) fn reports<'a, D>(docu: D) -> Vec<ReadabilityReport<'a>>
+where
+    D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
+{
+    let mut acc = Vec::new();
+    for (origin, chunks) in docu {
+        for chunk in chunks {
+            let plain = chunk.erase_markdown();
+            for section in sections(chunk, &plain) {
+                let line = section
+                    .sentences
+                    .first()
+                    .and_then(|sentence| locate(&plain, sentence.range.clone()))
+                    .map_or(0, |(_range, span)| origin.absolute_span(span).start.line);
+                let title = section.title.clone().or_else(|| {
+                    chunk
+                        .context()
+                        .and_then(|context| context.ident.as_ref())
+                        .map(|ident| format!("`{}`", ident))
+                });
+                acc.push(ReadabilityReport {
+                    origin,
+                    line,
+                    title,
+                    readability: section.readability(),
+                });
+            }
+        }
+    }
+    acc
+}
+
+pub(crate) struct ReadabilityChecker;
+
+impl Checker for ReadabilityChecker {
+    type Config = ReadabilityConfig;
+
+    fn check<'a, 's, D>(docu: D, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+        D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
+    {
+        let mut acc = SuggestionSet::new();
+        for (origin, chunks) in docu {
+            for chunk in chunks {
+                let plain = chunk.erase_markdown();
+                for section in sections(chunk, &plain) {
+                    let readability = section.readability();
+                    let mut found = Vec::<(&Sentence, String)>::new();
+                    if let Some(max) = config.max_sentence_length {
+                        for sentence in section.sentences.iter() {
+                            if sentence.words > max {
+                                found.push((
+                                    sentence,
+                                    format!(
+                                        "Sentence of {} words, longer than {} words.",
+                                        sentence.words, max
+                                    ),
+                                ));
+                            }
+                        }
+                    }
+                    // section wide metrics are meaningless for a few words
+                    if readability.words >= config.min_words() {
+                        if let Some(max) = config.max_average_sentence_length {
+                            let average = readability.average_sentence_length();
+                            if average > max {
+                                let longest = section
+                                    .sentences
+                                    .iter()
+                                    .max_by_key(|sentence| sentence.words)
+                                    .expect("A section has at least one sentence. qed");
+                                found.push((
+                                    longest,
+                                    format!(
+                                        "Sentences have {:.1} words on average, more than {:.1}, this is the longest one.",
+                                        average, max
+                                    ),
+                                ));
+                            }
+                        }
+                        if let Some(min) = config.min_reading_ease {
+                            let ease = readability.reading_ease();
+                            if ease < min {
+                                let densest = section
+                                    .sentences
+                                    .iter()
+                                    .min_by(|a, b| {
+                                        a.reading_ease()
+                                            .partial_cmp(&b.reading_ease())
+                                            .unwrap_or(std::cmp::Ordering::Equal)
+                                    })
+                                    .expect("A section has at least one sentence. qed");
+                                found.push((
+                                    densest,
+                                    format!(
+                                        "Reading ease is {:.1}, less than {:.1}, this is the densest sentence.",
+                                        ease, min
+                                    ),
+                                ));
+                            }
+                        }
+                    }
+
+                    // one suggestion per sentence, joining the reasons
+                    let mut joined = Vec::<(&Sentence, String)>::new();
+                    for (sentence, description) in found {
+                        if let Some((_, other)) = joined
+                            .iter_mut()
+                            .find(|(other, _)| other.range == sentence.range)
+                        {
+                            other.push(' ');
+                            other.push_str(&description);
+                        } else {
+                            joined.push((sentence, description));
+                        }
+                    }
+                    joined.sort_by_key(|(sentence, _)| sentence.range.start);
+
+                    for (sentence, description) in joined {
+                        if let Some((range, span)) = locate(&plain, sentence.range.clone()) {
+                            acc.add(
+                                origin.clone(),
+                                Suggestion {
+                                    detector: Detector::Readability,
+                                    origin: origin.clone(),
+                                    chunk,
+                                    span,
+                                    range,
+                                    replacements: Vec::new(),
+                                    description: Some(description),
+                                },
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::sub_chars;
+    use crate::{fluff_up, Documentation};
+
+    #[test]
+    fn syllable_count() {
+        assert_eq!(syllables("the"), 1);
+        assert_eq!(syllables("table"), 2);
+        assert_eq!(syllables("make"), 1);
+        assert_eq!(syllables("documentation"), 5);
+        assert_eq!(syllables("rhythm"), 1);
+    }
+
+    #[test]
+    fn sentence_split() {
+        let plain = "One two, e.g. three! Four?\n\nFive six\nseven. v1.2 is out";
+        let found = sentences(plain)
+            .into_iter()
+            .map(|sentence| (sub_chars(plain, sentence.range), sentence.words))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("One two, e.g. three!".to_owned(), 4),
+                ("Four?".to_owned(), 1),
+                ("Five six\nseven.".to_owned(), 3),
+                ("v1.2 is out".to_owned(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn metrics() {
+        let source = fluff_up![
+            "Short and simple. Very simple.",
+            "",
+            "# Examples",
+            "",
+            "Considerable complexity characterizes extraordinarily elaborate documentation."
+        ];
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::from((origin.clone(), source));
+        let reports = reports(&docs);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].title.as_deref(), Some("`Fluff`"));
+        assert_eq!(reports[0].readability.sentences, 2);
+        assert_eq!(reports[0].readability.longest, 3);
+        assert!(reports[0].readability.reading_ease() > 60.);
+        assert_eq!(reports[1].title.as_deref(), Some("Examples"));
+        assert_eq!(reports[1].line, 5);
+        assert!(reports[1].readability.reading_ease() < 0.);
+    }
+
+    #[test]
+    fn thresholds() {
+        let config = ReadabilityConfig {
+            max_sentence_length: Some(6),
+            min_reading_ease: Some(30.),
+            min_words: Some(0),
+            ..Default::default()
+        };
+        let source = fluff_up![
+            "Short and simple. This sentence is rather long and it",
+            "continues on the next line. Considerable complexity characterizes",
+            "extraordinarily elaborate documentation."
+        ];
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::from((origin.clone(), source));
+        let suggestions =
+            ReadabilityChecker::check(&docs, &config).expect("Readability checker never fails");
+        let found = suggestions
+            .suggestions(&origin)
+            .map(|suggestion| {
+                (
+                    suggestion.span.start.line,
+                    suggestion.span.end.line,
+                    suggestion.description.clone().unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].0, found[0].1), (1, 2));
+        assert!(found[0].2.starts_with("Sentence of 12 words"));
+        assert_eq!((found[1].0, found[1].1), (2, 3));
+        assert!(found[1].2.starts_with("Reading ease is"));
+    }
+}
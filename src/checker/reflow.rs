@@ -0,0 +1,400 @@
+//! Reflow
+//!
+//! Rewraps paragraphs of `///`, `//!` and `/** */` doc comments to the
+//! configured column. Only top level paragraphs are touched, lists, code
+//! blocks, tables, headings and link reference definitions are kept as they
+//! are. Each paragraph which would be wrapped differently is reported with the
+//! rewrapped paragraph as replacement, spanning multiple lines.
+
+use super::Checker;
+use crate::config::ReflowConfig;
+use crate::documentation::{CheckableChunk, ContentOrigin};
+use crate::suggestion::{Detector, Suggestion, SuggestionSet};
+use crate::{Range, Span};
+
+use anyhow::{anyhow, Result};
+use log::trace;
+use pulldown_cmark::{Event, Options, Parser, Tag};
+
+/// A rewrapped paragraph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Reflowed {
+    /// Characters of the chunk from the first to the last word.
+    range: Range,
+    /// Span in the source file from the first to the last word.
+    span: Span,
+    replacement: String,
+}
+
+/// A word could not start a line without changing the markdown structure,
+/// i.e. becoming a list item or a heading.
+fn starts_block(word: &str) -> bool {
+    let mut chars = word.chars();
+    let first = if let Some(first) = chars.next() {
+        first
+    } else {
+        return false;
+    };
+    let digits = word.chars().take_while(char::is_ascii_digit).count();
+    match first {
+        '>' | '|' | '<' => true,
+        '#' => word.chars().all(|c| c == '#') && word.len() <= 6,
+        '-' | '+' | '*' | '=' | '_' => chars.all(|c| c == first),
+        '`' => word.starts_with("```"),
+        '~' => word.starts_with("~~~"),
+        _ if digits > 0 => digits <= 9 && (word[digits..] == *"." || word[digits..] == *")"),
+        _ => false,
+    }
+}
+
+/// The first character of the span, as line and column.
+fn locate(chunk: &CheckableChunk, idx: usize) -> Option<Span> {
+    chunk.find_spans(idx..(idx + 1)).values().next().copied()
+}
+
+/// A comment prefix which is safe to use for all continuation lines.
+fn is_comment_prefix(prefix: &str) -> bool {
+    match prefix.trim() {
+        "///" | "//!" | "*" | "" => true,
+        _ => false,
+    }
+}
+
+/// Rewrap a paragraph given as byte range of the chunk.
+///
+/// Returns `None` if the paragraph is wrapped already, or if it can not be
+/// rewrapped safely.
+fn reflow_paragraph(
+    chunk: &CheckableChunk,
+    bytes: std::ops::Range<usize>,
+    source: &[&str],
+    max: usize,
+) -> Option<Reflowed> {
+    let s = chunk.as_str();
+
+    // words of all lines with their character ranges in the chunk, and
+    // the prefix of every line in the source file
+    let mut lines = Vec::<(String, Vec<(Range, &str)>)>::new();
+    let mut offset = bytes.start;
+    for line in s[bytes.clone()].split('\n') {
+        let line_start = s[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+        let chars_start = s[..line_start].chars().count();
+        let mut words = Vec::new();
+        let mut idx = s[..offset].chars().count();
+        let mut word_start = None;
+        for (byte, c) in line.char_indices().chain(Some((line.len(), ' '))) {
+            match (c.is_whitespace(), word_start) {
+                (false, None) => word_start = Some((byte, idx)),
+                (true, Some((byte_start, start))) => {
+                    words.push((start..idx, &line[byte_start..byte]));
+                    word_start = None;
+                }
+                _ => {}
+            }
+            idx += 1;
+        }
+        offset += line.len() + 1;
+        if words.is_empty() {
+            continue;
+        }
+        let leading = s[line_start..]
+            .chars()
+            .take_while(|c| *c != '\n' && c.is_whitespace())
+            .collect::<String>();
+        let span = locate(chunk, chars_start)?;
+        let prefix = source
+            .get(span.start.line.checked_sub(1)?)?
+            .chars()
+            .take(span.start.column)
+            .collect::<String>();
+        lines.push((prefix + &leading, words));
+    }
+
+    // a line starting like a block, but part of the paragraph, was not
+    // recognized as such, i.e. an indented table
+    if lines
+        .iter()
+        .any(|(_, words)| words.first().map_or(false, |(_, word)| starts_block(word)))
+    {
+        return None;
+    }
+
+    let (first_prefix, first_words) = lines.first()?;
+    let prefix = if let Some((prefix, _)) = lines.get(1) {
+        if !lines
+            .iter()
+            .skip(1)
+            .all(|(prefix, _)| is_comment_prefix(prefix))
+        {
+            return None;
+        }
+        prefix.clone()
+    } else {
+        match first_prefix.trim() {
+            "///" | "//!" => first_prefix.clone(),
+            _ => return None,
+        }
+    };
+    let (first, _) = first_words.first()?;
+    let first_span = locate(chunk, first.start)?;
+    let (last, _) = lines.last()?.1.last()?;
+    let last_span = locate(chunk, last.end - 1)?;
+
+    // greedy, but never start a line with a word which would start a block
+    let mut rewrapped = vec![Vec::<&str>::new()];
+    let mut width = first_span.start.column;
+    for (_range, word) in lines.iter().flat_map(|(_, words)| words.iter()) {
+        let len = word.chars().count();
+        let current = rewrapped
+            .last_mut()
+            .expect("Contains at least one line. qed");
+        if current.is_empty() {
+            width += len;
+            current.push(word);
+        } else if width + 1 + len <= max || starts_block(word) {
+            width += 1 + len;
+            current.push(word);
+        } else {
+            width = prefix.chars().count() + len;
+            rewrapped.push(vec![word]);
+        }
+    }
+
+    let wrapped = lines
+        .iter()
+        .map(|(_, words)| words.iter().map(|(_, word)| *word).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    if wrapped == rewrapped {
+        return None;
+    }
+    trace!("Rewrapping paragraph {:?} to {:?}", &wrapped, &rewrapped);
+    let replacement = rewrapped
+        .iter()
+        .map(|line| line.join(" "))
+        .collect::<Vec<_>>()
+        .join(&format!("\n{}", prefix));
+    Some(Reflowed {
+        range: first.start..last.end,
+        span: Span {
+            start: first_span.start,
+            end: last_span.end,
+        },
+        replacement,
+    })
+}
+
+/// Rewrap all top level paragraphs of a chunk, which are neither part of a
+/// list nor contain hard line breaks.
+fn reflow_chunk(chunk: &CheckableChunk, source: &[&str], max: usize) -> Vec<Reflowed> {
+    let s = chunk.as_str();
+    let mut acc = Vec::new();
+    let mut depth = 0usize;
+    let mut paragraph: Option<(std::ops::Range<usize>, bool)> = None;
+    for (event, bytes) in Parser::new_ext(s, Options::all()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Paragraph) if depth == 0 => {
+                paragraph = Some((bytes, true));
+                depth += 1;
+            }
+            Event::End(Tag::Paragraph) if depth == 1 => {
+                depth -= 1;
+                if let Some((bytes, true)) = paragraph.take() {
+                    acc.extend(reflow_paragraph(chunk, bytes, source, max));
+                }
+            }
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth = depth.saturating_sub(1),
+            Event::HardBreak | Event::Html(_) => {
+                if let Some((_, ref mut reflowable)) = paragraph {
+                    *reflowable = false;
+                }
+            }
+            _ => {}
+        }
+    }
+    acc
+}
+
+/// Rewrap all chunks of one origin, `source` is the content of the file.
+fn reflow_origin<'s>(
+    origin: &ContentOrigin,
+    chunks: &'s [CheckableChunk],
+    source: &str,
+    config: &ReflowConfig,
+    acc: &mut SuggestionSet<'s>,
+) {
+    let source = source.lines().collect::<Vec<_>>();
+    let max = config.max_line_length();
+    for chunk in chunks {
+        for reflowed in reflow_chunk(chunk, &source, max) {
+            acc.add(
+                origin.clone(),
+                Suggestion {
+                    detector: Detector::Reflow,
+                    origin: origin.clone(),
+                    chunk,
+                    span: reflowed.span,
+                    range: reflowed.range,
+                    replacements: vec![reflowed.replacement],
+                    description: Some(format!("Rewrap the paragraph to {} columns.", max)),
+                },
+            );
+        }
+    }
+}
+
+pub(crate) struct ReflowChecker;
+
+impl Checker for ReflowChecker {
+    type Config = ReflowConfig;
+
+    fn check<'a, 's, D>(docu: D, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+        D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
+    {
+        let mut acc = SuggestionSet::new();
+        for (origin, chunks) in docu {
+            // only doc comments are reflowed, the prefix of every line is
+            // taken from the source file
+            if let ContentOrigin::RustSourceFile(path) = origin {
+                let source = std::fs::read_to_string(path)
+                    .map_err(|e| anyhow!("Failed to read {}", path.display()).context(e))?;
+                reflow_origin(origin, chunks, &source, config, &mut acc);
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Documentation;
+
+    fn reflowed(source: &str, max: usize) -> Vec<(Span, String)> {
+        let _ = env_logger::builder()
+            .filter(None, log::LevelFilter::Trace)
+            .is_test(true)
+            .try_init();
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::from((origin.clone(), source));
+        let config = ReflowConfig {
+            max_line_length: Some(max),
+        };
+        let mut acc = SuggestionSet::new();
+        for (origin, chunks) in docs.iter() {
+            reflow_origin(origin, chunks, source, &config, &mut acc);
+        }
+        if acc.total_count() == 0 {
+            return Vec::new();
+        }
+        acc.suggestions(&origin)
+            .map(|suggestion| (suggestion.span, suggestion.replacements[0].clone()))
+            .collect()
+    }
+
+    #[test]
+    fn block_start() {
+        assert!(starts_block("-"));
+        assert!(starts_block("##"));
+        assert!(starts_block("1."));
+        assert!(starts_block("42)"));
+        assert!(starts_block("```rust"));
+        assert!(starts_block("---"));
+        assert!(starts_block(">"));
+        assert!(!starts_block("#[derive]"));
+        assert!(!starts_block("-1"));
+        assert!(!starts_block("1.5"));
+        assert!(!starts_block("*emphasized*"));
+        assert!(!starts_block("word"));
+    }
+
+    #[test]
+    fn paragraph() {
+        let source = r#"    /// A short line
+    /// followed by a rather long line which exceeds the configured width of forty.
+    ///
+    /// Already wrapped.
+    struct Ragged;
+"#;
+        assert_eq!(
+            reflowed(source, 40),
+            vec![(
+                Span {
+                    start: crate::LineColumn { line: 1, column: 8 },
+                    end: crate::LineColumn {
+                        line: 2,
+                        column: 82
+                    },
+                },
+                r#"A short line followed by a
+    /// rather long line which exceeds
+    /// the configured width of forty."#
+                    .to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn markdown_structure() {
+        let source = r#"/// # A heading which is long enough to be wrapped
+///
+/// * a list item which is long enough to be wrapped
+/// * another item
+///
+/// ```rust
+/// let code = "a code block which is long enough to be wrapped";
+/// ```
+///
+/// | a table | which is long enough | to be wrapped |
+/// |---------|----------------------|---------------|
+///
+/// [link]: https://example.com/a/link/reference/definition/which/is/long
+///
+/// Ends with a hard\
+/// break.
+///
+/// Keeps - and 1. off the start of a line.
+struct Markdown;
+"#;
+        assert_eq!(
+            reflowed(source, 30)
+                .into_iter()
+                .map(|(_, replacement)| replacement)
+                .collect::<Vec<_>>(),
+            vec!["Keeps - and 1. off the\n/// start of a line.".to_owned()]
+        );
+        // `-` and `1.` exceed the width, but would start a list
+        assert_eq!(
+            reflowed(source, 9)
+                .into_iter()
+                .map(|(_, replacement)| replacement)
+                .collect::<Vec<_>>(),
+            vec![
+                "Keeps -\n/// and 1.\n/// off\n/// the\n/// start\n/// of a\n/// line.".to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn block_comment() {
+        let source = r#"/** A block comment
+ * which is rewrapped. */
+struct Block;
+"#;
+        assert_eq!(
+            reflowed(source, 80),
+            vec![(
+                Span {
+                    start: crate::LineColumn { line: 1, column: 4 },
+                    end: crate::LineColumn {
+                        line: 2,
+                        column: 21
+                    },
+                },
+                "A block comment which is rewrapped.".to_owned()
+            )]
+        );
+    }
+}
@@ -0,0 +1,548 @@
+//! Rustdoc conventions
+//!
+//! Lints doc comments of rust items against the conventions of the
+//! API guidelines: a single sentence summary line ending with a period
+//! followed by a blank line, function summaries in third person and the
+//! `# Safety`, `# Errors` and `# Panics` sections where they apply.
+//! Identifiers in inline code which neither exist on the documented item
+//! nor anywhere in the crate are flagged as stale.
+//! Requires the item context, so only chunks of rust sources are checked.
+
+use super::{grouped, Checker};
+use crate::config::RustdocConfig;
+use crate::documentation::{CheckableChunk, ContentOrigin, ItemContext, ItemKind};
+use crate::suggestion::{Detector, Suggestion, SuggestionSet};
+use crate::vocabulary::{edit_distance, KEYWORDS};
+use crate::{Range, Vocabulary};
+
+use anyhow::Result;
+use log::trace;
+
+/// First words of a function summary which are not a verb.
+const NOT_A_VERB: &[&str] = &[
+    "a",
+    "an",
+    "the",
+    "this",
+    "that",
+    "these",
+    "those",
+    "if",
+    "when",
+    "for",
+    "in",
+    "on",
+    "of",
+    "like",
+    "see",
+    "same",
+    "no",
+    "all",
+    "only",
+    "internal",
+    "helper",
+    "shortcut",
+    "alias",
+    "convenience",
+    "equivalent",
+    "wrapper",
+    "constructor",
+];
+
+/// Identifiers which are valid in inline code, even if the crate does not use them.
+const PRIMITIVES: &[&str] = &[
+    "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+    "i128", "isize", "f32", "f64", "None", "Some", "Ok", "Err", "String", "Vec", "Option",
+    "Result", "Box",
+];
+
+/// A single violation, the range is in characters relative to the chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Violation {
+    range: Range,
+    replacements: Vec<String>,
+    description: String,
+}
+
+impl Violation {
+    fn new(range: Range, description: &str) -> Self {
+        Self {
+            range,
+            replacements: Vec::new(),
+            description: description.to_owned(),
+        }
+    }
+}
+
+pub(crate) struct RustdocChecker;
+
+impl Checker for RustdocChecker {
+    /// The rules and the vocabulary of the crate.
+    type Config = (RustdocConfig, Vocabulary);
+
+    fn check<'a, 's, D>(docu: D, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+        D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
+    {
+        let (config, vocabulary) = config;
+        let mut acc = SuggestionSet::new();
+        for (origin, chunks) in docu {
+            for chunk in chunks {
+                let context = if let Some(context) = chunk.context() {
+                    context
+                } else {
+                    continue;
+                };
+                let mut violations = lint(chunk.as_str(), context, config);
+                if config.stale_identifiers() {
+                    violations.extend(stale_identifiers(chunk.as_str(), context, vocabulary));
+                }
+                for violation in violations {
+                    trace!("Rustdoc violation {:?}", &violation);
+                    for (range, span) in grouped(chunk.find_spans(violation.range.clone())) {
+                        acc.add(
+                            origin.clone(),
+                            Suggestion {
+                                detector: Detector::Rustdoc,
+                                origin: origin.clone(),
+                                chunk,
+                                span,
+                                range,
+                                replacements: violation.replacements.clone(),
+                                description: Some(violation.description.clone()),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+/// Lines of the raw text with the character offset of their beginning.
+fn lines(raw: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    raw.split('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.chars().count() + 1;
+            (start, line)
+        })
+        .collect()
+}
+
+/// Character range of the trimmed content of a line.
+fn trimmed_range(start: usize, line: &str) -> Range {
+    let leading = line.chars().take_while(|c| c.is_whitespace()).count();
+    let trailing = line.chars().rev().take_while(|c| c.is_whitespace()).count();
+    let len = line.chars().count();
+    (start + leading)..(start + len - trailing).max(start + leading)
+}
+
+/// All headings outside of code blocks, without the leading `#`s.
+fn headings<'r>(lines: &[(usize, &'r str)]) -> Vec<&'r str> {
+    let mut fenced = false;
+    let mut acc = Vec::new();
+    for (_start, line) in lines {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        } else if !fenced && trimmed.starts_with('#') {
+            acc.push(trimmed.trim_start_matches('#').trim());
+        }
+    }
+    acc
+}
+
+/// The third person singular of a verb, i.e. `Returns` for `Return`.
+fn third_person(verb: &str) -> String {
+    let lower = verb.to_lowercase();
+    if ["s", "x", "z", "ch", "sh", "o"]
+        .iter()
+        .any(|suffix| lower.ends_with(suffix))
+    {
+        format!("{}es", verb)
+    } else if lower.ends_with('y') && !lower[..lower.len() - 1].ends_with(|c| "aeiou".contains(c)) {
+        format!("{}ies", &verb[..verb.len() - 1])
+    } else {
+        format!("{}s", verb)
+    }
+}
+
+fn lint(raw: &str, context: &ItemContext, config: &RustdocConfig) -> Vec<Violation> {
+    let lines = lines(raw);
+    let mut acc = Vec::new();
+
+    let summary_idx = if let Some(idx) = lines.iter().position(|(_, line)| !line.trim().is_empty())
+    {
+        idx
+    } else {
+        return acc;
+    };
+    let (start, line) = lines[summary_idx];
+    let summary = line.trim();
+    // a heading or a code block is no summary
+    if summary.starts_with('#') || summary.starts_with("```") {
+        return acc;
+    }
+    let range = trimmed_range(start, line);
+    let is_function = context.kind == ItemKind::Function;
+
+    if config.summary_sentence() {
+        if !summary.ends_with('.') {
+            let mut violation = Violation::new(
+                (range.end - 1)..range.end,
+                "The summary line should end with a period.",
+            );
+            let last = summary.chars().last().expect("Summary is not empty. qed");
+            if last.is_alphanumeric() || last == '`' || last == ')' {
+                violation.replacements = vec![format!("{}.", last)];
+            }
+            acc.push(violation);
+        }
+        let chars = summary.chars().collect::<Vec<char>>();
+        let multiple = chars.windows(3).any(|window| {
+            (window[0] == '.' || window[0] == '!' || window[0] == '?')
+                && window[1] == ' '
+                && window[2].is_uppercase()
+        });
+        if multiple {
+            acc.push(Violation::new(
+                range.clone(),
+                "The summary line should be a single sentence.",
+            ));
+        }
+    }
+
+    if config.summary_blank_line() {
+        if let Some((next_start, next)) = lines.get(summary_idx + 1) {
+            if !next.trim().is_empty() {
+                acc.push(Violation::new(
+                    trimmed_range(*next_start, next),
+                    "The summary line should be followed by a blank line.",
+                ));
+            }
+        }
+    }
+
+    if config.third_person() && is_function {
+        let verb = summary
+            .chars()
+            .take_while(|c| c.is_alphabetic())
+            .collect::<String>();
+        let lower = verb.to_lowercase();
+        let capitalized = verb.chars().next().map_or(false, char::is_uppercase);
+        if capitalized
+            && verb.chars().count() > 1
+            && !lower.ends_with('s')
+            && !NOT_A_VERB.contains(&lower.as_str())
+        {
+            acc.push(Violation {
+                range: range.start..(range.start + verb.chars().count()),
+                replacements: vec![third_person(&verb)],
+                description: "The summary of a function should start with a verb in third person."
+                    .to_owned(),
+            });
+        }
+    }
+
+    let headings = headings(&lines);
+    let mut section = |enabled: bool, applies: bool, name: &str| {
+        if enabled && applies && !headings.contains(&name) {
+            acc.push(Violation::new(
+                range.clone(),
+                &format!("The documentation lacks a `# {}` section.", name),
+            ));
+        }
+    };
+    section(
+        config.safety_section(),
+        is_function && context.is_unsafe,
+        "Safety",
+    );
+    section(
+        config.errors_section(),
+        is_function && context.is_public && context.returns_result,
+        "Errors",
+    );
+    section(
+        config.panics_section(),
+        is_function && context.is_public && context.can_panic,
+        "Panics",
+    );
+
+    acc.sort_by_key(|violation| violation.range.start);
+    acc
+}
+
+/// Inline code spans consisting of a single identifier, outside of code blocks.
+///
+/// The ranges exclude the backticks.
+fn inline_identifiers(raw: &str) -> Vec<(Range, String)> {
+    let mut acc = Vec::new();
+    let mut fenced = false;
+    for (start, line) in lines(raw) {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced {
+            continue;
+        }
+        let chars = line.chars().collect::<Vec<char>>();
+        let mut idx = 0;
+        while idx < chars.len() {
+            if chars[idx] != '`' {
+                idx += 1;
+                continue;
+            }
+            let run = chars[idx..].iter().take_while(|c| **c == '`').count();
+            let content_start = idx + run;
+            // find the closing run of the same length
+            let mut end = content_start;
+            let mut closing = None;
+            while end < chars.len() {
+                let other = chars[end..].iter().take_while(|c| **c == '`').count();
+                if other == run {
+                    closing = Some(end);
+                    break;
+                }
+                end += other.max(1);
+            }
+            let closing = if let Some(closing) = closing {
+                closing
+            } else {
+                break;
+            };
+            let content = chars[content_start..closing].iter().collect::<String>();
+            let is_identifier = content
+                .chars()
+                .next()
+                .map_or(false, |c| c.is_alphabetic() || c == '_')
+                && content.chars().all(|c| c.is_alphanumeric() || c == '_');
+            if run == 1 && is_identifier {
+                acc.push(((start + content_start)..(start + closing), content));
+            }
+            idx = closing + run;
+        }
+    }
+    acc
+}
+
+/// The name of the item closest to the identifier, allowing for more edits than
+/// the vocabulary does, since there are only few names to pick from.
+fn closest_name<'n>(names: &'n [String], ident: &str) -> Option<&'n str> {
+    let limit = (ident.chars().count() / 2).max(1);
+    names
+        .iter()
+        .map(|name| (edit_distance(ident, name), name))
+        .filter(|(distance, _name)| *distance <= limit)
+        .min_by_key(|(distance, _name)| *distance)
+        .map(|(_distance, name)| name.as_str())
+}
+
+/// Identifiers in inline code which exist neither on the documented item nor in the crate.
+///
+/// Names with an underscore are always checked, since they are almost certainly
+/// a parameter or field, all others only if they are close to one of the names
+/// of the item.
+fn stale_identifiers(raw: &str, context: &ItemContext, vocabulary: &Vocabulary) -> Vec<Violation> {
+    let names = context
+        .names
+        .iter()
+        .chain(context.ident.iter())
+        .cloned()
+        .collect::<Vec<String>>();
+
+    inline_identifiers(raw)
+        .into_iter()
+        .filter(|(_range, ident)| {
+            ident.chars().count() > 2
+                && !KEYWORDS.contains(&ident.as_str())
+                && !PRIMITIVES.contains(&ident.as_str())
+                && !names.contains(ident)
+                && !vocabulary.contains(ident)
+        })
+        .filter_map(|(range, ident)| {
+            let closest = closest_name(&names, &ident);
+            let snake_case = ident.contains('_')
+                && ident
+                    .chars()
+                    .all(|c| c.is_lowercase() || c.is_numeric() || c == '_');
+            let closest = if snake_case {
+                closest.or_else(|| vocabulary.closest(&ident))
+            } else if closest.is_some() {
+                closest
+            } else {
+                return None;
+            };
+            let item = context
+                .ident
+                .as_ref()
+                .map(|item| format!(" of `{}`", item))
+                .unwrap_or_default();
+            Some(Violation {
+                range,
+                replacements: closest.into_iter().map(str::to_owned).collect(),
+                description: format!(
+                    "`{}` is neither a parameter, field or variant{} nor an identifier of the crate.",
+                    ident, item
+                ),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::sub_chars;
+    use crate::Documentation;
+
+    fn descriptions(source: &'static str, config: &RustdocConfig) -> Vec<(String, String)> {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::from((origin.clone(), source));
+        let config = (config.clone(), docs.vocabulary().clone());
+        let suggestions =
+            RustdocChecker::check(&docs, &config).expect("Rustdoc checker never fails");
+        suggestions
+            .suggestions(&origin)
+            .map(|suggestion| {
+                (
+                    sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                    suggestion.description.clone().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn conjugation() {
+        assert_eq!(third_person("Return"), "Returns");
+        assert_eq!(third_person("Fetch"), "Fetches");
+        assert_eq!(third_person("Apply"), "Applies");
+        assert_eq!(third_person("Obey"), "Obeys");
+    }
+
+    #[test]
+    fn conventions() {
+        const SOURCE: &str = r#"
+/// Creates a thing.
+///
+/// # Safety
+///
+/// None.
+pub unsafe fn fine() {}
+
+/// Return the value. Or not
+/// continued
+pub unsafe fn wrong() -> Result<(), ()> {
+    assert!(true);
+    Ok(())
+}
+
+/// A struct without period
+struct Fluff;
+"#;
+        let found = descriptions(SOURCE, &RustdocConfig::default());
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "Return the value. Or not".to_owned(),
+                    "The summary line should be a single sentence.".to_owned()
+                ),
+                (
+                    "Return".to_owned(),
+                    "The summary of a function should start with a verb in third person."
+                        .to_owned()
+                ),
+                (
+                    "Return the value. Or not".to_owned(),
+                    "The documentation lacks a `# Safety` section.".to_owned()
+                ),
+                (
+                    "Return the value. Or not".to_owned(),
+                    "The documentation lacks a `# Errors` section.".to_owned()
+                ),
+                (
+                    "Return the value. Or not".to_owned(),
+                    "The documentation lacks a `# Panics` section.".to_owned()
+                ),
+                (
+                    "t".to_owned(),
+                    "The summary line should end with a period.".to_owned()
+                ),
+                (
+                    "continued".to_owned(),
+                    "The summary line should be followed by a blank line.".to_owned()
+                ),
+                (
+                    "d".to_owned(),
+                    "The summary line should end with a period.".to_owned()
+                ),
+            ]
+        );
+
+        let config = RustdocConfig {
+            summary_sentence: Some(false),
+            summary_blank_line: Some(false),
+            third_person: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(descriptions(SOURCE, &config).len(), 3);
+    }
+
+    #[test]
+    fn inline_code() {
+        let found = inline_identifiers(" Uses `a_b`, ``c`` and `d e` but\n```\n`f`\n```\n `g`");
+        assert_eq!(
+            found,
+            vec![(7..10, "a_b".to_owned()), (47..48, "g".to_owned())]
+        );
+    }
+
+    #[test]
+    fn stale() {
+        const SOURCE: &str = r#"
+/// Returns `None` if `max_len` is zero, keeps `self` and `limitt`.
+///
+/// Unlike `helper_fn`, `other_thing` or `Whatever`.
+pub fn truncate(max_length: usize, limit: u8) -> Option<usize> {
+    helper_fn();
+    None
+}
+"#;
+        let config = RustdocConfig {
+            summary_sentence: Some(false),
+            summary_blank_line: Some(false),
+            third_person: Some(false),
+            ..Default::default()
+        };
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::from((origin.clone(), SOURCE));
+        let config = (config, docs.vocabulary().clone());
+        let suggestions =
+            RustdocChecker::check(&docs, &config).expect("Rustdoc checker never fails");
+        let found = suggestions
+            .suggestions(&origin)
+            .map(|suggestion| {
+                (
+                    sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                    suggestion.replacements.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("max_len".to_owned(), vec!["max_length".to_owned()]),
+                ("limitt".to_owned(), vec!["limit".to_owned()]),
+                ("other_thing".to_owned(), vec![]),
+            ]
+        );
+    }
+}
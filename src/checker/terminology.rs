@@ -0,0 +1,241 @@
+//! Terminology
+//!
+//! Enforces the preferred spelling of terms, i.e. `GitHub` over `Github`,
+//! which dictionaries commonly accept in either form. Only the prose is
+//! checked, inline code and code blocks are erased beforehand.
+
+use super::{grouped, Checker};
+use crate::config::TerminologyConfig;
+use crate::documentation::{CheckableChunk, ContentOrigin};
+use crate::suggestion::{Detector, Suggestion, SuggestionSet};
+use crate::Range;
+
+use anyhow::{anyhow, Result};
+use fancy_regex::Regex;
+use log::trace;
+
+/// A disallowed form and its preferred replacement.
+struct Term {
+    regex: Regex,
+    replacement: String,
+    /// The replacement refers to capture groups of the regular expression.
+    expand: bool,
+}
+
+impl Term {
+    /// Match the disallowed form as a whole word only.
+    fn word(word: &str, replacement: &str, case_insensitive: bool) -> Result<Self> {
+        let flags = if case_insensitive { "(?i)" } else { "" };
+        let regex = Regex::new(&format!(r"{}(?<!\w){}(?!\w)", flags, escape(word)))
+            .map_err(|e| anyhow!("Failed to create a regex for term {}", word).context(e))?;
+        Ok(Self {
+            regex,
+            replacement: replacement.to_owned(),
+            expand: false,
+        })
+    }
+
+    /// All occurrences as byte range and the replacement.
+    fn find(&self, txt: &str) -> Result<Vec<(Range, String)>> {
+        let mut acc = Vec::new();
+        let mut pos = 0;
+        while pos <= txt.len() {
+            let captures = if let Some(captures) = self.regex.captures_from_pos(txt, pos)? {
+                captures
+            } else {
+                break;
+            };
+            let found = captures
+                .get(0)
+                .expect("The whole match is always present. qed");
+            let replacement = if self.expand {
+                let mut replacement = String::with_capacity(self.replacement.len());
+                captures.expand(&self.replacement, &mut replacement);
+                replacement
+            } else {
+                self.replacement.clone()
+            };
+            if !found.as_str().is_empty() && found.as_str() != replacement {
+                acc.push((found.range(), replacement));
+            }
+            pos = if found.end() > found.start() {
+                found.end()
+            } else {
+                found.end() + txt[found.end()..].chars().next().map_or(1, char::len_utf8)
+            };
+        }
+        Ok(acc)
+    }
+}
+
+/// Escape all characters with a special meaning in regular expressions.
+pub(super) fn escape(word: &str) -> String {
+    let mut escaped = String::with_capacity(word.len() * 2);
+    for c in word.chars() {
+        if r"\.+*?()|[]{}^$#&-~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// All configured terms, exact ones first, then case insensitive ones and
+/// regular expressions last.
+fn terms(config: &TerminologyConfig) -> Result<Vec<Term>> {
+    let mut acc = Vec::new();
+    for (word, replacement) in config.exact.iter().flatten() {
+        acc.push(Term::word(word, replacement, false)?);
+    }
+    for (word, replacement) in config.case_insensitive.iter().flatten() {
+        acc.push(Term::word(word, replacement, true)?);
+    }
+    for (regex, replacement) in config.regex.iter().flat_map(|terms| terms.iter()) {
+        acc.push(Term {
+            regex: regex.clone().into(),
+            replacement: replacement.clone(),
+            expand: true,
+        });
+    }
+    Ok(acc)
+}
+
+pub(crate) struct TerminologyChecker;
+
+impl Checker for TerminologyChecker {
+    type Config = TerminologyConfig;
+
+    fn check<'a, 's, D>(docu: D, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+        D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
+    {
+        let terms = terms(config)?;
+        let mut acc = SuggestionSet::new();
+        if terms.is_empty() {
+            return Ok(acc);
+        }
+        for (origin, chunks) in docu {
+            for chunk in chunks {
+                let plain = chunk.erase_markdown();
+                let txt = plain.as_str();
+
+                let mut found = Vec::<(Range, String)>::new();
+                for term in terms.iter() {
+                    for (range, replacement) in term.find(txt)? {
+                        // the first term matching a range wins
+                        if !found
+                            .iter()
+                            .any(|(other, _)| other.start < range.end && range.start < other.end)
+                        {
+                            found.push((range, replacement));
+                        }
+                    }
+                }
+                found.sort_by_key(|(range, _)| range.start);
+
+                for (bytes, replacement) in found {
+                    let start = txt[..bytes.start].chars().count();
+                    let range = start..(start + txt[bytes.clone()].chars().count());
+                    let description =
+                        format!("Prefer `{}` over `{}`.", &replacement, &txt[bytes.clone()]);
+                    let spans = grouped(plain.find_spans(range.clone()));
+                    trace!("Disallowed term at {:?} maps to {:?}", &range, &spans);
+                    // a partial replacement would garble the text
+                    let replacements = if spans.len() == 1 {
+                        vec![replacement]
+                    } else {
+                        Vec::new()
+                    };
+                    for (range, span) in spans {
+                        acc.add(
+                            origin.clone(),
+                            Suggestion {
+                                detector: Detector::Terminology,
+                                origin: origin.clone(),
+                                chunk,
+                                span,
+                                range,
+                                replacements: replacements.clone(),
+                                description: Some(description.clone()),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::sub_chars;
+    use crate::{fluff_up, Config, Documentation};
+
+    #[test]
+    fn escaping() {
+        assert_eq!(escape("e.g."), r"e\.g\.");
+        assert_eq!(escape("C++"), r"C\+\+");
+    }
+
+    #[test]
+    fn terminology() {
+        let config = Config::parse(
+            r#"
+[terminology.exact]
+Github = "GitHub"
+rust = "Rust"
+eg = "e.g."
+
+[terminology.case_insensitive]
+json = "JSON"
+
+[terminology.regex]
+"\\b([Cc])argo\\.toml\\b" = "`${1}argo.toml`"
+			"#,
+        )
+        .expect("Must parse");
+        let config = config.terminology.expect("Contains terminology");
+
+        let source = fluff_up![
+            "Github uses rust, eg json or JSON. Not `rust` or rusty",
+            "In Cargo.toml."
+        ];
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::from((origin.clone(), source));
+        let suggestions =
+            TerminologyChecker::check(&docs, &config).expect("Terminology checker never fails");
+        let found = suggestions
+            .suggestions(&origin)
+            .map(|suggestion| {
+                (
+                    sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                    suggestion.replacements.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("Github".to_owned(), vec!["GitHub".to_owned()]),
+                ("rust".to_owned(), vec!["Rust".to_owned()]),
+                ("eg".to_owned(), vec!["e.g.".to_owned()]),
+                ("json".to_owned(), vec!["JSON".to_owned()]),
+                ("Cargo.toml".to_owned(), vec!["`Cargo.toml`".to_owned()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_regex() {
+        assert!(Config::parse(
+            r#"
+[terminology.regex]
+"(unclosed" = "closed"
+			"#,
+        )
+        .is_err());
+    }
+}
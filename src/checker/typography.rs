@@ -0,0 +1,388 @@
+//! Typography
+//!
+//! A built-in checker for slips a dictionary does not catch: repeated words,
+//! multiple spaces, unbalanced brackets and backticks and sentences
+//! ending with two periods. Does not require any external service.
+
+use super::{grouped, tokenize, Checker};
+use crate::config::TypographyConfig;
+use crate::documentation::{CheckableChunk, ContentOrigin};
+use crate::suggestion::{Detector, Suggestion, SuggestionSet};
+use crate::util::sub_chars;
+use crate::Range;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use log::trace;
+
+/// Words which are commonly repeated on purpose.
+const INTENTIONALLY_REPEATED: &[&str] = &["that", "had"];
+
+/// A single finding, the range is in characters, relative to the text it was
+/// found in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mistake {
+    range: Range,
+    replacements: Vec<String>,
+    description: String,
+}
+
+pub(crate) struct TypographyChecker;
+
+impl Checker for TypographyChecker {
+    type Config = TypographyConfig;
+
+    fn check<'a, 's, D>(docu: D, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+        D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
+    {
+        let mut acc = SuggestionSet::new();
+        for (origin, chunks) in docu {
+            for chunk in chunks {
+                let plain = chunk.erase_markdown();
+                let txt = plain.as_str();
+
+                let mut mistakes = Vec::new();
+                if config.repeated_words() {
+                    mistakes.extend(repeated_words(txt));
+                }
+                if config.whitespace() {
+                    mistakes.extend(whitespace(txt));
+                }
+                if config.brackets() {
+                    mistakes.extend(brackets(txt));
+                }
+                if config.punctuation() {
+                    mistakes.extend(punctuation(txt));
+                }
+                mistakes.sort_by_key(|mistake: &Mistake| mistake.range.start);
+                for mistake in mistakes {
+                    let spans = grouped(plain.find_spans(mistake.range.clone()));
+                    add(&mut acc, origin, chunk, &mistake, spans);
+                }
+
+                // inline code is erased from the plain representation
+                if config.backticks() {
+                    for mistake in backticks(chunk.as_str()) {
+                        let spans = grouped(chunk.find_spans(mistake.range.clone()));
+                        add(&mut acc, origin, chunk, &mistake, spans);
+                    }
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+/// Add a suggestion per span of the mistake.
+///
+/// Replacements are only kept if the mistake maps to a single span, as
+/// grouped from its fragments, otherwise applying them would only replace
+/// a fragment.
+fn add<'s>(
+    acc: &mut SuggestionSet<'s>,
+    origin: &ContentOrigin,
+    chunk: &'s CheckableChunk,
+    mistake: &Mistake,
+    spans: IndexMap<Range, crate::Span>,
+) {
+    trace!("Typography mistake {:?} maps to {:?}", mistake, &spans);
+    let replacements = if spans.len() == 1 {
+        mistake.replacements.clone()
+    } else {
+        Vec::new()
+    };
+    for (range, span) in spans {
+        acc.add(
+            origin.clone(),
+            Suggestion {
+                detector: Detector::Typography,
+                origin: origin.clone(),
+                chunk,
+                span,
+                range,
+                replacements: replacements.clone(),
+                description: Some(mistake.description.clone()),
+            },
+        );
+    }
+}
+
+/// A word which is directly followed by itself, i.e. `the the`.
+fn repeated_words(txt: &str) -> Vec<Mistake> {
+    let tokens = tokenize(txt);
+    tokens
+        .windows(2)
+        .filter_map(|pair| {
+            let (first, second) = (&pair[0], &pair[1]);
+            let word = sub_chars(txt, first.clone());
+            if !word.chars().all(char::is_alphabetic)
+                || INTENTIONALLY_REPEATED.contains(&word.to_lowercase().as_str())
+                || word.to_lowercase() != sub_chars(txt, second.clone()).to_lowercase()
+            {
+                return None;
+            }
+            // only separated by whitespace within the same paragraph
+            let gap = sub_chars(txt, first.end..second.start);
+            if gap.is_empty() || !gap.chars().all(char::is_whitespace) || gap.contains("\n\n") {
+                return None;
+            }
+            Some(Mistake {
+                range: first.start..second.end,
+                description: format!("The word `{}` is repeated.", &word),
+                replacements: vec![word],
+            })
+        })
+        .collect()
+}
+
+/// Multiple spaces between two words of the same line.
+fn whitespace(txt: &str) -> Vec<Mistake> {
+    let chars = txt.chars().collect::<Vec<char>>();
+    let mut acc = Vec::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        if chars[idx] != ' ' {
+            idx += 1;
+            continue;
+        }
+        let start = idx;
+        while idx < chars.len() && chars[idx] == ' ' {
+            idx += 1;
+        }
+        // leading and trailing spaces carry meaning in markdown
+        let inline =
+            start > 0 && chars[start - 1] != '\n' && idx < chars.len() && chars[idx] != '\n';
+        if inline && idx - start > 1 {
+            acc.push(Mistake {
+                range: start..idx,
+                replacements: vec![" ".to_owned()],
+                description: "Multiple consecutive spaces.".to_owned(),
+            });
+        }
+    }
+    acc
+}
+
+/// Parentheses and brackets without a counterpart.
+fn brackets(txt: &str) -> Vec<Mistake> {
+    let chars = txt.chars().collect::<Vec<char>>();
+    let mut open = Vec::<(char, usize)>::new();
+    let mut acc = Vec::new();
+    let unbalanced = |idx: usize, c: char| Mistake {
+        range: idx..idx + 1,
+        replacements: Vec::new(),
+        description: format!("Unbalanced `{}`, the counterpart is missing.", c),
+    };
+    for (idx, c) in chars.iter().copied().enumerate() {
+        // smileys
+        if idx > 0 && (chars[idx - 1] == ':' || chars[idx - 1] == ';') {
+            continue;
+        }
+        match c {
+            '(' | '[' | '{' => open.push((c, idx)),
+            ')' | ']' | '}' => {
+                let counterpart = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                if open.last().map(|(c, _)| *c) == Some(counterpart) {
+                    let _ = open.pop();
+                } else if !is_list_marker(&chars, idx) {
+                    acc.push(unbalanced(idx, c));
+                }
+            }
+            _ => {}
+        }
+    }
+    acc.extend(open.into_iter().map(|(c, idx)| unbalanced(idx, c)));
+    acc.sort_by_key(|mistake| mistake.range.start);
+    acc
+}
+
+/// Enumerations such as `a)` or `12)` at the beginning of a line.
+fn is_list_marker(chars: &[char], idx: usize) -> bool {
+    let start = chars[..idx]
+        .iter()
+        .rposition(|c| !c.is_alphanumeric())
+        .map(|pos| pos + 1)
+        .unwrap_or(0);
+    let marker_len = idx - start;
+    let line_start = chars[..start]
+        .iter()
+        .rev()
+        .take_while(|c| **c != '\n')
+        .all(|c| c.is_whitespace());
+    (1..=2).contains(&marker_len) && line_start
+}
+
+/// Inline code which is never closed, fenced code blocks are skipped.
+fn backticks(raw: &str) -> Vec<Mistake> {
+    let mut acc = Vec::new();
+    // runs of backticks of the current paragraph, start and length
+    let mut runs = Vec::<(usize, usize)>::new();
+    let mut fenced = false;
+    let mut offset = 0;
+    for line in raw.split('\n') {
+        let len = line.chars().count();
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            unclosed(&mut runs, &mut acc);
+        } else if trimmed.is_empty() {
+            unclosed(&mut runs, &mut acc);
+        } else if !fenced {
+            let chars = line.chars().collect::<Vec<char>>();
+            let mut idx = 0;
+            while idx < chars.len() {
+                if chars[idx] != '`' || (idx > 0 && chars[idx - 1] == '\\') {
+                    idx += 1;
+                    continue;
+                }
+                let start = idx;
+                while idx < chars.len() && chars[idx] == '`' {
+                    idx += 1;
+                }
+                runs.push((offset + start, idx - start));
+            }
+        }
+        offset += len + 1;
+    }
+    unclosed(&mut runs, &mut acc);
+    acc
+}
+
+/// Pair the backtick runs of a paragraph, a run is only closed by a run of
+/// the same length, and report all runs that remain open.
+fn unclosed(runs: &mut Vec<(usize, usize)>, acc: &mut Vec<Mistake>) {
+    let mut idx = 0;
+    while idx < runs.len() {
+        let (start, len) = runs[idx];
+        if let Some(closing) = runs[(idx + 1)..]
+            .iter()
+            .position(|(_, other)| *other == len)
+        {
+            idx += closing + 2;
+        } else {
+            acc.push(Mistake {
+                range: start..start + len,
+                replacements: Vec::new(),
+                description: "Unbalanced backtick, the inline code is never closed.".to_owned(),
+            });
+            idx += 1;
+        }
+    }
+    runs.clear();
+}
+
+/// Sentences ending with `..` rather than a single period or an ellipsis.
+fn punctuation(txt: &str) -> Vec<Mistake> {
+    let chars = txt.chars().collect::<Vec<char>>();
+    (1..chars.len().saturating_sub(1))
+        .filter(|idx| {
+            let idx = *idx;
+            chars[idx] == '.'
+                && chars[idx + 1] == '.'
+                && chars[idx - 1].is_alphabetic()
+                && chars.get(idx + 2).map_or(true, |c| c.is_whitespace())
+        })
+        .map(|idx| Mistake {
+            range: idx..idx + 2,
+            replacements: vec![".".to_owned()],
+            description: "The sentence ends with two periods.".to_owned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fluff_up;
+    use crate::Documentation;
+
+    fn ranges(mistakes: Vec<Mistake>) -> Vec<Range> {
+        mistakes.into_iter().map(|mistake| mistake.range).collect()
+    }
+
+    #[test]
+    fn repeated() {
+        let mistakes = repeated_words("Add the the value to The\nthe set.");
+        assert_eq!(ranges(mistakes.clone()), vec![4..11, 21..28]);
+        assert_eq!(mistakes[0].replacements, vec!["the".to_owned()]);
+
+        assert!(repeated_words("He said that that is fine.").is_empty());
+        assert!(repeated_words("End of the\n\nthe next paragraph.").is_empty());
+        assert!(repeated_words("Takes 1 1 or nothing.").is_empty());
+    }
+
+    #[test]
+    fn spaces() {
+        assert_eq!(ranges(whitespace("One  two   three.")), vec![3..5, 8..11]);
+        assert!(whitespace("  Indented\nand trailing  \nline.").is_empty());
+    }
+
+    #[test]
+    fn balanced_brackets() {
+        assert_eq!(ranges(brackets("Just (a test.")), vec![5..6]);
+        assert_eq!(ranges(brackets("Just a test] (really)")), vec![11..12]);
+        assert!(brackets("A (nested [list]) and a smiley :)").is_empty());
+        assert!(brackets("Steps:\n a) first\n 2) second").is_empty());
+    }
+
+    #[test]
+    fn balanced_backticks() {
+        assert!(backticks(" Use `foo` or ``bar ` baz``.").is_empty());
+        assert_eq!(ranges(backticks(" Use `foo or ``bar``.")), vec![5..6]);
+        assert!(backticks(" ```rust\n let x = `;\n ```").is_empty());
+        // a paragraph closes all inline code
+        assert_eq!(ranges(backticks(" `foo\n\n bar`")), vec![1..2, 11..12]);
+    }
+
+    #[test]
+    fn two_periods() {
+        let mistakes = punctuation("The end.. Or not... Or 0..1.");
+        assert_eq!(ranges(mistakes.clone()), vec![7..9]);
+        assert_eq!(mistakes[0].replacements, vec![".".to_owned()]);
+    }
+
+    #[test]
+    fn checker() {
+        let _ = env_logger::builder()
+            .is_test(true)
+            .filter(None, log::LevelFilter::Trace)
+            .try_init();
+
+        let source = fluff_up!["A test of the the checker..", "With `code and  spaces."];
+        let docs = Documentation::from((ContentOrigin::TestEntityRust, source));
+        let suggestions = TypographyChecker::check(&docs, &TypographyConfig::default())
+            .expect("Typography checker never fails");
+        let found = suggestions
+            .suggestions(&ContentOrigin::TestEntityRust)
+            .map(|suggestion| {
+                (
+                    sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                    suggestion.replacements.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("the the".to_owned(), vec!["the".to_owned()]),
+                ("..".to_owned(), vec![".".to_owned()]),
+                ("  ".to_owned(), vec![" ".to_owned()]),
+                ("`".to_owned(), vec![]),
+            ]
+        );
+
+        let config = TypographyConfig {
+            repeated_words: Some(false),
+            ..Default::default()
+        };
+        let suggestions =
+            TypographyChecker::check(&docs, &config).expect("Typography checker never fails");
+        assert_eq!(suggestions.total_count(), 3);
+    }
+}
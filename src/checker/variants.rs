@@ -0,0 +1,401 @@
+//! Spelling variants
+//!
+//! Detects words used in both their American and British form across the
+//! whole project, i.e. `initialize` and `initialise`. Both are accepted by the
+//! dictionaries, but mixing them looks sloppy. The minority form is reported,
+//! unless a preferred spelling is configured.
+//!
+//! Variants are derived from suffix rules, so a form only counts as variant if
+//! the other form is used as well.
+
+use super::{grouped, tokenize, Checker};
+use crate::config::{Spelling, VariantsConfig};
+use crate::documentation::{CheckableChunk, ContentOrigin};
+use crate::suggestion::{Detector, Suggestion, SuggestionSet};
+use crate::util::sub_chars;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use log::trace;
+
+/// A spelling difference of both variants, followed by one of the suffixes.
+struct Rule {
+    american: &'static str,
+    british: &'static str,
+    suffixes: &'static [&'static str],
+    /// Minimal number of characters in front of the variant part.
+    min_stem: usize,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        american: "iz",
+        british: "is",
+        suffixes: &["e", "es", "ed", "ing", "er", "ers", "ation", "ations"],
+        min_stem: 3,
+    },
+    Rule {
+        american: "yz",
+        british: "ys",
+        suffixes: &["e", "es", "ed", "ing", "er", "ers"],
+        min_stem: 2,
+    },
+    Rule {
+        american: "or",
+        british: "our",
+        suffixes: &[
+            "", "s", "ed", "ing", "ful", "able", "ite", "ites", "hood", "less",
+        ],
+        min_stem: 3,
+    },
+    Rule {
+        american: "er",
+        british: "re",
+        suffixes: &["", "s"],
+        min_stem: 3,
+    },
+    Rule {
+        american: "el",
+        british: "ell",
+        suffixes: &["ed", "ing", "er", "ers"],
+        min_stem: 3,
+    },
+];
+
+/// Variants which do not follow a rule, matched as whole words.
+const WORDS: &[(&str, &str)] = &[
+    ("gray", "grey"),
+    ("defense", "defence"),
+    ("offense", "offence"),
+    ("artifact", "artefact"),
+    ("artifacts", "artefacts"),
+    ("judgment", "judgement"),
+    ("acknowledgment", "acknowledgement"),
+    ("fulfill", "fulfil"),
+    ("enrollment", "enrolment"),
+    ("aluminum", "aluminium"),
+    ("skeptical", "sceptical"),
+    ("catalog", "catalogue"),
+    ("analog", "analogue"),
+];
+
+/// A word recognized as one of two spelling variants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Variant {
+    /// Identifies the word family, shared by both variants and all
+    /// suffixes of a rule, i.e. `initialize` and `initialised`.
+    family: String,
+    spelling: Spelling,
+    /// The word in the other spelling, lowercase.
+    other: String,
+    /// Number of trailing characters which differ from the other spelling.
+    tail: usize,
+}
+
+/// Determine if the word could be a spelling variant.
+fn variant(word: &str) -> Option<Variant> {
+    let lowercase = word.to_lowercase();
+    if !lowercase.chars().all(char::is_alphabetic) {
+        return None;
+    }
+    for &(american, british) in WORDS {
+        for &(this, other, spelling) in &[
+            (american, british, Spelling::American),
+            (british, american, Spelling::British),
+        ] {
+            if lowercase == this {
+                return Some(Variant {
+                    family: american.to_owned(),
+                    spelling,
+                    other: other.to_owned(),
+                    tail: this.chars().count(),
+                });
+            }
+        }
+    }
+    for (idx, rule) in RULES.iter().enumerate() {
+        for suffix in rule.suffixes {
+            for &(this, other, spelling) in &[
+                (rule.american, rule.british, Spelling::American),
+                (rule.british, rule.american, Spelling::British),
+            ] {
+                let tail = format!("{}{}", this, suffix);
+                if let Some(stem) = lowercase.strip_suffix(tail.as_str()) {
+                    if stem.chars().count() < rule.min_stem {
+                        continue;
+                    }
+                    return Some(Variant {
+                        family: format!("{}:{}", idx, stem),
+                        spelling,
+                        other: format!("{}{}{}", stem, other, suffix),
+                        tail: tail.chars().count(),
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Spell the word in the other variant, keeping the casing of the stem.
+fn respell(word: &str, variant: &Variant) -> String {
+    if word.chars().count() > 1 && word.chars().all(char::is_uppercase) {
+        return variant.other.to_uppercase();
+    }
+    let keep = word.chars().count() - variant.tail;
+    let mut respelled = word.chars().take(keep).collect::<String>();
+    let mut other = variant.other.chars().skip(keep);
+    if keep == 0 && word.chars().next().map_or(false, char::is_uppercase) {
+        respelled.extend(other.next().into_iter().flat_map(char::to_uppercase));
+    }
+    respelled.extend(other);
+    respelled
+}
+
+/// Occurrences of both spellings of a word family.
+#[derive(Debug, Default)]
+struct Count {
+    american: usize,
+    british: usize,
+}
+
+impl Count {
+    fn add(&mut self, spelling: Spelling) {
+        match spelling {
+            Spelling::American => self.american += 1,
+            Spelling::British => self.british += 1,
+        }
+    }
+
+    fn get(&self, spelling: Spelling) -> usize {
+        match spelling {
+            Spelling::American => self.american,
+            Spelling::British => self.british,
+        }
+    }
+
+    /// The spelling used more often, if any.
+    fn majority(&self) -> Option<Spelling> {
+        if self.american > self.british {
+            Some(Spelling::American)
+        } else if self.british > self.american {
+            Some(Spelling::British)
+        } else {
+            None
+        }
+    }
+}
+
+pub(crate) struct VariantsChecker;
+
+impl Checker for VariantsChecker {
+    type Config = VariantsConfig;
+
+    fn check<'a, 's, D>(docu: D, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+        D: IntoIterator<Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>)>,
+    {
+        let docu = docu.into_iter().collect::<Vec<_>>();
+
+        // count both spellings of all word families in the whole project
+        let mut counts = IndexMap::<String, Count>::new();
+        for (_origin, chunks) in docu.iter() {
+            for chunk in chunks.iter() {
+                let plain = chunk.erase_markdown();
+                for range in tokenize(plain.as_str()) {
+                    if let Some(variant) = variant(&sub_chars(plain.as_str(), range)) {
+                        counts
+                            .entry(variant.family)
+                            .or_insert_with(Count::default)
+                            .add(variant.spelling);
+                    }
+                }
+            }
+        }
+
+        // explicitly preferred words win over the configured spelling,
+        // which wins over the majority within the family
+        // only families with both spellings are mixed, a tie is decided by
+        // the spelling used more often across all of them
+        counts.retain(|_family, count| count.american > 0 && count.british > 0);
+        let total = counts
+            .values()
+            .fold(Count::default(), |total, count| Count {
+                american: total.american + count.american,
+                british: total.british + count.british,
+            });
+        let mut preferred = IndexMap::<String, Spelling>::new();
+        for (family, count) in counts.iter() {
+            let spelling = count
+                .majority()
+                .or_else(|| total.majority())
+                .unwrap_or(Spelling::American);
+            let _ = preferred.insert(family.clone(), config.spelling.unwrap_or(spelling));
+        }
+        for word in config.prefer.iter().flatten() {
+            if let Some(variant) = variant(word) {
+                if let Some(spelling) = preferred.get_mut(&variant.family) {
+                    *spelling = variant.spelling;
+                }
+            }
+        }
+        trace!("Preferred spelling variants: {:?}", &preferred);
+
+        let mut acc = SuggestionSet::new();
+        if preferred.is_empty() {
+            return Ok(acc);
+        }
+        for (origin, chunks) in docu {
+            for chunk in chunks {
+                let plain = chunk.erase_markdown();
+                for range in tokenize(plain.as_str()) {
+                    let word = sub_chars(plain.as_str(), range.clone());
+                    let variant = if let Some(variant) = variant(&word) {
+                        variant
+                    } else {
+                        continue;
+                    };
+                    let spelling = match preferred.get(&variant.family) {
+                        Some(spelling) if *spelling != variant.spelling => *spelling,
+                        _ => continue,
+                    };
+                    let replacement = respell(&word, &variant);
+                    let description = if config.spelling.is_some() {
+                        format!(
+                            "Inconsistent spelling, the {} `{}` is preferred.",
+                            spelling, &replacement
+                        )
+                    } else {
+                        let count = &counts[&variant.family];
+                        format!(
+                            "Inconsistent spelling, `{}` is used {} times and `{}` {} times.",
+                            &replacement,
+                            count.get(spelling),
+                            &word,
+                            count.get(variant.spelling)
+                        )
+                    };
+                    let spans = grouped(plain.find_spans(range.clone()));
+                    // a partial replacement would garble the text
+                    let replacements = if spans.len() == 1 {
+                        vec![replacement]
+                    } else {
+                        Vec::new()
+                    };
+                    for (range, span) in spans {
+                        acc.add(
+                            origin.clone(),
+                            Suggestion {
+                                detector: Detector::Variants,
+                                origin: origin.clone(),
+                                chunk,
+                                span,
+                                range,
+                                replacements: replacements.clone(),
+                                description: Some(description.clone()),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fluff_up, Config, Documentation};
+
+    #[test]
+    fn variants() {
+        let initialise = variant("Initialised").expect("Is a variant");
+        assert_eq!(initialise.spelling, Spelling::British);
+        assert_eq!(initialise.other, "initialized");
+        assert_eq!(respell("Initialised", &initialise), "Initialized");
+        assert_eq!(
+            variant("initialize").expect("Is a variant").family,
+            initialise.family
+        );
+
+        let grey = variant("Grey").expect("Is a variant");
+        assert_eq!(respell("Grey", &grey), "Gray");
+        assert_eq!(respell("GREY", &grey), "GRAY");
+        assert_eq!(respell("colours", &variant("colours").unwrap()), "colors");
+        assert_eq!(
+            respell("travelled", &variant("travelled").unwrap()),
+            "traveled"
+        );
+
+        // stems too short to be a meaningful variant
+        assert_eq!(variant("four"), None);
+        assert_eq!(variant("size"), None);
+        assert_eq!(variant("foo_bar"), None);
+    }
+
+    fn found(config: &VariantsConfig, source: &str) -> Vec<(String, Vec<String>)> {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::from((origin.clone(), source));
+        let suggestions =
+            VariantsChecker::check(&docs, config).expect("Variants checker never fails");
+        if suggestions.total_count() == 0 {
+            return Vec::new();
+        }
+        suggestions
+            .suggestions(&origin)
+            .map(|suggestion| {
+                (
+                    sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                    suggestion.replacements.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn majority() {
+        let source = fluff_up![
+            "Initialize the colour, the colour is initialized.",
+            "Initialising a color the editor colours."
+        ];
+        assert_eq!(
+            found(&VariantsConfig::default(), source),
+            vec![
+                ("Initialising".to_owned(), vec!["Initializing".to_owned()]),
+                ("color".to_owned(), vec!["colour".to_owned()]),
+            ]
+        );
+
+        // consistent on its own
+        assert!(found(
+            &VariantsConfig::default(),
+            fluff_up!["The colour of the centre."]
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn configured() {
+        let config = Config::parse(
+            r#"
+[variants]
+spelling = "american"
+prefer = ["centre"]
+			"#,
+        )
+        .expect("Must parse");
+        let config = config.variants.expect("Contains variants");
+
+        let source = fluff_up!["The colour is colour, the color at the centre, not the center."];
+        assert_eq!(
+            found(&config, source),
+            vec![
+                ("colour".to_owned(), vec!["color".to_owned()]),
+                ("colour".to_owned(), vec!["color".to_owned()]),
+                ("center".to_owned(), vec!["centre".to_owned()]),
+            ]
+        );
+    }
+}
@@ -8,13 +8,14 @@
 // TODO pendeng refactor, avoid spending time on documenting the status quo.
 #![allow(missing_docs)]
 
-use crate::suggestion::Detector;
+use crate::suggestion::{Detector, Severity};
 use anyhow::{anyhow, bail, Error, Result};
 use fancy_regex::Regex;
 
 use log::trace;
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::convert::AsRef;
 use std::fmt;
 use std::fs::File;
@@ -31,6 +32,25 @@ pub struct Config {
     #[serde(alias = "languageTool")]
     #[serde(alias = "Languagetool")]
     pub languagetool: Option<LanguageToolConfig>,
+    #[serde(alias = "Typography")]
+    pub typography: Option<TypographyConfig>,
+    #[serde(alias = "Rustdoc")]
+    #[serde(alias = "RustDoc")]
+    pub rustdoc: Option<RustdocConfig>,
+    #[serde(alias = "Terminology")]
+    pub terminology: Option<TerminologyConfig>,
+    #[serde(alias = "Inclusive")]
+    pub inclusive: Option<InclusiveConfig>,
+    #[serde(alias = "Variants")]
+    pub variants: Option<VariantsConfig>,
+    #[serde(alias = "Readability")]
+    pub readability: Option<ReadabilityConfig>,
+    #[serde(alias = "Reflow")]
+    pub reflow: Option<ReflowConfig>,
+    /// Overrides applied to all content origins matching a glob, in order.
+    #[serde(rename = "override")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<Vec<Override>>,
 }
 
 #[derive(Debug)]
@@ -114,6 +134,72 @@ impl<'de> serde::de::Visitor<'de> for RegexVisitor {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct WrappedGlob(pub glob::Pattern);
+
+impl std::ops::Deref for WrappedGlob {
+    type Target = glob::Pattern;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Serialize for WrappedGlob {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for WrappedGlob {
+    fn deserialize<D>(deserializer: D) -> Result<WrappedGlob, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(GlobVisitor).map(WrappedGlob)
+    }
+}
+
+struct GlobVisitor;
+
+impl<'de> serde::de::Visitor<'de> for GlobVisitor {
+    type Value = glob::Pattern;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("String with valid glob pattern")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        glob::Pattern::new(value).map_err(E::custom)
+    }
+}
+
+impl WrappedGlob {
+    /// Check if the glob matches the given path.
+    ///
+    /// Relative globs are matched against the path relative to `base`,
+    /// absolute ones against the full path. `*` does not match across `/`,
+    /// but `**` does.
+    pub fn matches(&self, path: &Path, base: &Path) -> bool {
+        let options = glob::MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        if self.0.matches_path_with(path, options) {
+            return true;
+        }
+        path.strip_prefix(base)
+            .map(|relative| self.0.matches_path_with(relative, options))
+            .unwrap_or(false)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Quirks {
     /// A regular expression, whose capture groups will be checked, instead of the initial token.
@@ -252,30 +338,143 @@ impl<'de> serde::de::Visitor<'de> for SearchDirVisitor {
     }
 }
 
+/// One or multiple languages, either given as a single string or as a list.
+///
+/// `lang = "en_US"` and `lang = ["en_US", "en_GB"]` are both valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Languages(pub Vec<String>);
+
+impl std::ops::Deref for Languages {
+    type Target = Vec<String>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Serialize for Languages {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        if self.0.len() == 1 {
+            serializer.serialize_str(self.0[0].as_str())
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Languages {
+    fn deserialize<D>(deserializer: D) -> Result<Languages, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(LanguagesVisitor)
+    }
+}
+
+impl From<String> for Languages {
+    fn from(other: String) -> Languages {
+        Languages(vec![other])
+    }
+}
+
+impl From<Vec<String>> for Languages {
+    fn from(other: Vec<String>) -> Languages {
+        Languages(other)
+    }
+}
+
+struct LanguagesVisitor;
+
+impl<'de> serde::de::Visitor<'de> for LanguagesVisitor {
+    type Value = Languages;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("A language code or a non-empty sequence of language codes")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Languages::from(value.to_owned()))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut v = Vec::with_capacity(4);
+        while let Some(item) = seq.next_element::<String>()? {
+            v.push(item);
+        }
+        if v.is_empty() {
+            return Err(serde::de::Error::invalid_length(0, &self));
+        }
+        Ok(Languages(v))
+    }
+}
+
+/// How words are treated, that are only accepted by some of the configured languages.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LangPolicy {
+    /// A word is correct if any of the dictionaries accepts it.
+    Any,
+    /// A word is correct if any of the dictionaries accepts it, but words that
+    /// are not accepted by the dominant language of a document are flagged as
+    /// a mix of spelling variants.
+    Consistent,
+}
+
+impl Default for LangPolicy {
+    fn default() -> Self {
+        Self::Any
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct HunspellConfig {
-    /// The language we want to check against, used as the dictionary and affixes file name.
+    /// The languages we want to check against, used as the dictionary and affixes file names.
     // TODO impl a custom xx_YY code deserializer based on iso crates
-    pub lang: Option<String>,
+    pub lang: Option<Languages>,
+    /// Treatment of words which are only accepted by a subset of the languages.
+    pub lang_policy: Option<LangPolicy>,
     /// Additional search dirs for `.dic` and `.aff` files.
     // must be option so it can be omitted in the config
     pub search_dirs: SearchDirs,
     /// Additional dictionaries for topic specific lingo.
     pub extra_dictionaries: Option<Vec<PathBuf>>,
+    /// Look up replacement suggestions for misspelled words, defaults to `true`.
+    pub suggestions: Option<bool>,
+    /// Maximal number of replacement suggestions per word.
+    pub max_suggestions: Option<usize>,
+    /// Time budget in milliseconds to look up suggestions during the whole run,
+    /// once spent the remaining mistakes are reported without replacements.
+    pub suggestion_budget: Option<u64>,
     /// Additional quirks besides dictionary lookups.
+    // must be last, tables are emitted after values
     pub quirks: Option<Quirks>,
 }
 
 impl HunspellConfig {
-    pub fn lang(&self) -> &str {
+    pub fn lang(&self) -> &[String] {
+        lazy_static::lazy_static! {
+            static ref DEFAULT_LANG: Vec<String> = vec!["en_US".to_owned()];
+        };
         if let Some(ref lang) = self.lang {
-            lang.as_str()
+            lang.as_slice()
         } else {
-            "en_US"
+            DEFAULT_LANG.as_slice()
         }
     }
 
+    pub fn lang_policy(&self) -> LangPolicy {
+        self.lang_policy.unwrap_or_default()
+    }
+
     pub fn search_dirs(&self) -> &[PathBuf] {
         if let Some(ref search_dirs) = self.search_dirs.as_ref() {
             search_dirs.as_slice()
@@ -284,6 +483,14 @@ impl HunspellConfig {
         }
     }
 
+    pub fn suggestions(&self) -> bool {
+        self.suggestions.unwrap_or(true)
+    }
+
+    pub fn suggestion_budget(&self) -> Option<std::time::Duration> {
+        self.suggestion_budget.map(std::time::Duration::from_millis)
+    }
+
     pub fn extra_dictionaries(&self) -> &[PathBuf] {
         if let Some(ref extra_dictionaries) = self.extra_dictionaries {
             extra_dictionaries.as_slice()
@@ -351,6 +558,98 @@ impl HunspellConfig {
     }
 }
 
+/// A partial `HunspellConfig`, where every given value is applied on top
+/// of the base configuration.
+///
+/// `lang`, `lang_policy` and the `quirks` flags replace the base values,
+/// `search_dirs`, `extra_dictionaries` and `quirks.transform_regex` are
+/// added to the base values.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct HunspellOverride {
+    pub lang: Option<Languages>,
+    pub lang_policy: Option<LangPolicy>,
+    pub search_dirs: Option<Vec<PathBuf>>,
+    pub extra_dictionaries: Option<Vec<PathBuf>>,
+    pub quirks: Option<Quirks>,
+}
+
+impl HunspellOverride {
+    /// Apply the override on top of `base`.
+    fn apply_to(&self, base: &mut HunspellConfig) {
+        if let Some(ref lang) = self.lang {
+            base.lang = Some(lang.clone());
+        }
+        if let Some(lang_policy) = self.lang_policy {
+            base.lang_policy = Some(lang_policy);
+        }
+        if let Some(ref search_dirs) = self.search_dirs {
+            // take precedence over the ones already present
+            let mut combined = search_dirs.clone();
+            combined.extend(base.search_dirs().iter().cloned());
+            base.search_dirs = SearchDirs(Some(combined));
+        }
+        if let Some(ref extra_dictionaries) = self.extra_dictionaries {
+            base.extra_dictionaries
+                .get_or_insert_with(Vec::new)
+                .extend(extra_dictionaries.iter().cloned());
+        }
+        if let Some(ref quirks) = self.quirks {
+            let base_quirks = base.quirks.get_or_insert_with(Quirks::default);
+            if let Some(ref transform_regex) = quirks.transform_regex {
+                base_quirks
+                    .transform_regex
+                    .get_or_insert_with(Vec::new)
+                    .extend(transform_regex.iter().cloned());
+            }
+            if let Some(allow_concatenation) = quirks.allow_concatenation {
+                base_quirks.allow_concatenation = Some(allow_concatenation);
+            }
+            if let Some(allow_dashes) = quirks.allow_dashes {
+                base_quirks.allow_dashes = Some(allow_dashes);
+            }
+        }
+    }
+
+    /// Convert all relative paths to absolute paths in relation to `base`.
+    fn sanitize_paths(&mut self, base: &Path) {
+        let absolute = |path: &mut PathBuf| {
+            if !path.is_absolute() {
+                let abspath = base.join(&path);
+                *path = abspath.canonicalize().unwrap_or(abspath);
+            }
+        };
+        if let Some(ref mut search_dirs) = self.search_dirs {
+            search_dirs.iter_mut().for_each(absolute);
+        }
+        if let Some(ref mut extra_dictionaries) = self.extra_dictionaries {
+            extra_dictionaries.iter_mut().for_each(absolute);
+        }
+    }
+}
+
+/// Configuration applied to all content origins whose path matches `glob`.
+///
+/// Spelling variants are compared across the whole project at once, so
+/// their settings can not be overridden, matching origins can only be
+/// excluded with `disable`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Override {
+    /// Glob pattern, relative to the current working directory or absolute.
+    pub glob: WrappedGlob,
+    /// Partial hunspell configuration applied on top of the base one.
+    #[serde(alias = "Hunspell")]
+    pub hunspell: Option<HunspellOverride>,
+    /// Replaces the base languagetool configuration.
+    #[serde(alias = "LanguageTool")]
+    #[serde(alias = "languageTool")]
+    #[serde(alias = "Languagetool")]
+    pub languagetool: Option<LanguageToolConfig>,
+    /// Checkers that are not run for matching content origins.
+    pub disable: Option<Vec<Detector>>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct LanguageToolConfig {
@@ -363,6 +662,298 @@ impl LanguageToolConfig {
     }
 }
 
+/// Rules of the built-in typography checker, each one is enabled unless
+/// explicitly set to `false`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TypographyConfig {
+    /// Flag a word which is directly followed by itself, i.e. `the the`.
+    pub repeated_words: Option<bool>,
+    /// Flag multiple consecutive spaces within a line.
+    pub whitespace: Option<bool>,
+    /// Flag parentheses and brackets without a counterpart.
+    pub brackets: Option<bool>,
+    /// Flag inline code which is never closed by a matching backtick.
+    pub backticks: Option<bool>,
+    /// Flag sentences ending with `..`.
+    pub punctuation: Option<bool>,
+}
+
+impl Default for TypographyConfig {
+    fn default() -> Self {
+        Self {
+            repeated_words: Some(true),
+            whitespace: Some(true),
+            brackets: Some(true),
+            backticks: Some(true),
+            punctuation: Some(true),
+        }
+    }
+}
+
+impl TypographyConfig {
+    pub(crate) fn repeated_words(&self) -> bool {
+        self.repeated_words.unwrap_or(true)
+    }
+
+    pub(crate) fn whitespace(&self) -> bool {
+        self.whitespace.unwrap_or(true)
+    }
+
+    pub(crate) fn brackets(&self) -> bool {
+        self.brackets.unwrap_or(true)
+    }
+
+    pub(crate) fn backticks(&self) -> bool {
+        self.backticks.unwrap_or(true)
+    }
+
+    pub(crate) fn punctuation(&self) -> bool {
+        self.punctuation.unwrap_or(true)
+    }
+}
+
+/// Rules of the rustdoc convention lint, each one is enabled unless
+/// explicitly set to `false`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RustdocConfig {
+    /// The summary line is a single sentence, ending with a period.
+    pub summary_sentence: Option<bool>,
+    /// The summary line is followed by a blank line.
+    pub summary_blank_line: Option<bool>,
+    /// The summary of a function starts with a verb in third person, i.e. `Returns`.
+    pub third_person: Option<bool>,
+    /// An `unsafe fn` has a `# Safety` section.
+    pub safety_section: Option<bool>,
+    /// A public function returning a `Result` has an `# Errors` section.
+    pub errors_section: Option<bool>,
+    /// A public function which can panic has a `# Panics` section.
+    pub panics_section: Option<bool>,
+    /// Identifiers in inline code exist on the documented item or in the crate.
+    pub stale_identifiers: Option<bool>,
+}
+
+impl Default for RustdocConfig {
+    fn default() -> Self {
+        Self {
+            summary_sentence: Some(true),
+            summary_blank_line: Some(true),
+            third_person: Some(true),
+            safety_section: Some(true),
+            errors_section: Some(true),
+            panics_section: Some(true),
+            stale_identifiers: Some(true),
+        }
+    }
+}
+
+impl RustdocConfig {
+    pub(crate) fn summary_sentence(&self) -> bool {
+        self.summary_sentence.unwrap_or(true)
+    }
+
+    pub(crate) fn summary_blank_line(&self) -> bool {
+        self.summary_blank_line.unwrap_or(true)
+    }
+
+    pub(crate) fn third_person(&self) -> bool {
+        self.third_person.unwrap_or(true)
+    }
+
+    pub(crate) fn safety_section(&self) -> bool {
+        self.safety_section.unwrap_or(true)
+    }
+
+    pub(crate) fn errors_section(&self) -> bool {
+        self.errors_section.unwrap_or(true)
+    }
+
+    pub(crate) fn panics_section(&self) -> bool {
+        self.panics_section.unwrap_or(true)
+    }
+
+    pub(crate) fn stale_identifiers(&self) -> bool {
+        self.stale_identifiers.unwrap_or(true)
+    }
+}
+
+/// Preferred terms, each table maps disallowed forms to the preferred replacement.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct TerminologyConfig {
+    /// Disallowed words, matched as whole words with the exact casing.
+    pub exact: Option<BTreeMap<String, String>>,
+    /// Disallowed words, matched as whole words regardless of their casing.
+    #[serde(alias = "case-insensitive")]
+    pub case_insensitive: Option<BTreeMap<String, String>>,
+    /// Regular expressions, the replacement may refer to capture groups, i.e. `$1`.
+    pub regex: Option<RegexTerms>,
+}
+
+/// Inclusive language, extends or overrides the built-in list of terms.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct InclusiveConfig {
+    /// Check the built-in list of terms, defaults to `true`.
+    pub builtin: Option<bool>,
+    /// Additional terms, replacing a built-in term of the same name.
+    pub terms: Option<BTreeMap<String, InclusiveTerm>>,
+    /// Built-in terms which are accepted nonetheless.
+    pub allow: Option<Vec<String>>,
+    /// Severity of the findings, defaults to `warning`.
+    pub severity: Option<Severity>,
+    /// Exit code if any finding is reported, regardless of its severity.
+    pub code: Option<u8>,
+}
+
+impl InclusiveConfig {
+    pub(crate) fn builtin(&self) -> bool {
+        self.builtin.unwrap_or(true)
+    }
+
+    pub(crate) fn severity(&self) -> Severity {
+        self.severity.unwrap_or(Severity::Warning)
+    }
+}
+
+/// A term to avoid, matched as whole word regardless of its casing.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct InclusiveTerm {
+    /// Suggested alternatives, the first one is the preferred one.
+    #[serde(default)]
+    pub alternatives: Vec<String>,
+    /// Why the term should be avoided.
+    pub explanation: Option<String>,
+}
+
+/// Spelling of words with both an American and a British variant.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Spelling {
+    #[serde(alias = "American", alias = "en_US")]
+    American,
+    #[serde(alias = "British", alias = "en_GB")]
+    British,
+}
+
+impl fmt::Display for Spelling {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::American => "American",
+            Self::British => "British",
+        })
+    }
+}
+
+/// Consistent spelling variants across the whole project.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct VariantsConfig {
+    /// Preferred spelling of mixed words, by default the more frequent one wins.
+    pub spelling: Option<Spelling>,
+    /// Preferred variants of individual words, taking precedence over `spelling`.
+    pub prefer: Option<Vec<String>>,
+}
+
+/// Readability metrics of every section, sections are separated by headings.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ReadabilityConfig {
+    /// Print the metrics of every section after checking, defaults to `true`.
+    pub summary: Option<bool>,
+    /// Minimal Flesch reading ease of a section.
+    pub min_reading_ease: Option<f64>,
+    /// Maximal average number of words per sentence of a section.
+    pub max_average_sentence_length: Option<f64>,
+    /// Maximal number of words of a single sentence.
+    pub max_sentence_length: Option<usize>,
+    /// Sections with fewer words are exempt from the section wide thresholds,
+    /// defaults to `30`.
+    pub min_words: Option<usize>,
+}
+
+impl ReadabilityConfig {
+    pub(crate) fn summary(&self) -> bool {
+        self.summary.unwrap_or(true)
+    }
+
+    pub(crate) fn min_words(&self) -> usize {
+        self.min_words.unwrap_or(30)
+    }
+}
+
+/// Rewrapping of doc comment paragraphs.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ReflowConfig {
+    /// The column lines are wrapped at, including the comment prefix,
+    /// defaults to `80`.
+    pub max_line_length: Option<usize>,
+}
+
+impl ReflowConfig {
+    pub(crate) fn max_line_length(&self) -> usize {
+        self.max_line_length.unwrap_or(80)
+    }
+}
+
+/// Regular expressions mapped to their replacement, in the order of the config file.
+#[derive(Debug, Clone, Default)]
+pub struct RegexTerms(pub Vec<(WrappedRegex, String)>);
+
+impl std::ops::Deref for RegexTerms {
+    type Target = Vec<(WrappedRegex, String)>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Serialize for RegexTerms {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (regex, replacement) in self.0.iter() {
+            map.serialize_entry(regex.as_str(), replacement)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for RegexTerms {
+    fn deserialize<D>(deserializer: D) -> Result<RegexTerms, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(RegexTermsVisitor)
+    }
+}
+
+struct RegexTermsVisitor;
+
+impl<'de> serde::de::Visitor<'de> for RegexTermsVisitor {
+    type Value = RegexTerms;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Table of valid regex expressions and their replacements")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut terms = Vec::with_capacity(access.size_hint().unwrap_or(8));
+        while let Some((regex, replacement)) = access.next_entry::<WrappedRegex, String>()? {
+            terms.push((regex, replacement));
+        }
+        Ok(RegexTerms(terms))
+    }
+}
+
 impl Config {
     const QUALIFIER: &'static str = "io";
     const ORGANIZATION: &'static str = "spearow";
@@ -374,9 +965,75 @@ impl Config {
         if let Some(ref mut hunspell) = self.hunspell {
             hunspell.sanitize_paths(base)?;
         }
+        if let Some(ref mut overrides) = self.overrides {
+            for hunspell in overrides
+                .iter_mut()
+                .filter_map(|item| item.hunspell.as_mut())
+            {
+                hunspell.sanitize_paths(base);
+            }
+        }
         Ok(())
     }
 
+    /// Indices of all overrides whose glob matches `path`, in order.
+    ///
+    /// Relative globs are matched relative to `base`.
+    pub fn matching_overrides(&self, path: &Path, base: &Path) -> Vec<usize> {
+        self.overrides
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_idx, item)| item.glob.matches(path, base))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Create the effective configuration with the overrides given by index
+    /// applied in order.
+    pub fn with_overrides(&self, indices: &[usize]) -> Config {
+        let mut config = Config {
+            overrides: None,
+            ..self.clone()
+        };
+        let overrides = if let Some(ref overrides) = self.overrides {
+            overrides
+        } else {
+            return config;
+        };
+        for item in indices.iter().filter_map(|idx| overrides.get(*idx)) {
+            trace!("Applying override for {}", item.glob.as_str());
+            if let Some(ref hunspell) = item.hunspell {
+                let base = config.hunspell.get_or_insert_with(|| {
+                    Config::default()
+                        .hunspell
+                        .expect("Default config contains hunspell. qed")
+                });
+                hunspell.apply_to(base);
+            }
+            if let Some(ref languagetool) = item.languagetool {
+                config.languagetool = Some(languagetool.clone());
+            }
+            for detector in item.disable.iter().flatten() {
+                match detector {
+                    // both share the hunspell configuration
+                    Detector::Hunspell | Detector::Affix => config.hunspell = None,
+                    Detector::LanguageTool => config.languagetool = None,
+                    Detector::Typography => config.typography = None,
+                    Detector::Rustdoc => config.rustdoc = None,
+                    Detector::Terminology => config.terminology = None,
+                    Detector::Inclusive => config.inclusive = None,
+                    Detector::Variants => config.variants = None,
+                    Detector::Readability => config.readability = None,
+                    Detector::Reflow => config.reflow = None,
+                    #[cfg(test)]
+                    Detector::Dummy => {}
+                }
+            }
+        }
+        config
+    }
+
     pub fn parse<S: AsRef<str>>(s: S) -> Result<Self> {
         Ok(toml::from_str(s.as_ref())?)
     }
@@ -492,19 +1149,44 @@ impl Config {
 
     pub fn is_enabled(&self, detector: Detector) -> bool {
         match detector {
-            Detector::Hunspell => self.hunspell.is_some(),
+            Detector::Hunspell | Detector::Affix => self.hunspell.is_some(),
             Detector::LanguageTool => self.languagetool.is_some(),
+            Detector::Typography => self.typography.is_some(),
+            Detector::Rustdoc => self.rustdoc.is_some(),
+            Detector::Terminology => self.terminology.is_some(),
+            Detector::Inclusive => self.inclusive.is_some(),
+            Detector::Variants => self.variants.is_some(),
+            Detector::Readability => self.readability.is_some(),
+            Detector::Reflow => self.reflow.is_some(),
             #[cfg(test)]
             Detector::Dummy => true,
         }
     }
 
+    /// The severity suggestions of a detector are reported with.
+    pub fn severity(&self, detector: Detector) -> Severity {
+        match detector {
+            Detector::Inclusive => self
+                .inclusive
+                .as_ref()
+                .map_or(Severity::Warning, InclusiveConfig::severity),
+            _ => Severity::Error,
+        }
+    }
+
     pub fn full() -> Self {
         let languagetool = LanguageToolConfig {
             url: url::Url::parse("http://127.0.0.1:8010").expect("Default ip must be ok"),
         };
         Self {
             languagetool: Some(languagetool),
+            typography: Some(TypographyConfig::default()),
+            rustdoc: Some(RustdocConfig::default()),
+            terminology: Some(TerminologyConfig::default()),
+            inclusive: Some(InclusiveConfig::default()),
+            variants: Some(VariantsConfig::default()),
+            readability: Some(ReadabilityConfig::default()),
+            reflow: Some(ReflowConfig::default()),
             ..Default::default()
         }
     }
@@ -537,12 +1219,24 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             hunspell: Some(HunspellConfig {
-                lang: Some("en_US".to_owned()),
+                lang: Some(Languages::from("en_US".to_owned())),
+                lang_policy: Some(LangPolicy::Any),
                 search_dirs: Some(os_specific_search_dirs().to_vec()).into(),
                 extra_dictionaries: Some(Vec::new()),
+                suggestions: Some(true),
+                max_suggestions: None,
+                suggestion_budget: None,
                 quirks: Some(Quirks::default()),
             }),
             languagetool: None,
+            typography: None,
+            rustdoc: None,
+            terminology: None,
+            inclusive: None,
+            variants: None,
+            readability: None,
+            reflow: None,
+            overrides: None,
         }
     }
 }
@@ -704,4 +1398,187 @@ search_dirs = ["/search/1", "/search/2"]
         #[cfg(target_os = "macos")]
         assert!(search_dirs.len() >= 3);
     }
+
+    #[test]
+    fn partial_9() {
+        let cfg = Config::parse(
+            r#"
+[Hunspell]
+lang = ["en_US", "en_GB"]
+lang_policy = "consistent"
+			"#,
+        )
+        .unwrap();
+
+        let hunspell: HunspellConfig = cfg.hunspell.expect("Must contain hunspell cfg");
+        assert_eq!(hunspell.lang(), &["en_US".to_owned(), "en_GB".to_owned()]);
+        assert_eq!(hunspell.lang_policy(), LangPolicy::Consistent);
+    }
+
+    #[test]
+    fn partial_10() {
+        assert!(Config::parse(
+            r#"
+[Hunspell]
+lang = []
+			"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn overrides() {
+        let cfg = Config::parse(
+            r#"
+[LanguageTool]
+url = "http://127.0.0.1:8010/"
+
+[Hunspell]
+lang = "en_US"
+
+[[override]]
+glob = "docs/de/**"
+[override.hunspell]
+lang = "de_DE"
+
+[[override]]
+glob = "benches/**"
+disable = ["languagetool"]
+
+[[override]]
+glob = "CHANGELOG.md"
+[override.hunspell.quirks]
+transform_regex = ["^v[0-9]+$"]
+			"#,
+        )
+        .unwrap();
+
+        let base = Path::new("/project");
+        let matching = |path: &str| cfg.matching_overrides(Path::new(path), base);
+
+        assert_eq!(matching("/project/docs/de/intro.md"), vec![0]);
+        assert_eq!(matching("/project/docs/de/nested/deep.md"), vec![0]);
+        assert_eq!(matching("/project/docs/en/intro.md"), Vec::<usize>::new());
+        assert_eq!(matching("/project/benches/x.rs"), vec![1]);
+        assert_eq!(matching("/project/CHANGELOG.md"), vec![2]);
+        assert_eq!(matching("/project/src/CHANGELOG.md"), Vec::<usize>::new());
+        assert_eq!(matching("/elsewhere/docs/de/intro.md"), Vec::<usize>::new());
+
+        let german = cfg.with_overrides(&[0]);
+        assert_eq!(german.hunspell.unwrap().lang(), &["de_DE".to_owned()]);
+        assert!(german.languagetool.is_some());
+        assert!(german.overrides.is_none());
+
+        let benches = cfg.with_overrides(&[1]);
+        assert!(benches.languagetool.is_none());
+        assert!(benches.hunspell.is_some());
+
+        let changelog = cfg.with_overrides(&[2]);
+        let quirks = changelog.hunspell.unwrap().quirks.unwrap();
+        assert_eq!(quirks.transform_regex().len(), 1);
+    }
+
+    #[test]
+    fn overrides_variants() {
+        let cfg = Config::parse(
+            r#"
+[variants]
+spelling = "British"
+
+[[override]]
+glob = "benches/**"
+disable = ["variants"]
+			"#,
+        )
+        .unwrap();
+        assert!(cfg.with_overrides(&[]).is_enabled(Detector::Variants));
+        assert!(!cfg.with_overrides(&[0]).is_enabled(Detector::Variants));
+
+        // there is only one set of variants for the whole project
+        assert!(Config::parse(
+            r#"
+[variants]
+
+[[override]]
+glob = "docs/**"
+[override.variants]
+spelling = "American"
+			"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn overrides_invalid_glob() {
+        assert!(Config::parse(
+            r#"
+[[override]]
+glob = "src/***x"
+			"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn typography() {
+        let config = Config::parse(
+            r#"
+[typography]
+backticks = false
+			"#,
+        )
+        .unwrap();
+        assert!(config.is_enabled(Detector::Typography));
+        let typography = config.typography.unwrap();
+        assert!(typography.repeated_words());
+        assert!(!typography.backticks());
+
+        assert!(!Config::default().is_enabled(Detector::Typography));
+    }
+
+    #[test]
+    fn inclusive() {
+        let config = Config::parse(
+            r#"
+[inclusive]
+severity = "error"
+code = 3
+allow = ["master"]
+
+[inclusive.terms.guys]
+alternatives = ["folks", "everyone"]
+explanation = "Not everyone identifies as a guy."
+			"#,
+        )
+        .unwrap();
+        assert!(config.is_enabled(Detector::Inclusive));
+        assert_eq!(config.severity(Detector::Inclusive), Severity::Error);
+        assert_eq!(config.severity(Detector::Hunspell), Severity::Error);
+        let inclusive = config.inclusive.unwrap();
+        assert!(inclusive.builtin());
+        assert_eq!(inclusive.code, Some(3));
+        assert_eq!(inclusive.terms.unwrap()["guys"].alternatives.len(), 2);
+
+        let config = Config::parse("[inclusive]").unwrap();
+        assert_eq!(config.severity(Detector::Inclusive), Severity::Warning);
+    }
+
+    #[test]
+    fn lang_roundtrip() {
+        let single = Config::default();
+        let s = single.to_toml().expect("Must serialize");
+        assert!(s.contains(r#"lang = "en_US""#));
+
+        let mut multi = Config::default();
+        multi.hunspell.as_mut().unwrap().lang = Some(Languages::from(vec![
+            "en_US".to_owned(),
+            "de_DE".to_owned(),
+        ]));
+        let s = multi.to_toml().expect("Must serialize");
+        let reparsed = Config::parse(s).expect("Must parse what was serialized");
+        assert_eq!(
+            reparsed.hunspell.unwrap().lang(),
+            &["en_US".to_owned(), "de_DE".to_owned()]
+        );
+    }
 }
@@ -57,6 +57,25 @@ impl ContentOrigin {
     }
 }
 
+impl ContentOrigin {
+    /// Map a span relative to the content of this origin to a span of the
+    /// file, which only differs for the excerpt of a doc-test.
+    pub fn absolute_span(&self, span: Span) -> Span {
+        match self {
+            Self::RustDocTest(_path, excerpt) => span.rebase(excerpt.start),
+            _ => span,
+        }
+    }
+
+    /// The inverse of [`absolute_span`](Self::absolute_span).
+    pub fn relative_span(&self, span: Span) -> Result<Span> {
+        match self {
+            Self::RustDocTest(_path, excerpt) => span.relative_to_excerpt(excerpt.start),
+            _ => Ok(span),
+        }
+    }
+}
+
 impl fmt::Display for ContentOrigin {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(formatter, "{}", self.as_path().display())
@@ -73,6 +92,10 @@ pub struct CheckableChunk {
     /// `Span` referencing the location within the source file.
     /// For a markdown file i.e. this would become a single entry spanning from start to end.
     source_mapping: IndexMap<Range, Span>,
+    /// The item the chunk documents, only known for rust sources.
+    context: Option<ItemContext>,
+    /// The syntax of the literal every fragment originates from, only known for rust sources.
+    kinds: IndexMap<Range, LiteralKind>,
 }
 
 impl std::hash::Hash for CheckableChunk {
@@ -101,9 +124,17 @@ impl CheckableChunk {
         Self {
             content,
             source_mapping,
+            context: None,
+            kinds: IndexMap::new(),
         }
     }
 
+    /// Attach the syntax of the literal each fragment originates from.
+    pub(crate) fn with_kinds(mut self, kinds: IndexMap<Range, LiteralKind>) -> Self {
+        self.kinds = kinds;
+        self
+    }
+
     /// Find which part of the range maps to which span.
     /// Note that Range can very well be split into multiple fragments
     /// where each of them can be mapped to a potentially non-continuous
@@ -118,7 +149,7 @@ impl CheckableChunk {
     ///           (13,17) => (4,0)->(4,3),
     /// ]
     /// ```
-    pub(super) fn find_spans(&self, range: Range) -> IndexMap<Range, Span> {
+    pub fn find_spans(&self, range: Range) -> IndexMap<Range, Span> {
         trace!(target: "find_spans",
             "############################################ chunk find_span {:?}",
             &range
@@ -209,6 +240,22 @@ impl CheckableChunk {
             .collect::<IndexMap<_, _>>()
     }
 
+    /// The item the chunk documents, if known.
+    pub fn context(&self) -> Option<&ItemContext> {
+        self.context.as_ref()
+    }
+
+    /// The syntax of the literal the start of `range` originates from, if known.
+    pub fn kind(&self, range: &Range) -> Option<LiteralKind> {
+        self.kinds
+            .iter()
+            .find(|(fragment, _kind)| {
+                fragment.start <= range.start
+                    && (range.start < fragment.end || fragment.start == fragment.end)
+            })
+            .map(|(_fragment, kind)| *kind)
+    }
+
     /// Yields a set of ranges covering all spanned lines (the full line).
     pub fn find_covered_lines<'i>(&'i self, range: Range) -> Vec<Range> {
         // assumes the _mistake_ is within one line
@@ -287,10 +334,19 @@ impl CheckableChunk {
 /// as well as vector of checkable chunks.
 impl From<Clusters> for Vec<CheckableChunk> {
     fn from(clusters: Clusters) -> Vec<CheckableChunk> {
+        let contexts = clusters.contexts;
         clusters
             .set
             .into_iter()
-            .map(|literal_set| CheckableChunk::from_literalset(literal_set))
+            .map(|literal_set| {
+                let line = literal_set
+                    .literals()
+                    .first()
+                    .map(|literal| literal.span().start.line);
+                let mut chunk = CheckableChunk::from_literalset(literal_set);
+                chunk.context = line.and_then(|line| contexts.get(&line)).cloned();
+                chunk
+            })
             .collect::<Vec<_>>()
     }
 }
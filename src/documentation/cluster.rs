@@ -2,7 +2,7 @@
 
 use super::*;
 use crate::documentation::Range;
-use crate::Span;
+use crate::{Span, Vocabulary};
 use anyhow::{anyhow, Error, Result};
 use std::convert::TryFrom;
 
@@ -10,6 +10,10 @@ use std::convert::TryFrom;
 #[derive(Debug)]
 pub struct Clusters {
     pub(super) set: Vec<LiteralSet>,
+    /// Identifiers used in the file
+    pub(super) vocabulary: Vocabulary,
+    /// Item context by the line of the doc attribute
+    pub(super) contexts: IndexMap<usize, ItemContext>,
 }
 
 impl Clusters {
@@ -43,6 +47,7 @@ impl Clusters {
                     // if we find an identifier
                     // which is doc
                     if ident != "doc" {
+                        self.vocabulary.add_identifier(&ident.to_string());
                         continue;
                     }
 
@@ -100,10 +105,13 @@ impl TryFrom<&str> for Clusters {
     fn try_from(source: &str) -> Result<Self> {
         let mut chunk = Self {
             set: Vec::with_capacity(64),
+            vocabulary: Vocabulary::new(),
+            contexts: IndexMap::new(),
         };
         let stream = syn::parse_str::<proc_macro2::TokenStream>(source)
             .map_err(|e| anyhow!("Failed to parse content to stream").context(e))?;
         chunk.parse_token_tree(source, stream)?;
+        chunk.contexts = context::collect(source);
         Ok(chunk)
     }
 }
@@ -0,0 +1,387 @@
+//! Item context of doc comments
+//!
+//! The token based clustering does not know what a doc comment documents,
+//! so the source is parsed into a `syn::File` as well and every documented
+//! item is recorded by the lines of its doc attributes.
+
+use indexmap::IndexMap;
+use syn::visit::{self, Visit};
+
+/// The kind of item a chunk of documentation belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    /// A free function, a method or an associated function.
+    Function,
+    /// A module or the crate itself, documented by inner or outer attributes.
+    Module,
+    /// A struct, enum, union or type alias.
+    Type,
+    /// A trait or trait alias.
+    Trait,
+    /// A constant or static.
+    Constant,
+    /// A declarative macro.
+    Macro,
+    /// A field of a struct or a variant of an enum.
+    Member,
+    /// Anything else, i.e. an impl block.
+    Other,
+}
+
+/// What a chunk of documentation documents.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ItemContext {
+    /// The kind of item.
+    pub kind: ItemKind,
+    /// Name of the item, if it has one.
+    pub ident: Option<String>,
+    /// Part of the public interface, for associated items the
+    /// visibility of the item itself is considered.
+    pub is_public: bool,
+    /// Declared as `unsafe`.
+    pub is_unsafe: bool,
+    /// Returns a `Result`.
+    pub returns_result: bool,
+    /// Calls a panicking macro or `unwrap` / `expect` within its body.
+    pub can_panic: bool,
+    /// Names of the parameters, fields, variants or associated items.
+    pub names: Vec<String>,
+}
+
+impl ItemContext {
+    fn new(kind: ItemKind, ident: Option<&syn::Ident>, vis: &syn::Visibility) -> Self {
+        Self {
+            kind,
+            ident: ident.map(|ident| ident.to_string()),
+            is_public: is_public(vis),
+            is_unsafe: false,
+            returns_result: false,
+            can_panic: false,
+            names: Vec::new(),
+        }
+    }
+
+    fn with_fields(mut self, fields: &syn::Fields) -> Self {
+        self.names.extend(
+            fields
+                .iter()
+                .filter_map(|field| field.ident.as_ref())
+                .map(|ident| ident.to_string()),
+        );
+        self
+    }
+
+    fn function(sig: &syn::Signature, vis: &syn::Visibility, block: Option<&syn::Block>) -> Self {
+        let mut context = Self::new(ItemKind::Function, Some(&sig.ident), vis);
+        context.is_unsafe = sig.unsafety.is_some();
+        context.returns_result = returns_result(&sig.output);
+        context.can_panic = block.map(can_panic).unwrap_or(false);
+        context.names = generics(&sig.generics);
+        for input in sig.inputs.iter() {
+            match input {
+                syn::FnArg::Receiver(_) => context.names.push("self".to_owned()),
+                syn::FnArg::Typed(pat_type) => {
+                    if let syn::Pat::Ident(ref pat) = *pat_type.pat {
+                        context.names.push(pat.ident.to_string());
+                    }
+                }
+            }
+        }
+        context
+    }
+}
+
+fn is_public(vis: &syn::Visibility) -> bool {
+    match vis {
+        syn::Visibility::Public(_) => true,
+        _ => false,
+    }
+}
+
+fn generics(generics: &syn::Generics) -> Vec<String> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            syn::GenericParam::Type(param) => param.ident.to_string(),
+            syn::GenericParam::Lifetime(param) => param.lifetime.ident.to_string(),
+            syn::GenericParam::Const(param) => param.ident.to_string(),
+        })
+        .collect()
+}
+
+fn returns_result(output: &syn::ReturnType) -> bool {
+    if let syn::ReturnType::Type(_, ty) = output {
+        if let syn::Type::Path(ref path) = **ty {
+            return path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident == "Result")
+                .unwrap_or(false);
+        }
+    }
+    false
+}
+
+/// Looks for panicking macros and methods, not following any calls.
+#[derive(Default)]
+struct PanicFinder {
+    found: bool,
+}
+
+impl<'ast> Visit<'ast> for PanicFinder {
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        const PANICKING: &[&str] = &[
+            "panic",
+            "assert",
+            "assert_eq",
+            "assert_ne",
+            "unreachable",
+            "todo",
+            "unimplemented",
+        ];
+        if let Some(segment) = mac.path.segments.last() {
+            if PANICKING.iter().any(|name| segment.ident == name) {
+                self.found = true;
+            }
+        }
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        if call.method == "unwrap" || call.method == "expect" {
+            self.found = true;
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_item(&mut self, _item: &'ast syn::Item) {
+        // nested items are documented on their own
+    }
+}
+
+fn can_panic(block: &syn::Block) -> bool {
+    let mut finder = PanicFinder::default();
+    finder.visit_block(block);
+    finder.found
+}
+
+/// Collects the item context by the line of each doc attribute.
+#[derive(Default)]
+struct ContextCollector {
+    contexts: IndexMap<usize, ItemContext>,
+}
+
+impl ContextCollector {
+    fn record(&mut self, attrs: &[syn::Attribute], context: ItemContext) {
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("doc")) {
+            let line = attr.pound_token.spans[0].start().line;
+            self.contexts.insert(line, context.clone());
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ContextCollector {
+    fn visit_file(&mut self, file: &'ast syn::File) {
+        let context = ItemContext {
+            kind: ItemKind::Module,
+            ident: None,
+            is_public: true,
+            is_unsafe: false,
+            returns_result: false,
+            can_panic: false,
+            names: Vec::new(),
+        };
+        self.record(&file.attrs, context);
+        visit::visit_file(self, file);
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        let context = ItemContext::function(&item.sig, &item.vis, Some(&item.block));
+        self.record(&item.attrs, context);
+        visit::visit_item_fn(self, item);
+    }
+
+    fn visit_impl_item_method(&mut self, item: &'ast syn::ImplItemMethod) {
+        let context = ItemContext::function(&item.sig, &item.vis, Some(&item.block));
+        self.record(&item.attrs, context);
+        visit::visit_impl_item_method(self, item);
+    }
+
+    fn visit_trait_item_method(&mut self, item: &'ast syn::TraitItemMethod) {
+        let mut context = ItemContext::function(&item.sig, &syn::Visibility::Inherited, None);
+        // trait items share the visibility of the trait
+        context.is_public = true;
+        self.record(&item.attrs, context);
+        visit::visit_trait_item_method(self, item);
+    }
+
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        let context = ItemContext::new(ItemKind::Module, Some(&item.ident), &item.vis);
+        self.record(&item.attrs, context);
+        visit::visit_item_mod(self, item);
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        let context = ItemContext::new(ItemKind::Type, Some(&item.ident), &item.vis)
+            .with_fields(&item.fields);
+        self.record(&item.attrs, context);
+        visit::visit_item_struct(self, item);
+    }
+
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        let mut context = ItemContext::new(ItemKind::Type, Some(&item.ident), &item.vis);
+        context.names = item
+            .variants
+            .iter()
+            .map(|variant| variant.ident.to_string())
+            .collect();
+        self.record(&item.attrs, context);
+        visit::visit_item_enum(self, item);
+    }
+
+    fn visit_item_union(&mut self, item: &'ast syn::ItemUnion) {
+        let context = ItemContext::new(ItemKind::Type, Some(&item.ident), &item.vis)
+            .with_fields(&syn::Fields::Named(item.fields.clone()));
+        self.record(&item.attrs, context);
+        visit::visit_item_union(self, item);
+    }
+
+    fn visit_item_type(&mut self, item: &'ast syn::ItemType) {
+        let context = ItemContext::new(ItemKind::Type, Some(&item.ident), &item.vis);
+        self.record(&item.attrs, context);
+        visit::visit_item_type(self, item);
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        let mut context = ItemContext::new(ItemKind::Trait, Some(&item.ident), &item.vis);
+        context.names = item
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::TraitItem::Method(method) => Some(&method.sig.ident),
+                syn::TraitItem::Type(ty) => Some(&ty.ident),
+                syn::TraitItem::Const(constant) => Some(&constant.ident),
+                _ => None,
+            })
+            .map(|ident| ident.to_string())
+            .collect();
+        self.record(&item.attrs, context);
+        visit::visit_item_trait(self, item);
+    }
+
+    fn visit_item_const(&mut self, item: &'ast syn::ItemConst) {
+        let context = ItemContext::new(ItemKind::Constant, Some(&item.ident), &item.vis);
+        self.record(&item.attrs, context);
+        visit::visit_item_const(self, item);
+    }
+
+    fn visit_item_static(&mut self, item: &'ast syn::ItemStatic) {
+        let context = ItemContext::new(ItemKind::Constant, Some(&item.ident), &item.vis);
+        self.record(&item.attrs, context);
+        visit::visit_item_static(self, item);
+    }
+
+    fn visit_item_macro(&mut self, item: &'ast syn::ItemMacro) {
+        let context = ItemContext::new(
+            ItemKind::Macro,
+            item.ident.as_ref(),
+            &syn::Visibility::Inherited,
+        );
+        self.record(&item.attrs, context);
+        visit::visit_item_macro(self, item);
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        let context = ItemContext::new(ItemKind::Other, None, &syn::Visibility::Inherited);
+        self.record(&item.attrs, context);
+        visit::visit_item_impl(self, item);
+    }
+
+    fn visit_field(&mut self, field: &'ast syn::Field) {
+        let context = ItemContext::new(ItemKind::Member, field.ident.as_ref(), &field.vis);
+        self.record(&field.attrs, context);
+        visit::visit_field(self, field);
+    }
+
+    fn visit_variant(&mut self, variant: &'ast syn::Variant) {
+        let context = ItemContext::new(
+            ItemKind::Member,
+            Some(&variant.ident),
+            &syn::Visibility::Inherited,
+        )
+        .with_fields(&variant.fields);
+        self.record(&variant.attrs, context);
+        visit::visit_variant(self, variant);
+    }
+}
+
+/// Item context of all doc attributes of a source file, keyed by the
+/// line each doc attribute starts at.
+///
+/// Returns an empty map if the source is not a complete rust file.
+pub(super) fn collect(source: &str) -> IndexMap<usize, ItemContext> {
+    match syn::parse_file(source) {
+        Ok(file) => {
+            let mut collector = ContextCollector::default();
+            collector.visit_file(&file);
+            collector.contexts
+        }
+        Err(e) => {
+            log::debug!("No item context available, failed to parse file: {}", e);
+            IndexMap::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items() {
+        const SOURCE: &str = r#"//! Crate docs.
+
+/// A struct.
+pub struct Fluff {
+    /// A field.
+    field: u8,
+}
+
+impl Fluff {
+    /// Does things.
+    pub unsafe fn things(&self) -> std::io::Result<()> {
+        let x = Some(1).unwrap();
+        Ok(())
+    }
+
+    /// Does nothing.
+    fn nothing() {
+        /// Nested.
+        fn nested() { panic!() }
+    }
+}
+"#;
+        let contexts = collect(SOURCE);
+        assert_eq!(contexts.len(), 6);
+        assert_eq!(contexts[&1].kind, ItemKind::Module);
+        assert_eq!(contexts[&3].kind, ItemKind::Type);
+        assert_eq!(contexts[&5].kind, ItemKind::Member);
+
+        let things = &contexts[&10];
+        assert_eq!(things.kind, ItemKind::Function);
+        assert_eq!(things.ident.as_deref(), Some("things"));
+        assert!(things.is_public && things.is_unsafe && things.returns_result && things.can_panic);
+        assert_eq!(things.names, vec!["self".to_owned()]);
+        assert_eq!(contexts[&3].names, vec!["field".to_owned()]);
+
+        let nothing = &contexts[&16];
+        assert!(!nothing.is_public && !nothing.can_panic && !nothing.returns_result);
+        assert!(contexts[&18].can_panic);
+    }
+
+    #[test]
+    fn not_a_file() {
+        assert!(collect("/// Docs of nothing.").is_empty());
+    }
+}
@@ -6,6 +6,56 @@ use fancy_regex::Regex;
 use std::convert::TryFrom;
 use std::fmt;
 
+/// The syntax a doc comment literal is written in, which determines how its
+/// content must be escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LiteralKind {
+    /// A `///` or `//!` comment.
+    LineComment,
+    /// A `/** */` or `/*! */` comment.
+    BlockComment,
+    /// A string, i.e. `#[doc = "..."]`.
+    Str,
+    /// A raw string with the given number of `#`, i.e. `#[doc = r#"..."#]`.
+    RawStr(usize),
+}
+
+impl LiteralKind {
+    /// Escape `content`, so it can be written into a literal of this kind.
+    ///
+    /// Errors if the content can not be represented, i.e. a raw string
+    /// can not contain its own terminator and a line comment ends with the
+    /// line, so it can not contain a newline.
+    pub fn escape(&self, content: &str) -> Result<String> {
+        match *self {
+            Self::LineComment => {
+                if content.contains(|c| c == '\n' || c == '\r') {
+                    bail!("A line comment can not contain a newline: >{}<", content);
+                }
+                Ok(content.to_owned())
+            }
+            Self::BlockComment => {
+                if content.contains("*/") {
+                    bail!("A block comment can not contain `*/`: >{}<", content);
+                }
+                Ok(content.to_owned())
+            }
+            Self::Str => Ok(content.replace('\\', "\\\\").replace('"', "\\\"")),
+            Self::RawStr(hashes) => {
+                let terminator = format!("\"{}", "#".repeat(hashes));
+                if content.contains(&terminator) {
+                    bail!(
+                        "A raw string can not contain its terminator `{}`: >{}<",
+                        terminator,
+                        content
+                    );
+                }
+                Ok(content.to_owned())
+            }
+        }
+    }
+}
+
 /// A literal with meta info where the first and list whitespace may be found.
 #[derive(Clone)]
 pub struct TrimmedLiteral {
@@ -93,6 +143,16 @@ impl TryFrom<(&str, proc_macro2::Literal)> for TrimmedLiteral {
             // Since we can not distinguish between orignally escaped, we simply
             // use the content read from source.
 
+            (span, pre, post)
+        } else if rendered.starts_with("/**") || rendered.starts_with("/*!") {
+            let pre = 3; // `/**`
+            let post = 2; // `*/`
+
+            // may span multiple lines, the decoration of the lines
+            // is stripped when creating the chunk
+            span.start.column += pre;
+            span.end.column = span.end.column.saturating_sub(post);
+
             (span, pre, post)
         } else {
             // pre and post are for the rendered content
@@ -186,6 +246,26 @@ impl TrimmedLiteral {
         &self.rendered.as_str()
     }
 
+    /// A block comment, i.e. `/** */`, which may span multiple lines.
+    pub fn is_block_comment(&self) -> bool {
+        self.prefix().starts_with("/*")
+    }
+
+    /// The syntax the literal is written in.
+    pub fn kind(&self) -> LiteralKind {
+        let prefix = self.prefix();
+        if prefix.starts_with("//") {
+            LiteralKind::LineComment
+        } else if prefix.starts_with("/*") {
+            LiteralKind::BlockComment
+        } else if prefix.starts_with('r') {
+            // `r`, the hashes and the opening `"`
+            LiteralKind::RawStr(prefix.len().saturating_sub(2))
+        } else {
+            LiteralKind::Str
+        }
+    }
+
     /// Length in characters, excluding `pre` and `post`.
     pub fn len_in_chars(&self) -> usize {
         self.len_in_chars
@@ -704,4 +784,21 @@ fn unicode(&self) -> bool {
     fn raw_variant_7_unicode_symbols() {
         comment_variant_span_range_validation(7);
     }
+
+    #[test]
+    fn block_comment() {
+        const SOURCE: &str = "/** Block comment\n * doc. */\nstruct Block;\n";
+        let literals = annotated_literals(SOURCE);
+        assert_eq!(literals.len(), 1);
+        let literal = literals.first().expect("Must contain exactly one literal");
+        assert!(literal.is_block_comment());
+        assert_eq!(literal.as_str(), " Block comment\n * doc. ");
+        assert_eq!(
+            literal.span(),
+            Span {
+                start: LineColumn { line: 1, column: 3 },
+                end: LineColumn { line: 2, column: 7 },
+            }
+        );
+    }
 }
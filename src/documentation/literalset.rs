@@ -57,6 +57,7 @@ impl LiteralSet {
     pub fn into_chunk(self) -> crate::documentation::CheckableChunk {
         let n = self.len();
         let mut source_mapping = indexmap::IndexMap::with_capacity(n);
+        let mut kinds = indexmap::IndexMap::with_capacity(n);
         let mut content = String::with_capacity(n * 120);
         if n > 0 {
             // cursor operates on characters
@@ -67,6 +68,30 @@ impl LiteralSet {
             let mut it = self.literals.iter();
             let mut next = it.next();
             while let Some(literal) = next {
+                if literal.is_block_comment() {
+                    for (idx, (line, span)) in block_comment_lines(literal).into_iter().enumerate()
+                    {
+                        if idx > 0 {
+                            content.push('\n');
+                            cursor += 1;
+                        }
+                        start = cursor;
+                        cursor += line.chars().count();
+                        end = cursor;
+                        // empty lines have no valid span
+                        if let Some(span) = span {
+                            source_mapping.insert(Range { start, end }, span);
+                            kinds.insert(Range { start, end }, literal.kind());
+                        }
+                        content.push_str(&line);
+                    }
+                    next = it.next();
+                    if next.is_some() {
+                        content.push('\n');
+                        cursor += 1;
+                    }
+                    continue;
+                }
                 start = cursor;
                 cursor += literal.len_in_chars();
                 end = cursor;
@@ -78,7 +103,8 @@ impl LiteralSet {
                     assert_eq!(range.len(), span_len);
                 }
                 // keep zero length values too, to guarantee continuity
-                source_mapping.insert(range, span);
+                source_mapping.insert(range.clone(), span);
+                kinds.insert(range, literal.kind());
                 content.push_str(literal.as_str());
                 // the newline is _not_ covered by a span, after all it's inserted by us!
                 next = it.next();
@@ -89,10 +115,65 @@ impl LiteralSet {
                 }
             }
         }
-        CheckableChunk::from_string(content, source_mapping)
+        CheckableChunk::from_string(content, source_mapping).with_kinds(kinds)
     }
 }
 
+/// Lines of a block comment with their span, where the decoration of all
+/// but the first line is stripped, that is a leading `*` as in ` * doc`, or
+/// the common indentation otherwise.
+fn block_comment_lines(literal: &TrimmedLiteral) -> Vec<(String, Option<crate::Span>)> {
+    let lines = literal.as_str().split('\n').collect::<Vec<&str>>();
+    let indentation = |line: &str| line.chars().take_while(|c| c.is_whitespace()).count();
+    let non_blank = lines
+        .iter()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>();
+    let decorated = !non_blank.is_empty()
+        && non_blank
+            .iter()
+            .all(|line| line.trim_start().starts_with('*'));
+    let common = non_blank
+        .iter()
+        .map(|line| indentation(line))
+        .min()
+        .unwrap_or_default();
+
+    let origin = literal.span().start;
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            let (skip, column) = if idx == 0 {
+                (0, origin.column)
+            } else if line.trim().is_empty() {
+                (line.chars().count(), 0)
+            } else if decorated {
+                let skip = indentation(line) + 1;
+                (skip, skip)
+            } else {
+                (common, common)
+            };
+            let stripped = line.chars().skip(skip).collect::<String>();
+            let len = stripped.chars().count();
+            let span = if len > 0 {
+                let line = origin.line + idx;
+                Some(crate::Span {
+                    start: crate::LineColumn { line, column },
+                    end: crate::LineColumn {
+                        line,
+                        column: column + len - 1,
+                    },
+                })
+            } else {
+                None
+            };
+            (stripped, span)
+        })
+        .collect()
+}
+
 use std::fmt;
 
 impl<'s> fmt::Display for LiteralSet {
@@ -314,6 +395,32 @@ struct Vikings;
 
     use crate::util::load_span_from;
 
+    #[test]
+    fn block_comment_into_chunk() {
+        const BLOCK: &str = "/** Block comment,\n * decorated.\n *\n * Two. */\nstruct Block;\n";
+        let chunk = gen_literal_set(BLOCK).into_chunk();
+        assert_eq!(chunk.as_str(), " Block comment,\n decorated.\n\n Two. ");
+
+        for (range, span) in chunk.iter() {
+            assert_eq!(
+                crate::util::load_span_from(BLOCK.as_bytes(), span.clone())
+                    .expect("Span extraction must work"),
+                crate::util::sub_chars(chunk.as_str(), range.clone())
+            );
+        }
+        let spans = chunk.find_spans(17..26);
+        assert_eq!(
+            spans.values().next(),
+            Some(&crate::Span {
+                start: crate::LineColumn { line: 2, column: 3 },
+                end: crate::LineColumn {
+                    line: 2,
+                    column: 11
+                },
+            })
+        );
+    }
+
     #[test]
     fn literal_set_into_chunk() {
         let _ = env_logger::builder()
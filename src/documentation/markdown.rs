@@ -243,9 +243,11 @@ impl<'a> PlainOverlay<'a> {
 
     /// Since most checkers will operate on the plain data, an indirection to map cmark reduced / plain
     /// back to raw ranges, which are then mapped back to `Span`s.
-    /// The returned key `Ranges` are in the condensed domain.
+    ///
+    /// A range covering multiple fragments, i.e. wrapping across lines, yields
+    /// one entry per overlapped fragment. The returned key `Ranges` are in the
+    /// domain of the raw chunk.
     pub fn find_spans(&self, condensed_range: Range) -> IndexMap<Range, Span> {
-        let mut active = false;
         let Range { start, end } = condensed_range;
         self.mapping
             .iter()
@@ -259,51 +261,20 @@ impl<'a> PlainOverlay<'a> {
                 sub.len() > 0
             })
             .fold(IndexMap::<_, _>::new(), |mut acc, (sub, raw)| {
-                fn recombine(range: Range, offset: usize, len: usize) -> Range {
-                    Range {
-                        start: range.start + offset,
-                        end: range.start + offset + len,
-                    }
-                };
-                let _ = if sub.contains(&start) {
-                    // calculate the offset between our `condensed_range.start` and
-                    // the `sub` which is one entry in the mappings
-                    let offset = start - sub.start;
-                    if sub.contains(&(end - 1)) {
-                        // complete start to end
-                        active = false;
-                        let raw = recombine(raw.clone(), offset, end - start);
-                        Some((start..end, raw))
-                    } else {
-                        // only start, continue taking until end
-                        active = true;
-                        let raw = recombine(raw.clone(), offset, sub.end - start);
-                        Some((start..sub.end, raw))
-                    }
-                // TODO must be implemented properly
-                // } else if active {
-                //     let offset = sub.end - end;
-                //     if sub.contains(&(end - 1)) {
-                //         active = false;
-                //         Some((sub.start..end, offset))
-                //     } else {
-                //         Some((sub.clone(), offset))
-                //     }
+                // the part of the `sub` covered by `condensed_range`, which
+                // is the same part of the corresponding `raw` range
+                let covered = start.max(sub.start)..end.min(sub.end);
+                let offset = covered.start - sub.start;
+                let raw = (raw.start + offset)..(raw.start + offset + covered.len());
+                trace!("convert:  cmark-erased={:?} -> raw={:?}", covered, raw);
+
+                if raw.len() > 0 {
+                    let resolved = self.raw.find_spans(raw.clone());
+                    trace!("cmark-erased range to spans: {:?} -> {:?}", raw, resolved);
+                    acc.extend(resolved.into_iter());
                 } else {
-                    None
+                    warn!("linear range to spans: {:?} empty!", raw);
                 }
-                .and_then(|(sub, raw)| {
-                    trace!("convert:  cmark-erased={:?} -> raw={:?}", sub, raw);
-
-                    if raw.len() > 0 {
-                        let resolved = self.raw.find_spans(raw.clone());
-                        trace!("cmark-erased range to spans: {:?} -> {:?}", raw, resolved);
-                        acc.extend(resolved.into_iter());
-                    } else {
-                        warn!("linear range to spans: {:?} empty!", raw);
-                    }
-                    Some(())
-                });
                 acc
             })
     }
@@ -27,12 +27,14 @@ pub type Range = core::ops::Range<usize>;
 
 mod chunk;
 mod cluster;
+mod context;
 mod literal;
 pub(crate) mod literalset;
 mod markdown;
 
 pub use chunk::*;
 pub use cluster::*;
+pub use context::*;
 pub use literal::*;
 pub use literalset::*;
 pub use markdown::*;
@@ -41,6 +43,10 @@ pub use markdown::*;
 pub struct Documentation {
     /// Mapping of a path to documentation literals
     index: IndexMap<ContentOrigin, Vec<CheckableChunk>>,
+    /// Identifiers of all rust sources
+    vocabulary: Vocabulary,
+    /// Digests of the file contents as they were parsed
+    digests: IndexMap<PathBuf, u64>,
 }
 
 impl Documentation {
@@ -48,6 +54,8 @@ impl Documentation {
     pub fn new() -> Self {
         Self {
             index: IndexMap::with_capacity(64),
+            vocabulary: Vocabulary::new(),
+            digests: IndexMap::new(),
         }
     }
 
@@ -68,7 +76,14 @@ impl Documentation {
 
     /// Join `self` with another doc to form a new one.
     pub fn join(&mut self, other: Documentation) -> &mut Self {
-        other
+        let Documentation {
+            index,
+            vocabulary,
+            digests,
+        } = other;
+        self.vocabulary.join(vocabulary);
+        self.digests.extend(digests);
+        index
             .into_iter()
             .for_each(|(origin, chunks): (_, Vec<CheckableChunk>)| {
                 let _ = self.add_inner(origin, chunks);
@@ -87,8 +102,36 @@ impl Documentation {
         });
     }
 
-    /// Adds a set of `CheckableChunk`s to the documentation to be checked.
+    /// Identifiers collected from all rust sources.
+    pub fn vocabulary(&self) -> &Vocabulary {
+        &self.vocabulary
+    }
+
+    /// Digests of the contents of all files, as they were parsed.
+    pub fn digests(&self) -> &IndexMap<PathBuf, u64> {
+        &self.digests
+    }
+
+    /// Remember the digest of the full content of the file `origin` refers to.
+    fn add_digest(&mut self, origin: &ContentOrigin, content: &str) {
+        match origin {
+            ContentOrigin::RustSourceFile(path) | ContentOrigin::CommonMarkFile(path) => {
+                self.digests
+                    .insert(path.to_owned(), crate::util::digest(content.as_bytes()));
+            }
+            // the content is an excerpt of the file
+            ContentOrigin::RustDocTest(..) => {}
+            #[cfg(test)]
+            _ => {}
+        }
+    }
+
+    /// Adds a set of `CheckableChunk`s to the documentation to be checked,
+    /// origins without any chunks are not listed.
     fn add_inner(&mut self, origin: ContentOrigin, mut chunks: Vec<CheckableChunk>) {
+        if chunks.is_empty() {
+            return;
+        }
         self.index
             .entry(origin)
             .and_modify(|acc: &mut Vec<CheckableChunk>| {
@@ -100,7 +143,10 @@ impl Documentation {
 
     /// Adds a rust content str to the documentation.
     pub fn add_rust(&mut self, origin: ContentOrigin, content: &str) -> Result<()> {
-        let cluster = Clusters::try_from(content)?;
+        let mut cluster = Clusters::try_from(content)?;
+        self.add_digest(&origin, content);
+        self.vocabulary
+            .join(std::mem::take(&mut cluster.vocabulary));
 
         let chunks = Vec::<CheckableChunk>::from(cluster);
         self.add_inner(origin, chunks);
@@ -123,6 +169,7 @@ impl Documentation {
             .ok_or_else(|| anyhow!("Common mark / markdown file does not contain a single line"))?;
 
         let span = Span { start, end };
+        self.add_digest(&origin, content);
         let source_mapping = indexmap::indexmap! {
             0..content.chars().count() => span
         };
@@ -134,6 +181,16 @@ impl Documentation {
     }
 }
 
+/// Iterate over all content origins and their chunks.
+impl<'a> IntoIterator for &'a Documentation {
+    type Item = (&'a ContentOrigin, &'a Vec<CheckableChunk>);
+    type IntoIter = indexmap::map::Iter<'a, ContentOrigin, Vec<CheckableChunk>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.index.iter()
+    }
+}
+
 /// only a shortcut to avoid duplicate code
 impl From<(ContentOrigin, &str)> for Documentation {
     fn from((origin, content): (ContentOrigin, &str)) -> Self {
@@ -171,6 +228,29 @@ mod tests {
 
     use std::convert::From;
 
+    #[test]
+    fn vocabulary_from_identifiers() {
+        const TEST_SOURCE: &str = r#"/// A doc comment.
+        struct Vikings;
+
+        /// Another one.
+        fn raid(_longship: Vikings) {}
+        "#;
+
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::from((origin, TEST_SOURCE));
+        let mut replacements = vec!["Viking".to_owned()];
+        docs.vocabulary().rank("Vikigns", &mut replacements);
+        assert_eq!(
+            replacements,
+            vec!["Vikings".to_owned(), "Viking".to_owned()]
+        );
+
+        let mut replacements = Vec::new();
+        docs.vocabulary().rank("struc", &mut replacements);
+        assert!(replacements.is_empty());
+    }
+
     #[test]
     fn parse_and_construct() {
         let _ = env_logger::builder()
@@ -15,13 +15,19 @@ mod span;
 mod suggestion;
 mod traverse;
 mod util;
+#[cfg_attr(not(any(feature = "hunspell", feature = "affix")), allow(dead_code))]
+mod vocabulary;
 
 pub use self::action::*;
-pub use self::config::{Config, HunspellConfig, LanguageToolConfig};
+pub use self::config::{
+    Config, HunspellConfig, InclusiveConfig, LanguageToolConfig, ReadabilityConfig, ReflowConfig,
+    RustdocConfig, TerminologyConfig, TypographyConfig, VariantsConfig,
+};
 pub use self::documentation::*;
 pub use self::span::*;
 pub use self::suggestion::*;
 pub use self::util::*;
+pub use self::vocabulary::*;
 
 use docopt::Docopt;
 
@@ -41,10 +47,12 @@ const USAGE: &str = r#"
 Spellcheck all your doc comments
 
 Usage:
-    cargo-spellcheck [(-v...|-q)] check [--cfg=<cfg>] [--code=<code>] [--skip-readme] [--checkers=<checkers>] [[--recursive] <paths>... ]
-    cargo-spellcheck [(-v...|-q)] fix [--cfg=<cfg>] [--code=<code>] [--skip-readme] [--checkers=<checkers>] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] check [--cfg=<cfg>] [--code=<code>] [--skip-readme] [--checkers=<checkers>] [--no-suggestions] [--jobs=<jobs>] [--no-cache] [--format=<format>] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] fix [--cfg=<cfg>] [--code=<code>] [--skip-readme] [--checkers=<checkers>] [--jobs=<jobs>] [--no-cache] [--auto] [--from=<report>] [--keys-from=<file>] [--resume|--restart] [--backup] [--dry-run] [--diff-output=<file>] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] reflow [--cfg=<cfg>] [--code=<code>] [--fix] [--backup] [--dry-run] [--diff-output=<file>] [--skip-readme] [--jobs=<jobs>] [[--recursive] <paths>... ]
     cargo-spellcheck [(-v...|-q)] config (--user|--stdout|--cfg=<cfg>) [--force]
-    cargo-spellcheck [(-v...|-q)] [--cfg=<cfg>] [--fix] [--code=<code>] [--skip-readme] [--checkers=<checkers>] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] cache clean
+    cargo-spellcheck [(-v...|-q)] [--cfg=<cfg>] [--fix] [--auto] [--from=<report>] [--keys-from=<file>] [--resume|--restart] [--backup] [--dry-run] [--diff-output=<file>] [--code=<code>] [--skip-readme] [--checkers=<checkers>] [--no-suggestions] [--jobs=<jobs>] [--no-cache] [--format=<format>] [[--recursive] <paths>... ]
     cargo-spellcheck --help
     cargo-spellcheck --version
 
@@ -53,6 +61,16 @@ Options:
   --version                 Print the version and exit.
 
   --fix                     Interactively apply spelling and grammer fixes, synonym to `fix` sub-command.
+                            With `reflow`, rewrap all paragraphs without asking.
+  --auto                    Only apply fixes with a single replacement, of known typos, or the top spelling
+                            suggestion if it is a single edit away, without asking. All others are listed.
+  --from=<report>           Apply the replacements picked in a report of `--format=json`, if the files still match.
+  --keys-from=<file>        Replay the keys recorded in `<file>` instead of reading them from the terminal.
+  --resume                  Continue the last interrupted `fix` session, applying the fixes accepted so far.
+  --restart                 Discard the last interrupted `fix` session and start over.
+  --backup                  Keep a copy of every file modified by a fix, with an additional `.orig` extension.
+  --dry-run                 Print the fixes as unified diff instead of modifying any file.
+  --diff-output=<file>      Write the fixes as unified diff to `<file>` instead of modifying any file.
   -r --recursive            If a path is provided, if recursion into subdirectories is desired.
   --checkers=<checkers>     Calculate the intersection between
                             configured by config file and the ones provided on commandline.
@@ -65,6 +83,10 @@ Options:
   -q --quiet                Silences all printed messages. Overrules `-v`.
   -m --code=<code>          Overwrite the exit value for a successful run with content mistakes found. [default=0]
   --skip-readme             Do not attempt to process README.md files listed in Cargo.toml manifests.
+  --no-suggestions          Do not look up replacement suggestions for spelling mistakes, only report them.
+  -j --jobs=<jobs>          Number of worker threads to read and check files with, defaults to the number of CPUs.
+  --format=<format>         Report the findings as `human` readable text or as `json`. [default: human]
+  --no-cache                Check all chunks, ignoring and not updating the results cached in `target/spellcheck`.
 "#;
 
 /// A simple exit code representation.
@@ -98,6 +120,14 @@ impl ExitCode {
 struct Args {
     arg_paths: Vec<PathBuf>,
     flag_fix: bool,
+    flag_auto: bool,
+    flag_from: Option<PathBuf>,
+    flag_keys_from: Option<PathBuf>,
+    flag_resume: bool,
+    flag_restart: bool,
+    flag_backup: bool,
+    flag_dry_run: bool,
+    flag_diff_output: Option<PathBuf>,
     flag_recursive: bool,
     flag_verbose: usize,
     flag_quiet: bool,
@@ -108,11 +138,18 @@ struct Args {
     flag_force: bool,
     flag_user: bool,
     flag_skip_readme: bool,
+    flag_no_suggestions: bool,
+    flag_jobs: Option<usize>,
+    flag_no_cache: bool,
+    flag_format: String,
     flag_code: u8,
     flag_stdout: bool,
     cmd_fix: bool,
     cmd_check: bool,
     cmd_config: bool,
+    cmd_reflow: bool,
+    cmd_cache: bool,
+    cmd_clean: bool,
 }
 
 /// Handle incoming signals.
@@ -125,6 +162,9 @@ fn signal_handler() {
     for s in signals.forever() {
         match s {
             SIGTERM | SIGINT | SIGQUIT => {
+                if let Err(e) = action::interactive::fullscreen::restore_screen() {
+                    warn!("Failed to leave the full screen: {}", e);
+                }
                 if let Err(e) = action::interactive::ScopedRaw::restore_terminal() {
                     warn!("Failed to restore terminal: {}", e);
                 }
@@ -211,7 +251,9 @@ fn run() -> anyhow::Result<ExitCode> {
                 .split(',')
                 .map(|checker| checker.to_lowercase())
                 .collect::<Vec<_>>();
-            if !checkers.contains(&"hunspell".to_owned()) {
+            // the affix checker uses the hunspell configuration too
+            if !checkers.contains(&"hunspell".to_owned()) && !checkers.contains(&"affix".to_owned())
+            {
                 if !config.hunspell.take().is_some() {
                     warn!("Hunspell was never configured.")
                 }
@@ -221,6 +263,28 @@ fn run() -> anyhow::Result<ExitCode> {
                     warn!("Languagetool was never configured.")
                 }
             }
+            // opt-in checkers, not being configured is not worth a warning
+            if !checkers.contains(&"typography".to_owned()) {
+                config.typography = None;
+            }
+            if !checkers.contains(&"rustdoc".to_owned()) {
+                config.rustdoc = None;
+            }
+            if !checkers.contains(&"terminology".to_owned()) {
+                config.terminology = None;
+            }
+            if !checkers.contains(&"inclusive".to_owned()) {
+                config.inclusive = None;
+            }
+            if !checkers.contains(&"variants".to_owned()) {
+                config.variants = None;
+            }
+            if !checkers.contains(&"readability".to_owned()) {
+                config.readability = None;
+            }
+            if !checkers.contains(&"reflow".to_owned()) {
+                config.reflow = None;
+            }
         }
     };
 
@@ -256,6 +320,14 @@ fn run() -> anyhow::Result<ExitCode> {
         trace!("Not configuration sub command");
     }
 
+    // handle `cache clean` sub command
+    if args.cmd_cache && args.cmd_clean {
+        let dir = checker::Cache::dir()?;
+        info!("Removing cache {}", dir.display());
+        checker::Cache::clean(&dir)?;
+        return Ok(ExitCode::Success);
+    }
+
     let (explicit_cfg, config_path) = match args.flag_cfg.as_ref() {
         Some(path) => {
             let path = if path.is_absolute() {
@@ -341,31 +413,188 @@ fn run() -> anyhow::Result<ExitCode> {
 
     checkers(&mut config);
 
+    if args.flag_no_suggestions {
+        if let Some(hunspell) = config.hunspell.as_mut() {
+            hunspell.suggestions = Some(false);
+        }
+    }
+
+    // `reflow` falls back to the defaults without a `[reflow]` section
+    if args.cmd_reflow && config.reflow.is_none() {
+        config.reflow = Some(ReflowConfig::default());
+    }
+
     // extract operation mode
-    let action = if args.cmd_fix || args.flag_fix {
+    let action = if args.cmd_reflow && args.flag_fix {
+        Action::Apply
+    } else if (args.cmd_fix || args.flag_fix) && args.flag_auto {
+        Action::Auto
+    } else if args.cmd_fix || args.flag_fix {
         Action::Fix
     } else {
         // check
-        Action::Check
+        match args.flag_format.as_str() {
+            "human" => Action::Check,
+            "json" => Action::Report,
+            format => {
+                return Err(anyhow::anyhow!(
+                    "Unknown format `{}`, expected `human` or `json`",
+                    format
+                ))
+            }
+        }
+    };
+
+    // replacements picked in a report are applied instead of checking again
+    let report = match args.flag_from.as_ref() {
+        Some(path) if action == Action::Fix || action == Action::Auto => Some(Report::load(path)?),
+        _ => None,
     };
 
     trace!("Executing: {:?} with {:?}", action, &config);
 
-    let combined = traverse::extract(
-        args.arg_paths,
-        args.flag_recursive,
-        args.flag_skip_readme,
+    // files are read and checked by a pool of workers, `0` picks the number of CPUs
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(args.flag_jobs.unwrap_or_default())
+        .build()?;
+
+    let combined = if let Some(report) = report.as_ref() {
+        // only the files the report refers to
+        pool.install(|| traverse::extract(report.paths(), false, true, &config))?
+    } else {
+        let paths = args.arg_paths.clone();
+        pool.install(|| {
+            traverse::extract(paths, args.flag_recursive, args.flag_skip_readme, &config)
+        })?
+    };
+
+    let writeback = Writeback {
+        backup: args.flag_backup,
+        digests: combined.digests().clone(),
+        dry_run: args.flag_dry_run || args.flag_diff_output.is_some(),
+        diff_output: args.flag_diff_output.clone(),
+    };
+
+    if let Some(report) = report.as_ref() {
+        let finish = report.apply(&combined, &config, &writeback)?;
+        summary(finish, args.flag_quiet);
+        return Ok(exit_code(finish, args.flag_code, None));
+    }
+
+    // results of unchanged chunks are taken from the previous runs
+    let cache = if args.flag_no_cache || args.cmd_reflow {
+        None
+    } else {
+        Some(checker::Cache::load(&checker::Cache::dir()?))
+    };
+
+    let suggestion_set = pool.install(|| {
+        if args.cmd_reflow {
+            checker::reflow(&combined, &config)
+        } else {
+            checker::check(&combined, &config, cache.as_ref())
+        }
+    })?;
+
+    if let Some(cache) = cache.as_ref() {
+        if let Err(e) = cache.save() {
+            warn!("Failed to store the results in the cache: {}", e);
+        }
+    }
+
+    // inclusive language findings carry their own exit code, regardless of their severity
+    let inclusive_code = config
+        .inclusive
+        .as_ref()
+        .and_then(|inclusive| inclusive.code)
+        .filter(|_| {
+            suggestion_set.iter().any(|(_origin, suggestions)| {
+                suggestions
+                    .iter()
+                    .any(|suggestion| suggestion.detector == Detector::Inclusive)
+            })
+        });
+
+    // keys are replayed from a script, if one was recorded
+    let mut terminal: Box<dyn Terminal> = match args.flag_keys_from.as_ref() {
+        Some(path) => Box::new(KeyScript::load(path, std::io::stdout())?),
+        None => Box::new(Tty::default()),
+    };
+
+    // interactive decisions are kept until the fixes are written, a dry run
+    // leaves the stored session as it is
+    let mut session = if action == Action::Fix {
+        let dir = checker::Cache::dir()?;
+        if args.flag_resume {
+            let session = Session::resume(&dir, combined.digests())?;
+            if writeback.dry_run {
+                session.in_memory()
+            } else {
+                session
+            }
+        } else if writeback.dry_run {
+            Session::default()
+        } else if args.flag_restart {
+            Session::restart(&dir, combined.digests())?
+        } else {
+            Session::start(&dir, combined.digests())?
+        }
+    } else {
+        Session::default()
+    };
+
+    let finish = action.run(
+        suggestion_set,
         &config,
+        &writeback,
+        terminal.as_mut(),
+        &mut session,
     )?;
+    if let Finish::Abort = finish {
+        if !session.is_empty() && !writeback.dry_run && !args.flag_quiet {
+            eprintln!(
+                "Kept {} decisions, continue with `cargo spellcheck fix --resume`",
+                session.len()
+            );
+        }
+    }
+    summary(finish, args.flag_quiet);
+
+    // the readability summary follows the reported suggestions
+    if action == Action::Check {
+        if let Some(readability) = config.readability.as_ref() {
+            if readability.summary() {
+                for report in checker::readability(&combined) {
+                    println!("{}", report);
+                }
+            }
+        }
+    }
 
-    let suggestion_set = checker::check(&combined, &config)?;
+    Ok(exit_code(finish, args.flag_code, inclusive_code))
+}
 
-    let finish = action.run(suggestion_set, &config)?;
+/// Summarize a non-interactive fix, on stderr since stdout might carry a diff.
+fn summary(finish: Finish, quiet: bool) {
+    if let Finish::Fixed { applied, remaining } = finish {
+        if !quiet {
+            eprintln!("Applied {} fixes, {} findings remain", applied, remaining);
+        }
+    }
+}
 
+/// Determine the exit code, the higher one wins if both mistakes were found and
+/// an exit code for inclusive language findings applies.
+fn exit_code(finish: Finish, code: u8, inclusive_code: Option<u8>) -> ExitCode {
     match finish {
-        Finish::MistakeCount(0) => Ok(ExitCode::Success),
-        Finish::MistakeCount(_n) => Ok(ExitCode::Custom(args.flag_code)),
-        Finish::Abort => Ok(ExitCode::Signal),
+        Finish::Abort => ExitCode::Signal,
+        Finish::MistakeCount(n) | Finish::Fixed { remaining: n, .. } => {
+            let code = if n > 0 { code } else { 0 };
+            match code.max(inclusive_code.unwrap_or_default()) {
+                0 => ExitCode::Success,
+                code => ExitCode::Custom(code),
+            }
+        }
     }
 }
 
@@ -397,9 +626,87 @@ mod tests {
             "cargo-spellcheck -q fix Cargo.toml",
             "cargo spellcheck -v fix Cargo.toml",
             "cargo spellcheck -m 11 check",
+            "cargo spellcheck reflow",
+            "cargo spellcheck check --no-suggestions",
+            "cargo spellcheck check -j 4",
+            "cargo spellcheck fix --jobs=1",
+            "cargo spellcheck check --no-cache",
+            "cargo spellcheck --no-cache",
+            "cargo spellcheck cache clean",
+            "cargo spellcheck fix --backup",
+            "cargo spellcheck reflow --fix --backup",
+            "cargo spellcheck fix --dry-run",
+            "cargo spellcheck fix --auto",
+            "cargo spellcheck fix --auto --diff-output=fixes.patch",
+            "cargo spellcheck --fix --auto",
+            "cargo spellcheck check --format=json",
+            "cargo spellcheck --format=json",
+            "cargo spellcheck fix --from=report.json",
+            "cargo spellcheck fix --from=report.json --dry-run",
+            "cargo spellcheck fix --keys-from=keys.txt",
+            "cargo spellcheck fix --keys-from=keys.txt --dry-run -r demo",
+            "cargo spellcheck fix --resume",
+            "cargo spellcheck --fix --resume",
+            "cargo spellcheck fix --restart",
+            "cargo spellcheck --fix --restart --dry-run",
+            "cargo spellcheck fix --diff-output=fixes.patch",
+            "cargo spellcheck reflow --fix --dry-run",
+            "cargo spellcheck --fix --dry-run",
+            "cargo spellcheck reflow --fix src/lib.rs",
         ];
         for command in commands {
-            assert!(parse_args(commandline_to_iter(command)).is_ok());
+            assert!(
+                parse_args(commandline_to_iter(command)).is_ok(),
+                "{}",
+                command
+            );
         }
+        assert!(parse_args(commandline_to_iter(
+            "cargo spellcheck fix --resume --restart"
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(
+            exit_code(Finish::MistakeCount(0), 1, None),
+            ExitCode::Success
+        );
+        assert_eq!(
+            exit_code(Finish::MistakeCount(2), 1, None),
+            ExitCode::Custom(1)
+        );
+        assert_eq!(
+            exit_code(Finish::MistakeCount(0), 1, Some(3)),
+            ExitCode::Custom(3)
+        );
+        assert_eq!(
+            exit_code(Finish::MistakeCount(2), 7, Some(3)),
+            ExitCode::Custom(7)
+        );
+        assert_eq!(exit_code(Finish::Abort, 1, Some(3)), ExitCode::Signal);
+        assert_eq!(
+            exit_code(
+                Finish::Fixed {
+                    applied: 3,
+                    remaining: 0
+                },
+                1,
+                None
+            ),
+            ExitCode::Success
+        );
+        assert_eq!(
+            exit_code(
+                Finish::Fixed {
+                    applied: 0,
+                    remaining: 1
+                },
+                1,
+                None
+            ),
+            ExitCode::Custom(1)
+        );
     }
 }
@@ -64,7 +64,7 @@ impl Span {
 
     /// Check if `self` span covers provided `line` number, which is 1-indexed.
     pub fn covers_line(&self, line: usize) -> bool {
-        self.end.line <= line && line >= self.start.line
+        self.start.line <= line && line <= self.end.line
     }
 
     /// If this one resembles a single line, returns the a `Some(len)` value.
@@ -77,6 +77,54 @@ impl Span {
         }
     }
 
+    /// Re-base a span within an excerpt onto the full content, the excerpt
+    /// starts at `offset` of the full content.
+    ///
+    /// Only the first line of the excerpt is shifted by the column of `offset`,
+    /// all following lines of the excerpt are full lines.
+    pub fn rebase(&self, offset: LineColumn) -> Self {
+        let rebase = |position: LineColumn| LineColumn {
+            line: position.line + offset.line - 1,
+            column: if position.line == 1 {
+                position.column + offset.column
+            } else {
+                position.column
+            },
+        };
+        Self {
+            start: rebase(self.start),
+            end: rebase(self.end),
+        }
+    }
+
+    /// The inverse of [`rebase`](Self::rebase), a span within the full content
+    /// becomes relative to the excerpt starting at `offset`.
+    pub fn relative_to_excerpt(&self, offset: LineColumn) -> Result<Self> {
+        let relative = |position: LineColumn| -> Result<LineColumn> {
+            if (position.line, position.column) < (offset.line, offset.column) {
+                bail!(
+                    "{}:{} is located before the excerpt starting at {}:{}",
+                    position.line,
+                    position.column,
+                    offset.line,
+                    offset.column
+                );
+            }
+            Ok(LineColumn {
+                line: position.line + 1 - offset.line,
+                column: if position.line == offset.line {
+                    position.column - offset.column
+                } else {
+                    position.column
+                },
+            })
+        };
+        Ok(Self {
+            start: relative(self.start)?,
+            end: relative(self.end)?,
+        })
+    }
+
     /// Convert a given span `self` into a `Range`
     ///
     /// The `Chunk` has a associated `Span` (or a set of `Range` -> `Span` mappings)
@@ -418,6 +466,51 @@ AlphaOmega
         }
     }
 
+    #[test]
+    fn rebase_excerpt() {
+        let offset = LineColumn { line: 7, column: 4 };
+        let first_line = Span {
+            start: LineColumn { line: 1, column: 2 },
+            end: LineColumn { line: 1, column: 5 },
+        };
+        let multi_line = Span {
+            start: LineColumn { line: 1, column: 6 },
+            end: LineColumn { line: 3, column: 1 },
+        };
+        assert_eq!(
+            first_line.rebase(offset),
+            Span {
+                start: LineColumn { line: 7, column: 6 },
+                end: LineColumn { line: 7, column: 9 },
+            }
+        );
+        assert_eq!(
+            multi_line.rebase(offset),
+            Span {
+                start: LineColumn {
+                    line: 7,
+                    column: 10
+                },
+                end: LineColumn { line: 9, column: 1 },
+            }
+        );
+        for span in &[first_line, multi_line] {
+            assert_eq!(
+                span.rebase(offset).relative_to_excerpt(offset).unwrap(),
+                *span
+            );
+        }
+        // the identity for an excerpt which is the full content
+        let start = LineColumn { line: 1, column: 0 };
+        assert_eq!(multi_line.rebase(start), multi_line);
+
+        let before = Span {
+            start: LineColumn { line: 7, column: 3 },
+            end: LineColumn { line: 7, column: 5 },
+        };
+        assert!(before.relative_to_excerpt(offset).is_err());
+    }
+
     #[test]
     fn extraction_fluff() {
         const CHUNK_S: &'static str = r#" one
@@ -15,24 +15,77 @@ use crate::documentation::{CheckableChunk, ContentOrigin};
 
 use std::cmp;
 use std::convert::TryFrom;
+use std::fmt;
 
 use enumflags2::BitFlags;
+use serde::{Deserialize, Serialize};
 
 use crate::{Range, Span};
 
 /// Bitflag of available checkers by compilation / configuration.
-#[derive(Debug, Clone, Copy, BitFlags, Eq, PartialEq, Hash)]
-#[repr(u8)]
+#[derive(Debug, Clone, Copy, BitFlags, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[repr(u16)]
 pub enum Detector {
     /// Hunspell lib based detector.
+    #[serde(alias = "Hunspell")]
     Hunspell = 0b0001,
     /// Language tool server based detection.
+    #[serde(alias = "LanguageTool")]
     LanguageTool = 0b0010,
+    /// Rust based detector, using the hunspell dictionaries and configuration.
+    #[serde(alias = "Affix")]
+    Affix = 0b0100,
+    /// Built-in detector of repeated words and typographic slips.
+    #[serde(alias = "Typography")]
+    Typography = 0b1_0000,
+    /// Built-in lint of the rustdoc conventions.
+    #[serde(alias = "Rustdoc", alias = "RustDoc")]
+    Rustdoc = 0b10_0000,
+    /// Built-in detector of disallowed terms, as configured.
+    #[serde(alias = "Terminology")]
+    Terminology = 0b100_0000,
+    /// Built-in detector of non-inclusive terms.
+    #[serde(alias = "Inclusive")]
+    Inclusive = 0b1000_0000,
+    /// Built-in detector of mixed American and British spelling.
+    #[serde(alias = "Variants")]
+    Variants = 0b1_0000_0000,
+    /// Built-in readability metrics with optional thresholds.
+    #[serde(alias = "Readability")]
+    Readability = 0b10_0000_0000,
+    /// Built-in rewrapping of doc comment paragraphs.
+    #[serde(alias = "Reflow")]
+    Reflow = 0b100_0000_0000,
     /// Detection of nothing, a test helper.
     #[cfg(test)]
     Dummy = 0b1000,
 }
 
+/// How severe a reported suggestion is.
+///
+/// Only errors count as mistakes, warnings are reported but do not
+/// affect the exit code.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Reported, but not counted as mistake.
+    #[serde(alias = "Warning")]
+    Warning,
+    /// Reported and counted as mistake.
+    #[serde(alias = "Error")]
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
 /// Terminal size in characters.
 ///
 /// Returns `80usize` for tests and in case the terminal size
@@ -63,13 +116,19 @@ pub fn get_terminal_size() -> usize {
 //     }
 // }
 
-use std::fmt;
-
 impl fmt::Display for Detector {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         formatter.write_str(match self {
             Self::LanguageTool => "LanguageTool",
             Self::Hunspell => "Hunspell",
+            Self::Affix => "Affix",
+            Self::Typography => "Typography",
+            Self::Rustdoc => "Rustdoc",
+            Self::Terminology => "Terminology",
+            Self::Inclusive => "Inclusive",
+            Self::Variants => "Variants",
+            Self::Readability => "Readability",
+            Self::Reflow => "Reflow",
             #[cfg(test)]
             Self::Dummy => "Dummy",
         })
@@ -297,21 +356,40 @@ pub struct Suggestion<'s> {
     pub description: Option<String>,
 }
 
-impl<'s> fmt::Display for Suggestion<'s> {
-    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl<'s> Suggestion<'s> {
+    /// Display the suggestion with the given severity, `Display` reports
+    /// every suggestion as an error.
+    pub fn with_severity<'a>(&'a self, severity: Severity) -> WithSeverity<'a, 's> {
+        WithSeverity {
+            suggestion: self,
+            severity,
+        }
+    }
+
+    fn fmt_with_severity(
+        &self,
+        severity: Severity,
+        formatter: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
         use console::Style;
+        use fmt::Display;
 
         let highlight = Style::new().bold().white();
-        let error = Style::new().bold().red();
+        let error = match severity {
+            Severity::Error => Style::new().bold().red(),
+            Severity::Warning => Style::new().bold().yellow(),
+        };
         let arrow_marker = Style::new().blue();
         let context_marker = Style::new().bold().blue();
         let fix = Style::new().green();
         let help = Style::new().yellow().bold();
 
-        let line_number_digit_count = self.span.start.line.to_string().len();
+        // the line numbers of the file, a doc-test is an excerpt of it
+        let line = self.origin.absolute_span(self.span).start.line;
+        let line_number_digit_count = line.to_string().len();
         let indent = 3 + line_number_digit_count;
 
-        error.apply_to("error").fmt(formatter)?;
+        error.apply_to(severity).fmt(formatter)?;
         highlight
             .apply_to(format!(": spellcheck({})", &self.detector))
             .fmt(formatter)?;
@@ -321,35 +399,22 @@ impl<'s> fmt::Display for Suggestion<'s> {
             .apply_to(format!("{:>width$}", "-->", width = indent + 1))
             .fmt(formatter)?;
 
-        let x = self.span.start.line;
-        let (path, line) = match self.origin {
-            ContentOrigin::RustDocTest(ref path, ref span) => {
-                (path.display().to_string(), x + span.start.line)
-            }
-            ref origin => (origin.as_path().display().to_string(), x),
-        };
-        writeln!(formatter, " {path}:{line}", path = path, line = line)?;
+        writeln!(
+            formatter,
+            " {path}:{line}",
+            path = self.origin.as_path().display(),
+            line = line
+        )?;
         context_marker
             .apply_to(format!("{:>width$}", "|", width = indent))
             .fmt(formatter)?;
         formatter.write_str("\n")?;
         context_marker
-            .apply_to(format!(
-                "{:>width$} |",
-                self.span.start.line,
-                width = indent - 2,
-            ))
+            .apply_to(format!("{:>width$} |", line, width = indent - 2,))
             .fmt(formatter)?;
 
         // underline the relevant part with ^^^^^
 
-        // TODO this needs some more thought once multiline comments pop up
-        let marker_size = self.span.one_line_len().unwrap_or_else(|| {
-            self.chunk
-                .len_in_chars()
-                .saturating_sub(self.span.start.column)
-        });
-
         // assumes the _mistake_ is within one line
         // if not we chop it down to the first line
         let mistake_lines = self.chunk.find_covered_lines(self.range.clone());
@@ -367,6 +432,11 @@ impl<'s> fmt::Display for Suggestion<'s> {
             start: start_of_line_offset,
             end: cmp::min(start_of_line_offset + self.range.len(), line_range.len()),
         };
+        // a multiline mistake is underlined up to the end of the first line
+        let marker_size = self
+            .span
+            .one_line_len()
+            .unwrap_or_else(|| intra_line_mistake_range.len());
         let relevant_line = self
             .chunk
             .as_str()
@@ -475,6 +545,24 @@ impl<'s> fmt::Display for Suggestion<'s> {
     }
 }
 
+impl<'s> fmt::Display for Suggestion<'s> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with_severity(Severity::Error, formatter)
+    }
+}
+
+/// Displays a suggestion with a particular severity.
+pub struct WithSeverity<'a, 's> {
+    suggestion: &'a Suggestion<'s>,
+    severity: Severity,
+}
+
+impl<'a, 's> fmt::Display for WithSeverity<'a, 's> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.suggestion.fmt_with_severity(self.severity, formatter)
+    }
+}
+
 impl<'s> fmt::Debug for Suggestion<'s> {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         match crate::documentation::ChunkDisplay::try_from((self.chunk, self.span)) {
@@ -513,6 +601,14 @@ impl<'s> SuggestionSet<'s> {
         self.per_file.iter()
     }
 
+    /// Iterate mutably over all suggestions tupled with the content origin of the file the
+    /// suggestion relates to.
+    pub fn iter_mut<'a>(
+        &'a mut self,
+    ) -> impl Iterator<Item = (&'a ContentOrigin, &'a mut Vec<Suggestion<'s>>)> {
+        self.per_file.iter_mut()
+    }
+
     /// Adds a new suggestion to the set.
     pub fn add(&mut self, origin: ContentOrigin, suggestion: Suggestion<'s>) {
         self.per_file
@@ -688,7 +784,11 @@ mod tests {
    |
    |   Possible spelling mistake found.
 "#;
-        assert_display_eq(suggestion, EXPECTED);
+        assert_display_eq(&suggestion, EXPECTED);
+        assert_display_eq(
+            suggestion.with_severity(Severity::Warning),
+            EXPECTED.replacen("error", "warning", 1),
+        );
     }
 
     #[test]
@@ -1,7 +1,4 @@
 use super::*;
-use crate::Documentation;
-
-use std::fs;
 
 use log::{trace, warn};
 
@@ -90,7 +87,6 @@ impl TraverseModulesIter {
     }
 
     /// Create a new path with (almost) infinite depth bounds
-    #[allow(unused)]
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
         Self::with_depth_limit(path, usize::MAX)
     }
@@ -126,28 +122,6 @@ impl Iterator for TraverseModulesIter {
     }
 }
 
-/// traverse path with a depth limit, if the path is a directory all its children will be collected
-/// instead
-pub(crate) fn traverse(path: &Path) -> Result<impl Iterator<Item = Documentation>> {
-    traverse_with_depth_limit(path, usize::MAX)
-}
-
-/// traverse path with a depth limit, if the path is a directory all its children will be collected
-/// as depth 0 instead
-pub(crate) fn traverse_with_depth_limit(
-    path: &Path,
-    max_depth: usize,
-) -> Result<impl Iterator<Item = Documentation>> {
-    let it = TraverseModulesIter::with_depth_limit(path, max_depth)?
-        .filter_map(|path: PathBuf| -> Option<Documentation> {
-            fs::read_to_string(&path).ok().map(|content| {
-                Documentation::from((ContentOrigin::RustSourceFile(path), content.as_str()))
-            })
-        })
-        .filter(|documentation| !documentation.is_empty());
-    Ok(it)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -7,6 +7,7 @@ use crate::Documentation;
 
 use anyhow::{anyhow, bail, Error, Result};
 use log::{debug, trace, warn};
+use rayon::prelude::*;
 
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -389,49 +390,76 @@ pub(crate) fn extract(
         })?;
 
     // stage 4 - expand from the passed source files, if recursive, recurse down the module train
-    let combined: Documentation = files_to_check
-        .into_iter()
-        .try_fold::<Documentation, _, Result<Documentation>>(
-            Documentation::new(),
-            |mut docs, item| {
-                match item {
-                    CheckEntity::Source(path, recurse) => {
-                        if recurse {
-                            let iter = traverse(path.as_path())?;
-                            docs.extend(iter);
-                        } else {
-                            let content: String = fs::read_to_string(&path).map_err(|e| {
-                                anyhow!("Failed to read {}", path.display()).context(e)
-                            })?;
-                            docs.add_rust(
-                                ContentOrigin::RustSourceFile(path.to_owned()),
-                                content.as_str(),
-                            )
-                            .unwrap_or_else(|_e| {
-                                log::error!("BUG: Failed to create cluster for {}", path.display())
-                            });
-                        }
-                    }
-                    CheckEntity::Markdown(path) => {
-                        let content = std::fs::read_to_string(&path).map_err(|e| {
-                            anyhow!("Common mark / markdown file does not exist").context(e)
-                        })?;
-                        if content.len() < 1 {
-                            bail!("Common mark / markdown file is empty")
-                        }
-                        docs.add_commonmark(
-                            ContentOrigin::CommonMarkFile(path.to_owned()),
+    #[derive(Debug, Clone)]
+    enum Source {
+        /// A file passed explicitly, which must be readable.
+        Rust(PathBuf),
+        /// A module found while recursing.
+        Module(PathBuf),
+        Markdown(PathBuf),
+    }
+
+    let mut sources = Vec::with_capacity(files_to_check.len());
+    for item in files_to_check {
+        match item {
+            CheckEntity::Source(path, true) => {
+                sources.extend(TraverseModulesIter::new(path.as_path())?.map(Source::Module))
+            }
+            CheckEntity::Source(path, false) => sources.push(Source::Rust(path)),
+            CheckEntity::Markdown(path) => sources.push(Source::Markdown(path)),
+            other => {
+                warn!("Did not impl handling of {:?} type files", other);
+                // TODO generate Documentation structs from non-file sources
+            }
+        }
+    }
+
+    // stage 5 - read and parse all files in parallel, their order is kept
+    let docs = sources
+        .into_par_iter()
+        .map(|source| -> Result<Documentation> {
+            let mut docs = Documentation::new();
+            match source {
+                Source::Module(path) => {
+                    // there is nothing we can do about modules which do not exist
+                    if let Ok(content) = fs::read_to_string(&path) {
+                        docs = Documentation::from((
+                            ContentOrigin::RustSourceFile(path),
                             content.as_str(),
-                        )?;
+                        ));
                     }
-                    other => {
-                        warn!("Did not impl handling of {:?} type files", other);
-                        // TODO generate Documentation structs from non-file sources
+                }
+                Source::Rust(path) => {
+                    let content: String = fs::read_to_string(&path)
+                        .map_err(|e| anyhow!("Failed to read {}", path.display()).context(e))?;
+                    docs.add_rust(
+                        ContentOrigin::RustSourceFile(path.to_owned()),
+                        content.as_str(),
+                    )
+                    .unwrap_or_else(|_e| {
+                        log::error!("BUG: Failed to create cluster for {}", path.display())
+                    });
+                }
+                Source::Markdown(path) => {
+                    let content = std::fs::read_to_string(&path).map_err(|e| {
+                        anyhow!("Common mark / markdown file does not exist").context(e)
+                    })?;
+                    if content.len() < 1 {
+                        bail!("Common mark / markdown file is empty")
                     }
+                    docs.add_commonmark(
+                        ContentOrigin::CommonMarkFile(path.to_owned()),
+                        content.as_str(),
+                    )?;
                 }
-                Ok(docs)
-            },
-        )?;
+            }
+            // even without any doc comments the identifiers and the digest are kept
+            Ok(docs)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut combined = Documentation::new();
+    combined.extend(docs);
 
     Ok(combined)
 }
@@ -633,4 +661,43 @@ mod tests {
         "src/nested/justtwo.rs",
         "src/nested/mod.rs"
     ]);
+
+    #[test]
+    fn extraction_order_is_independent_of_jobs() {
+        let extract_with = |jobs: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build()
+                .expect("Must be able to build thread pool");
+            pool.install(|| extract(vec![demo_dir()], true, false, &Config::default()))
+                .expect("Must be able to extract demo dir")
+                .iter()
+                .map(|(origin, chunks)| (origin.clone(), chunks.len()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(extract_with(1), extract_with(4));
+    }
+
+    #[test]
+    fn undocumented_modules_contribute_vocabulary() {
+        let dir =
+            std::env::temp_dir().join(format!("spellcheck-vocabulary-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("lib.rs"), "/// Uses frobnicate.\nmod plain;\n").unwrap();
+        fs::write(dir.join("plain.rs"), "pub fn frobnicate() {}\n").unwrap();
+
+        let docs = extract(vec![dir.join("lib.rs")], true, false, &Config::default())
+            .expect("Must be able to extract");
+        assert_eq!(
+            docs.iter()
+                .map(|(origin, _chunks)| origin.as_path().to_owned())
+                .collect::<Vec<_>>(),
+            vec![dir.join("lib.rs")]
+        );
+        assert!(docs.vocabulary().contains("frobnicate"));
+        assert!(docs.digests().contains_key(&dir.join("plain.rs")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
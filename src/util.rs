@@ -86,6 +86,14 @@ pub fn sub_chars(s: &str, range: Range) -> String {
         .collect::<String>()
 }
 
+/// Digest of some content, to detect modifications within a single run.
+pub fn digest(content: &[u8]) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
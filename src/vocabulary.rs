@@ -0,0 +1,279 @@
+//! Vocabulary of a crate
+//!
+//! Collects the identifiers used in the crate's sources and the words of the
+//! extra dictionaries, and uses them to rank replacement suggestions, so terms
+//! of the crate itself are preferred over similar generic words.
+
+use crate::util::sub_chars;
+use crate::{Detector, SuggestionSet};
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use log::trace;
+use std::path::Path;
+
+/// Upper bound of the edit distance of a term to be considered a replacement.
+const MAX_DISTANCE: usize = 3;
+
+/// Upper bound of terms added to the replacements of a single suggestion.
+const MAX_TERMS: usize = 5;
+
+/// Keywords are not part of the vocabulary, even though they are identifiers.
+pub(crate) const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "for", "impl", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while",
+];
+
+/// Terms of a crate with their number of occurrences.
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    counts: IndexMap<String, usize>,
+}
+
+impl Vocabulary {
+    /// Create a new and empty vocabulary.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an identifier as found in the sources, ignoring keywords and
+    /// identifiers too short to be a meaningful replacement.
+    pub fn add_identifier(&mut self, ident: &str) {
+        let ident = ident.trim_start_matches("r#");
+        if ident.chars().count() < 3 || KEYWORDS.contains(&ident) {
+            return;
+        }
+        self.add(ident);
+    }
+
+    /// Add a single occurrence of a term.
+    pub fn add(&mut self, term: &str) {
+        *self.counts.entry(term.to_owned()).or_insert(0) += 1;
+    }
+
+    /// Join `self` with another vocabulary, summing up the occurrences.
+    pub fn join(&mut self, other: Vocabulary) {
+        for (term, count) in other.counts {
+            *self.counts.entry(term).or_insert(0) += count;
+        }
+    }
+
+    /// Add all words of a hunspell `.dic` file, dropping the affix flags.
+    pub fn add_dictionary(&mut self, path: &Path) -> Result<()> {
+        let content = std::fs::read(path)
+            .map_err(|e| anyhow!("Failed to read dictionary {}: {}", path.display(), e))?;
+        let content = String::from_utf8_lossy(&content);
+        let mut lines = content.lines().peekable();
+        // the first line is the approximate number of words
+        if let Some(line) = lines.peek() {
+            if line.trim().parse::<usize>().is_ok() {
+                let _ = lines.next();
+            }
+        }
+        for line in lines {
+            let term = line.split('/').next().unwrap_or_default().trim();
+            if !term.is_empty() {
+                self.add(term);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if there are any terms at all.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Check if the term is part of the vocabulary.
+    pub fn contains(&self, term: &str) -> bool {
+        self.counts.contains_key(term)
+    }
+
+    /// Terms close to the word by edit distance, ordered by distance and
+    /// number of occurrences, the word itself is excluded.
+    fn close_terms(&self, word: &str) -> Vec<&str> {
+        let lowercase = word.to_lowercase().chars().collect::<Vec<char>>();
+        let limit = (lowercase.len() / 4).max(1).min(MAX_DISTANCE);
+
+        let mut terms = self
+            .counts
+            .iter()
+            .filter(|(term, _count)| term.as_str() != word)
+            .filter_map(|(term, count)| {
+                let other = term.to_lowercase().chars().collect::<Vec<char>>();
+                let len_diff = if other.len() > lowercase.len() {
+                    other.len() - lowercase.len()
+                } else {
+                    lowercase.len() - other.len()
+                };
+                if len_diff > limit {
+                    return None;
+                }
+                let distance = distance(&lowercase, &other);
+                if distance <= limit {
+                    Some((distance, *count, term))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        terms.sort_by(
+            |(a_distance, a_count, a_term), (b_distance, b_count, b_term)| {
+                a_distance
+                    .cmp(b_distance)
+                    .then_with(|| b_count.cmp(a_count))
+                    .then_with(|| a_term.cmp(b_term))
+            },
+        );
+        trace!("Vocabulary terms close to {}: {:?}", word, &terms);
+        terms
+            .into_iter()
+            .map(|(_distance, _count, term)| term.as_str())
+            .collect()
+    }
+
+    /// The term closest to the word, if any is close enough.
+    pub fn closest(&self, word: &str) -> Option<&str> {
+        self.close_terms(word).into_iter().next()
+    }
+
+    /// Rank the replacements of a misspelled word.
+    ///
+    /// Terms of the vocabulary close to the word by edit distance are put first,
+    /// ordered by distance and number of occurrences, followed by the remaining
+    /// replacements in their original order.
+    pub fn rank(&self, word: &str, replacements: &mut Vec<String>) {
+        let terms = self.close_terms(word);
+        if terms.is_empty() {
+            return;
+        }
+        let mut ranked = terms
+            .into_iter()
+            .take(MAX_TERMS)
+            .map(str::to_owned)
+            .collect::<Vec<String>>();
+        for replacement in replacements.drain(..) {
+            if !ranked.contains(&replacement) {
+                ranked.push(replacement);
+            }
+        }
+        *replacements = ranked;
+    }
+
+    /// Rank the replacements of all suggestions of dictionary based checkers.
+    pub fn rank_all<'s>(&self, suggestions: &mut SuggestionSet<'s>) {
+        if self.is_empty() {
+            return;
+        }
+        for (_origin, suggestions) in suggestions.iter_mut() {
+            for suggestion in
+                suggestions
+                    .iter_mut()
+                    .filter(|suggestion| match suggestion.detector {
+                        Detector::Hunspell | Detector::Affix => true,
+                        _ => false,
+                    })
+            {
+                let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+                self.rank(word.as_str(), &mut suggestion.replacements);
+            }
+        }
+    }
+}
+
+/// Edit distance of two words in characters, case sensitive.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    distance(
+        &a.chars().collect::<Vec<char>>(),
+        &b.chars().collect::<Vec<char>>(),
+    )
+}
+
+/// Edit distance of two words, where transposing two adjacent
+/// characters counts as a single edit.
+fn distance(a: &[char], b: &[char]) -> usize {
+    let mut matrix = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=b.len() {
+        matrix[0][j] = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut d = (matrix[i - 1][j] + 1)
+                .min(matrix[i][j - 1] + 1)
+                .min(matrix[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d = d.min(matrix[i - 2][j - 2] + 1);
+            }
+            matrix[i][j] = d;
+        }
+    }
+    matrix[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn edit_distance() {
+        assert_eq!(distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(distance(&chars("teh"), &chars("the")), 1);
+        assert_eq!(distance(&chars(""), &chars("abc")), 3);
+        assert_eq!(distance(&chars("same"), &chars("same")), 0);
+    }
+
+    #[test]
+    fn identifiers() {
+        let mut vocabulary = Vocabulary::new();
+        vocabulary.add_identifier("fn");
+        vocabulary.add_identifier("struct");
+        vocabulary.add_identifier("r#match_me");
+        vocabulary.add_identifier("SuggestionSet");
+        assert_eq!(vocabulary.counts.len(), 2);
+        assert!(vocabulary.counts.contains_key("match_me"));
+    }
+
+    #[test]
+    fn rank() {
+        let mut vocabulary = Vocabulary::new();
+        vocabulary.add_identifier("SuggestionSet");
+        vocabulary.add_identifier("Suggestion");
+        vocabulary.add_identifier("Suggestion");
+        vocabulary.add_identifier("Documentation");
+
+        let mut replacements = vec!["Suggestions Set".to_owned(), "Suggestion".to_owned()];
+        vocabulary.rank("SugestionSet", &mut replacements);
+        assert_eq!(
+            replacements,
+            vec![
+                "SuggestionSet".to_owned(),
+                "Suggestions Set".to_owned(),
+                "Suggestion".to_owned()
+            ]
+        );
+
+        // the more frequent term wins on equal distance
+        let mut other = Vocabulary::new();
+        other.add("Suggestion");
+        vocabulary.join(other);
+        let mut replacements = vec!["Suggestive".to_owned()];
+        vocabulary.rank("Sugestion", &mut replacements);
+        assert_eq!(replacements[0], "Suggestion");
+        assert_eq!(replacements.len(), 2);
+
+        // nothing close, nothing changes
+        let mut replacements = vec!["the".to_owned()];
+        vocabulary.rank("teh", &mut replacements);
+        assert_eq!(replacements, vec!["the".to_owned()]);
+    }
+}
@@ -0,0 +1,167 @@
+//! Replay recorded keys with `fix --keys-from` on a copy of the `demo/` crate.
+//!
+//! Only the rustdoc checker runs, it needs no dictionaries and finds the
+//! summaries in all kinds of doc literals of `demo/src/lib.rs`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Copy `src` into `dst` recursively, leaving out build artifacts.
+fn copy_dir(src: &Path, dst: &Path) {
+    fs::create_dir_all(dst).unwrap();
+    for entry in fs::read_dir(src).unwrap() {
+        let entry = entry.unwrap();
+        let path = entry.path();
+        if entry.file_name() == "target" {
+            continue;
+        }
+        if path.is_dir() {
+            copy_dir(&path, &dst.join(entry.file_name()));
+        } else {
+            fs::copy(&path, dst.join(entry.file_name())).unwrap();
+        }
+    }
+}
+
+/// A copy of the demo crate in a directory of its own, named after the test.
+fn demo(name: &str) -> PathBuf {
+    let pid = std::process::id();
+    let dir = std::env::temp_dir().join(format!("spellcheck-demo-{}-{}", name, pid));
+    let _ = fs::remove_dir_all(&dir);
+    copy_dir(&Path::new(env!("CARGO_MANIFEST_DIR")).join("demo"), &dir);
+    fs::write(dir.join("rustdoc.toml"), "[rustdoc]\n").unwrap();
+    dir
+}
+
+/// Run `cargo spellcheck fix` on the library of the demo crate in `dir`,
+/// replaying the keys of `script`.
+fn fix(dir: &Path, script: &str, args: &[&str]) -> Output {
+    fs::write(dir.join("keys.txt"), script).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-spellcheck"))
+        .current_dir(dir)
+        // the session is kept in the `target` directory of the copy
+        .env_remove("CARGO_TARGET_DIR")
+        .args(&[
+            "fix",
+            "--cfg=rustdoc.toml",
+            "--checkers=rustdoc",
+            "--keys-from=keys.txt",
+        ])
+        .args(args)
+        .arg("src/lib.rs")
+        .output()
+        .expect("Must be able to run cargo-spellcheck");
+    eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+/// The bandaids accepted in the session stored in `dir`, as start, end and
+/// replacement.
+fn accepted(dir: &Path) -> Vec<([i64; 2], [i64; 2], String)> {
+    let session = fs::read_to_string(dir.join("target/spellcheck/session.toml"))
+        .expect("Must keep the session");
+    let session = session
+        .parse::<toml::Value>()
+        .expect("Session must be valid toml");
+    let position = |value: &toml::Value| {
+        let value = value.as_array().unwrap();
+        [
+            value[0].as_integer().unwrap(),
+            value[1].as_integer().unwrap(),
+        ]
+    };
+    session["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|file| file["decisions"].as_array().unwrap().iter())
+        .flat_map(|decision| decision["bandaids"].as_array().unwrap().iter())
+        .map(|bandaid| {
+            (
+                position(&bandaid["start"]),
+                position(&bandaid["end"]),
+                bandaid["replacement"].as_str().unwrap().to_owned(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn quit_and_resume() {
+    let dir = demo("resume");
+    let lib = dir.join("src/lib.rs");
+    let original = fs::read_to_string(&lib).unwrap();
+
+    // accept, skip, accept, a custom replacement, then quit
+    let output = fix(
+        &dir,
+        r#"
+y
+n
+y
+e "Dodge" <backspace> "es" <enter>
+q
+"#,
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(130));
+    assert_eq!(fs::read_to_string(&lib).unwrap(), original);
+    assert_eq!(
+        accepted(&dir),
+        vec![
+            ([4, 4], [4, 7], "Picks".to_owned()),
+            ([14, 12], [14, 15], "Picks".to_owned()),
+            ([19, 11], [19, 14], "Dodges".to_owned()),
+        ]
+    );
+
+    // a plain `fix` does not discard the decisions
+    let output = fix(&dir, "", &[]);
+    assert!(!output.status.success());
+    assert_eq!(fs::read_to_string(&lib).unwrap(), original);
+
+    // only the last finding is left to decide on
+    let output = fix(&dir, "y", &["--resume"]);
+    assert!(output.status.success());
+    let expected = original
+        .replacen("/// Pick option a", "/// Picks option a", 1)
+        .replacen("r##\"Pick option c", "r##\"Picks option c", 1)
+        .replacen("r#\"Risk is", "r#\"Dodges is", 1)
+        .replacen("/// Possible ways", "/// Possibles ways", 1);
+    assert_eq!(fs::read_to_string(&lib).unwrap(), expected);
+    assert!(!dir.join("target/spellcheck/session.toml").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dry_run() {
+    let dir = demo("dry-run");
+    let lib = dir.join("src/lib.rs");
+    let original = fs::read_to_string(&lib).unwrap();
+
+    let output = fix(&dir, "n y n n n", &["--dry-run"]);
+    assert!(output.status.success());
+    let patch = String::from_utf8(output.stdout).unwrap();
+    assert!(patch.contains("--- a/src/lib.rs\n+++ b/src/lib.rs\n"));
+    assert!(patch.contains(
+        "-#[doc = \"Pick option b also known as door #2.\"]\n+#[doc = \"Picks option b also known as door #2.\"]\n"
+    ));
+    assert_eq!(
+        patch
+            .lines()
+            .filter(|line| line.starts_with('+') && !line.starts_with("+++"))
+            .count(),
+        1
+    );
+    assert_eq!(fs::read_to_string(&lib).unwrap(), original);
+    assert!(!dir.join("target/spellcheck/session.toml").exists());
+
+    // the script ends before all findings are reviewed
+    let output = fix(&dir, "y", &[]);
+    assert!(!output.status.success());
+    assert_eq!(fs::read_to_string(&lib).unwrap(), original);
+
+    fs::remove_dir_all(&dir).unwrap();
+}